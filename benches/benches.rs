@@ -44,6 +44,12 @@ mod bench {
                 grid.binary_tree(None)
             });
         }
+
+        #[bench]
+        fn regenerate_100_x_100(b: &mut Bencher) {
+            let mut grid = minotaur::Grid::new(100, 100);
+            b.iter(|| grid.binary_tree(None));
+        }
     }
 
     mod aldous_broder {
@@ -125,4 +131,144 @@ mod bench {
             });
         }
     }
+
+    mod ellers {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.ellers(None)
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.ellers(None)
+            });
+        }
+    }
+
+    mod kruskals {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.kruskals(None)
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.kruskals(None)
+            });
+        }
+    }
+
+    mod recursive_division {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.recursive_division(None)
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.recursive_division(None)
+            });
+        }
+    }
+
+    mod prims {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.prims(None)
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.prims(None)
+            });
+        }
+    }
+
+    mod growing_tree {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.growing_tree(None, minotaur::GrowingTreeStrategy::Mixed(0.5))
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.growing_tree(None, minotaur::GrowingTreeStrategy::Mixed(0.5))
+            });
+        }
+    }
+
+    mod weave {
+        use super::*;
+
+        #[bench]
+        fn generate_10_x_10(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(10, 10);
+                grid.weave(None, 0.5)
+            });
+        }
+
+        #[bench]
+        fn generate_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.weave(None, 0.5)
+            });
+        }
+    }
+
+    mod packed_grid {
+        use super::*;
+
+        #[bench]
+        fn ellers_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::PackedGrid::new(100, 100);
+                grid.ellers(None)
+            });
+        }
+
+        #[bench]
+        fn grid_ellers_100_x_100(b: &mut Bencher) {
+            b.iter(|| {
+                let mut grid = minotaur::Grid::new(100, 100);
+                grid.ellers(None)
+            });
+        }
+    }
 }
@@ -0,0 +1,65 @@
+// Generates a maze too tall to hold in memory with Grid::ellers_streaming,
+// writing it out to a PNG image a row of pixels at a time.
+//
+//     cargo run --release --example ellers_png -- [width] [height] [output.png]
+
+use minotaur::{Cell, Grid};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+const CELL_SIZE: usize = 4;
+const WALL: u8 = 0;
+const PASSAGE: u8 = 255;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let width: usize = args.next().map_or(Ok(200), |arg| arg.parse())?;
+    let height: usize = args.next().map_or(Ok(20_000), |arg| arg.parse())?;
+    let output = args.next().unwrap_or_else(|| "maze.png".to_string());
+
+    // Each cell is CELL_SIZE pixels across, including the wall on its north and west sides.
+    // The walls on the east and south edges of the maze add one more pixel
+    let image_width = width * CELL_SIZE + 1;
+    let image_height = height * CELL_SIZE + 1;
+
+    let file = BufWriter::new(File::create(&output)?);
+    let mut encoder = png::Encoder::new(file, image_width as u32, image_height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer();
+
+    let mut pixels = vec![WALL; image_width];
+    let mut result = Ok(());
+    Grid::ellers_streaming(width, height, None, |row| {
+        if result.is_err() {
+            return;
+        }
+        for line in 0..CELL_SIZE {
+            for (col, cell) in row.iter().enumerate() {
+                let x = col * CELL_SIZE;
+                let open = if line == 0 {
+                    cell.contains(Cell::NORTH)
+                } else {
+                    cell.contains(Cell::WEST)
+                };
+                // The corner is always a wall, then the north wall along the top line,
+                // or the west wall then the inside of the cell on the lines below it
+                pixels[x] = if line > 0 && open { PASSAGE } else { WALL };
+                let inside = if line == 0 && !open { WALL } else { PASSAGE };
+                for pixel in &mut pixels[x + 1..x + CELL_SIZE] {
+                    *pixel = inside;
+                }
+            }
+            result = stream.write_all(&pixels);
+        }
+    });
+    result?;
+
+    // The south wall along the bottom
+    stream.write_all(&vec![WALL; image_width])?;
+    stream.finish()?;
+    println!("Saved a {}x{} maze to {}", width, height, output);
+    Ok(())
+}
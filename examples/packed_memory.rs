@@ -0,0 +1,38 @@
+// Compares the memory a maze takes as a Grid and as a PackedGrid,
+// generating the same maze both ways with Eller's algorithm.
+//
+//     cargo run --release --example packed_memory -- [width] [height]
+
+use minotaur::{Cell, Grid, PackedGrid};
+
+use std::time::Instant;
+
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / 1_000_000.0
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let width: usize = args.next().map_or(Ok(10_000), |arg| arg.parse())?;
+    let height: usize = args.next().map_or(Ok(10_000), |arg| arg.parse())?;
+
+    let start = Instant::now();
+    let mut packed = PackedGrid::new(width, height);
+    packed.ellers(Some(1));
+    println!(
+        "PackedGrid: {:.1}MB, generated in {:.2?}",
+        megabytes(packed.as_bytes().len()),
+        start.elapsed()
+    );
+    drop(packed);
+
+    let start = Instant::now();
+    let mut grid = Grid::new(width, height);
+    grid.ellers(Some(1));
+    println!(
+        "Grid:       {:.1}MB, generated in {:.2?}",
+        megabytes(grid.cells.len() * std::mem::size_of::<Cell>()),
+        start.elapsed()
+    );
+    Ok(())
+}
@@ -0,0 +1,739 @@
+use crate::maze::{self, Maze};
+use crate::{Cell, Grid};
+
+use image::GrayImage;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_pcg::Lcg64Xsh32;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// How many mazes generate_with_target_length tries before giving up.
+const TARGET_LENGTH_TRIES: u64 = 1000;
+
+/*
+Algorithm names one of the maze generating algorithms,
+so that it can be picked at runtime.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    BinaryTree,
+    Sidewinder,
+    AldousBroder,
+    Wilsons,
+    HuntAndKill,
+    RecursiveBacktracker,
+    Ellers,
+    Kruskals,
+    RecursiveDivision,
+    Prims,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 10] = [
+        Algorithm::BinaryTree,
+        Algorithm::Sidewinder,
+        Algorithm::AldousBroder,
+        Algorithm::Wilsons,
+        Algorithm::HuntAndKill,
+        Algorithm::RecursiveBacktracker,
+        Algorithm::Ellers,
+        Algorithm::Kruskals,
+        Algorithm::RecursiveDivision,
+        Algorithm::Prims,
+    ];
+
+    /// The names of all the algorithms, as accepted by from_str.
+    pub fn variants() -> [&'static str; 10] {
+        let mut names = [""; 10];
+        for (name, algorithm) in names.iter_mut().zip(Algorithm::ALL.iter()) {
+            *name = algorithm.name();
+        }
+        names
+    }
+
+    /// description sums up in a line what the mazes the algorithm makes look like,
+    /// e.g. to help choose one.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Algorithm::BinaryTree => {
+                "Fast, but with open corridors along the north and east edges and a diagonal grain"
+            }
+            Algorithm::Sidewinder => {
+                "Long horizontal corridors, with the whole top row open and short passages north"
+            }
+            Algorithm::AldousBroder => "Every maze equally likely, but slow to finish on big grids",
+            Algorithm::Wilsons => "Every maze equally likely, and much faster than AldousBroder",
+            Algorithm::HuntAndKill => {
+                "Long winding passages with few dead ends, like RecursiveBacktracker"
+            }
+            Algorithm::RecursiveBacktracker => {
+                "Long winding passages with few dead ends, and a very long solution"
+            }
+            Algorithm::Ellers => {
+                "Row by row in little memory, with more horizontal passages than vertical"
+            }
+            Algorithm::Kruskals => "Lots of short dead ends, fairly even in every direction",
+            Algorithm::RecursiveDivision => {
+                "Long straight walls dividing the maze into boxes, like a floor plan"
+            }
+            Algorithm::Prims => "Lots of short dead ends, radiating out from where it starts",
+        }
+    }
+
+    /// is_uniform is whether every possible maze is equally likely to come out, so the
+    /// algorithm has no bias towards any texture of its own.
+    pub fn is_uniform(&self) -> bool {
+        matches!(self, Algorithm::AldousBroder | Algorithm::Wilsons)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::BinaryTree => "BinaryTree",
+            Algorithm::Sidewinder => "Sidewinder",
+            Algorithm::AldousBroder => "AldousBroder",
+            Algorithm::Wilsons => "Wilsons",
+            Algorithm::HuntAndKill => "HuntAndKill",
+            Algorithm::RecursiveBacktracker => "RecursiveBacktracker",
+            Algorithm::Ellers => "Ellers",
+            Algorithm::Kruskals => "Kruskals",
+            Algorithm::RecursiveDivision => "RecursiveDivision",
+            Algorithm::Prims => "Prims",
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parses an algorithm by name, ignoring case.
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Algorithm, String> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "valid values: {}",
+                    Algorithm::variants().join(", ").to_lowercase()
+                )
+            })
+    }
+}
+
+impl Grid {
+    /// generate populates the maze with the given algorithm.
+    pub fn generate(&mut self, algorithm: Algorithm, seed: Option<u64>) {
+        self.generate_with_rng(algorithm, &mut Grid::get_rng(seed));
+    }
+
+    /// generate_from_str_seed populates the maze like generate, seeded by text that's
+    /// easier to remember and share than a number, e.g. "correct horse battery staple".
+    /// It's the same as generate with Grid::seed_from_str(seed).
+    pub fn generate_from_str_seed(&mut self, algorithm: Algorithm, seed: &str) {
+        self.generate(algorithm, Some(Grid::seed_from_str(seed)));
+    }
+
+    /// seed_from_str hashes text into a seed with 64 bit FNV-1a. Unlike the hashers
+    /// in std, it never changes, so the same text makes the same maze on every
+    /// platform and in every version.
+    pub fn seed_from_str(seed: &str) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        seed.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+
+    /// daily creates the maze of the day: a width x height maze, generated with algorithm,
+    /// that's the same for everyone who makes one on the same day in UTC, like a daily
+    /// puzzle. It's daily_for_date with today's date.
+    pub fn daily(width: usize, height: usize, algorithm: Algorithm) -> Grid {
+        Grid::daily_for_date(width, height, algorithm, utc_date(SystemTime::now()))
+    }
+
+    /// daily_for_date creates the maze of the day for date, given as (year, month, day),
+    /// e.g. to look back at an earlier one. The seed is seed_from_str of the date written
+    /// as "YYYY-MM-DD", such as "2024-03-09", so the same date always gives the same maze.
+    pub fn daily_for_date(
+        width: usize,
+        height: usize,
+        algorithm: Algorithm,
+        (year, month, day): (i32, u32, u32),
+    ) -> Grid {
+        let date = format!("{:04}-{:02}-{:02}", year, month, day);
+        let mut grid = Grid::new(width, height);
+        grid.generate_from_str_seed(algorithm, &date);
+        grid
+    }
+
+    /// generate_with_rng populates the maze with the given algorithm,
+    /// drawing its random numbers from rng.
+    pub fn generate_with_rng<R: Rng>(&mut self, algorithm: Algorithm, rng: &mut R) {
+        match algorithm {
+            Algorithm::BinaryTree => self.binary_tree_with_rng(rng),
+            Algorithm::Sidewinder => self.sidewinder_with_rng(rng),
+            Algorithm::AldousBroder => self.aldous_broder_with_rng(rng),
+            Algorithm::Wilsons => self.wilsons_with_rng(rng),
+            Algorithm::HuntAndKill => self.hunt_and_kill_with_rng(rng),
+            Algorithm::RecursiveBacktracker => self.recursive_backtracker_with_rng(rng),
+            Algorithm::Ellers => self.ellers_with_rng(rng),
+            Algorithm::Kruskals => self.kruskals_with_rng(rng),
+            Algorithm::RecursiveDivision => self.recursive_division_with_rng(rng),
+            Algorithm::Prims => self.prims_with_rng(rng),
+        }
+        debug_assert_eq!(
+            Ok(()),
+            self.check_symmetry(),
+            "{:?} left links that its neighbors don't mirror",
+            algorithm
+        );
+    }
+
+    /// generate_with_observer populates the maze like generate, calling observer with the
+    /// grid after every new link, e.g. to record each step as a frame of an animation.
+    /// The maze ends up the same as generate would make it with the same seed.
+    ///
+    /// Only AldousBroder, Wilsons, HuntAndKill, RecursiveBacktracker and Prims can be
+    /// watched step by step. For the other algorithms, observer is called once at the end.
+    pub fn generate_with_observer(
+        &mut self,
+        algorithm: Algorithm,
+        seed: Option<u64>,
+        observer: &mut dyn FnMut(&Grid),
+    ) {
+        let generate: Generator<Observed<'_>> = match algorithm {
+            Algorithm::AldousBroder => maze::aldous_broder,
+            Algorithm::Wilsons => maze::wilsons,
+            Algorithm::HuntAndKill => maze::hunt_and_kill,
+            Algorithm::RecursiveBacktracker => maze::recursive_backtracker,
+            Algorithm::Prims => maze::prims,
+            _ => {
+                self.generate(algorithm, seed);
+                observer(self);
+                return;
+            }
+        };
+
+        self.reset();
+        let mut rng = Grid::get_rng(seed);
+        generate(
+            &mut Observed {
+                grid: self,
+                observer,
+            },
+            &mut rng,
+        );
+    }
+
+    /// generate_weighted_by_image populates the maze like generate, but steers it with
+    /// a grayscale picture stretched over the grid, so that the texture of the maze
+    /// follows the picture. Moving into a cell costs 256 minus the brightness of the
+    /// pixel under its center, like the weights of set_weight, and the walks pick each
+    /// next cell with odds inversely proportional to its cost. So white cells are picked
+    /// 256 times as often as black ones, and passages flow along the bright parts.
+    ///
+    /// Only AldousBroder, Wilsons, HuntAndKill, RecursiveBacktracker and Prims walk from
+    /// cell to cell. The other algorithms ignore image. The grid's own weights aren't changed.
+    ///
+    /// Panics if image has no pixels.
+    pub fn generate_weighted_by_image(
+        &mut self,
+        algorithm: Algorithm,
+        image: &GrayImage,
+        seed: Option<u64>,
+    ) {
+        let (image_width, image_height) = image.dimensions();
+        assert!(
+            image_width > 0 && image_height > 0,
+            "a {}x{} image has no pixels to steer the maze with",
+            image_width,
+            image_height
+        );
+        let costs = (0..self.cells.len())
+            .map(|i| {
+                let (row, col) = (i / self.width, i % self.width);
+                let x = (2 * col + 1) * image_width as usize / (2 * self.width);
+                let y = (2 * row + 1) * image_height as usize / (2 * self.height);
+                256 - u32::from(image.get_pixel(x as u32, y as u32)[0])
+            })
+            .collect();
+
+        let generate: Generator<Steered<'_>> = match algorithm {
+            Algorithm::AldousBroder => maze::aldous_broder,
+            Algorithm::Wilsons => maze::wilsons,
+            Algorithm::HuntAndKill => maze::hunt_and_kill,
+            Algorithm::RecursiveBacktracker => maze::recursive_backtracker,
+            Algorithm::Prims => maze::prims,
+            _ => return self.generate(algorithm, seed),
+        };
+        self.reset();
+        generate(&mut Steered { grid: self, costs }, &mut Grid::get_rng(seed));
+        debug_assert_eq!(
+            Ok(()),
+            self.check_symmetry(),
+            "{:?} left links that its neighbors don't mirror",
+            algorithm
+        );
+    }
+
+    /// generate_batch creates one width x height maze per seed, in the same order as seeds.
+    /// Each maze is exactly what generate would make with that seed.
+    ///
+    /// With the "parallel" feature, the mazes are generated on all cores with rayon.
+    pub fn generate_batch(
+        width: usize,
+        height: usize,
+        algorithm: Algorithm,
+        seeds: &[u64],
+    ) -> Vec<Grid> {
+        let generate = |&seed: &u64| {
+            let mut grid = Grid::new(width, height);
+            grid.generate(algorithm, Some(seed));
+            grid
+        };
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            seeds.par_iter().map(generate).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            seeds.iter().map(generate).collect()
+        }
+    }
+
+    /// generate_with_target_length creates a width x height maze whose solution from start
+    /// to goal is between min_len and max_len steps long, inclusive, for puzzles of
+    /// a given difficulty. Like every perfect maze, it has exactly one solution.
+    ///
+    /// Mazes are generated with algorithm and seed, seed + 1, seed + 2 and so on until
+    /// one fits, so the same arguments always give the same maze. Without a seed, each
+    /// try is random. Gives up with None after 1000 tries, or straight away if start
+    /// or goal isn't in the grid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_with_target_length(
+        width: usize,
+        height: usize,
+        seed: Option<u64>,
+        algorithm: Algorithm,
+        min_len: usize,
+        max_len: usize,
+        start: usize,
+        goal: usize,
+    ) -> Option<Grid> {
+        let mut grid = Grid::new(width, height);
+        for tries in 0..TARGET_LENGTH_TRIES {
+            grid.generate(algorithm, seed.map(|seed| seed.wrapping_add(tries)));
+            let steps = grid.solve(start, goal)?.len() - 1;
+            if (min_len..=max_len).contains(&steps) {
+                return Some(grid);
+            }
+        }
+        None
+    }
+}
+
+// The (year, month, day) of time in UTC, counting days since 1970-01-01
+// into the proleptic Gregorian calendar, which repeats every 400 years.
+fn utc_date(time: SystemTime) -> (i32, u32, u32) {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    };
+    // Counting from 0000-03-01 instead puts the leap day at the end of each year
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month as u32, day as u32)
+}
+
+type Generator<M> = fn(&mut M, &mut Lcg64Xsh32);
+
+// Observed is a Grid that calls observer every time it gets a new link.
+struct Observed<'a> {
+    grid: &'a mut Grid,
+    observer: &'a mut dyn FnMut(&Grid),
+}
+
+// Steered is a Grid whose walks are drawn towards the cells that cost the least to move into.
+struct Steered<'a> {
+    grid: &'a mut Grid,
+    costs: Vec<u32>,
+}
+
+impl Steered<'_> {
+    fn choose_cheap<R: Rng>(&self, candidates: &[usize], rng: &mut R) -> Option<usize> {
+        candidates
+            .choose_weighted(rng, |&i| 1.0 / f64::from(self.costs[i]))
+            .ok()
+            .copied()
+    }
+}
+
+impl Maze for Steered<'_> {
+    type Dir = Cell;
+
+    fn cell_count(&self) -> usize {
+        self.grid.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(Cell, usize)> {
+        Maze::neighbors(&*self.grid, i)
+    }
+
+    fn link(&mut self, a: usize, b: usize) {
+        Maze::link(&mut *self.grid, a, b);
+    }
+
+    fn is_masked(&self, i: usize) -> bool {
+        self.grid.is_masked(i)
+    }
+
+    fn random_neighbor<R: Rng>(&self, i: usize, rng: &mut R) -> Option<usize> {
+        let neighbors: Vec<usize> = self
+            .grid
+            .neighbors(i)
+            .map(|(_, neighbor)| neighbor)
+            .collect();
+        self.choose_cheap(&neighbors, rng)
+    }
+
+    fn choose_next<R: Rng>(&self, _i: usize, candidates: &[usize], rng: &mut R) -> Option<usize> {
+        self.choose_cheap(candidates, rng)
+    }
+}
+
+impl Maze for Observed<'_> {
+    type Dir = Cell;
+
+    fn cell_count(&self) -> usize {
+        self.grid.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(Cell, usize)> {
+        Maze::neighbors(&*self.grid, i)
+    }
+
+    fn link(&mut self, a: usize, b: usize) {
+        Maze::link(&mut *self.grid, a, b);
+        (self.observer)(self.grid);
+    }
+
+    fn is_masked(&self, i: usize) -> bool {
+        self.grid.is_masked(i)
+    }
+
+    fn random_neighbor<R: Rng>(&self, i: usize, rng: &mut R) -> Option<usize> {
+        self.grid.random_neighbor(i, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Mask;
+
+    use rand::rngs::mock::StepRng;
+    use rand::SeedableRng;
+
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_str() {
+        for algorithm in Algorithm::ALL.iter() {
+            let name = algorithm.to_string();
+            assert_eq!(Ok(*algorithm), name.parse());
+            assert_eq!(Ok(*algorithm), name.to_lowercase().parse());
+            assert_eq!(Ok(*algorithm), name.to_uppercase().parse());
+        }
+        assert!("Minotaur".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn test_description() {
+        let uniform: Vec<Algorithm> = Algorithm::ALL
+            .iter()
+            .copied()
+            .filter(Algorithm::is_uniform)
+            .collect();
+        assert_eq!(vec![Algorithm::AldousBroder, Algorithm::Wilsons], uniform);
+
+        let mut descriptions = HashSet::new();
+        for algorithm in Algorithm::ALL.iter() {
+            assert!(descriptions.insert(algorithm.description()));
+        }
+    }
+
+    #[test]
+    fn test_generate_with_target_length() {
+        let steps = |grid: &Grid| grid.solve(0, 99).unwrap().len() - 1;
+        for &(min_len, max_len) in [(18, 18), (24, 26), (30, 32)].iter() {
+            let grid = Grid::generate_with_target_length(
+                10,
+                10,
+                Some(1),
+                Algorithm::Kruskals,
+                min_len,
+                max_len,
+                0,
+                99,
+            )
+            .unwrap();
+            assert!(grid.is_perfect());
+            assert!((min_len..=max_len).contains(&steps(&grid)));
+        }
+
+        // Shorter than the straightest path there is, and cells that aren't in the grid
+        let generate = |min_len, max_len, goal| {
+            Grid::generate_with_target_length(
+                10,
+                10,
+                None,
+                Algorithm::Prims,
+                min_len,
+                max_len,
+                0,
+                goal,
+            )
+        };
+        assert_eq!(None, generate(0, 17, 99));
+        assert_eq!(None, generate(0, 1000, 100));
+    }
+
+    #[test]
+    fn test_daily() {
+        let date = (2024, 3, 9);
+        let grid = Grid::daily_for_date(12, 8, Algorithm::Wilsons, date);
+        assert!(grid.is_perfect());
+        assert_eq!(grid, Grid::daily_for_date(12, 8, Algorithm::Wilsons, date));
+
+        let mut expected = Grid::new(12, 8);
+        expected.generate_from_str_seed(Algorithm::Wilsons, "2024-03-09");
+        assert_eq!(expected, grid);
+        assert_ne!(
+            grid,
+            Grid::daily_for_date(12, 8, Algorithm::Wilsons, (2024, 3, 10))
+        );
+
+        let today = Grid::daily(6, 6, Algorithm::Prims);
+        assert!(today.is_perfect());
+    }
+
+    #[test]
+    fn test_utc_date() {
+        let at = |seconds: i64| {
+            let offset = Duration::from_secs(seconds.unsigned_abs());
+            if seconds < 0 {
+                utc_date(UNIX_EPOCH - offset)
+            } else {
+                utc_date(UNIX_EPOCH + offset)
+            }
+        };
+        assert_eq!((1970, 1, 1), at(0));
+        assert_eq!((1970, 1, 1), at(86_399));
+        assert_eq!((1970, 1, 2), at(86_400));
+        assert_eq!((1969, 12, 31), at(-1));
+        // Leap days, and the ends of leap and century years
+        assert_eq!((2000, 2, 29), at(951_782_400));
+        assert_eq!((2024, 2, 29), at(1_709_208_000));
+        assert_eq!((2024, 12, 31), at(1_735_689_599));
+        assert_eq!((2100, 3, 1), at(4_107_542_400));
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut grid = Grid::new(10, 10);
+        let mut expected = Grid::new(10, 10);
+
+        grid.generate(Algorithm::RecursiveBacktracker, Some(12345));
+        expected.recursive_backtracker(Some(12345));
+        assert_eq!(expected, grid);
+
+        grid.generate(Algorithm::Kruskals, Some(12345));
+        expected.kruskals(Some(12345));
+        assert_eq!(expected, grid);
+    }
+
+    #[test]
+    fn test_generate_from_str_seed() {
+        // Published FNV-1a test vectors, so seeds never change between platforms
+        assert_eq!(0xcbf2_9ce4_8422_2325, Grid::seed_from_str(""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, Grid::seed_from_str("a"));
+        assert_eq!(0x8594_4171_f739_67e8, Grid::seed_from_str("foobar"));
+
+        let mut grid = Grid::new(9, 7);
+        let mut expected = Grid::new(9, 7);
+        grid.generate_from_str_seed(Algorithm::Prims, "minotaur");
+        expected.generate(Algorithm::Prims, Some(Grid::seed_from_str("minotaur")));
+        assert_eq!(expected, grid);
+
+        expected.generate_from_str_seed(Algorithm::Prims, "minotaur");
+        assert_eq!(expected, grid);
+        expected.generate_from_str_seed(Algorithm::Prims, "Minotaur");
+        assert_ne!(expected, grid);
+    }
+
+    #[test]
+    fn test_generate_with_rng() {
+        for &algorithm in Algorithm::ALL.iter() {
+            let mut grid = Grid::new(12, 10);
+            let mut expected = Grid::new(12, 10);
+            grid.generate_with_rng(algorithm, &mut Lcg64Xsh32::seed_from_u64(42));
+            expected.generate(algorithm, Some(42));
+            assert_eq!(
+                expected, grid,
+                "{} doesn't match its seeded version",
+                algorithm
+            );
+        }
+
+        // One generator can be shared between mazes
+        let mut rng = Lcg64Xsh32::seed_from_u64(42);
+        let mut grid = Grid::new(12, 10);
+        let mut again = Grid::new(12, 10);
+        grid.kruskals_with_rng(&mut rng);
+        again.kruskals_with_rng(&mut rng);
+        assert_ne!(grid, again);
+
+        // Even one that isn't random at all still makes a perfect maze
+        grid.kruskals_with_rng(&mut StepRng::new(0, 1 << 40));
+        let links: usize = (0..120).map(|i| grid.links(i).count()).sum();
+        assert_eq!(2 * 119, links);
+        assert!(grid.distances(0).iter().all(|distance| distance.is_some()));
+    }
+
+    #[test]
+    fn test_generate_weighted_by_image() {
+        // Alternating white and black stripes, two pixels to each row of cells
+        let image = GrayImage::from_fn(32, 32, |_, y| {
+            image::Luma([if y / 2 % 2 == 0 { 255 } else { 0 }])
+        });
+        // How many of the walls between the cells of the white rows have been removed
+        let along_white = |grid: &Grid| {
+            (0..16)
+                .step_by(2)
+                .flat_map(|row| (0..15).map(move |col| row * 16 + col))
+                .filter(|&i| grid.cells[i].contains(Cell::EAST))
+                .count()
+        };
+
+        for &algorithm in Algorithm::ALL.iter() {
+            let mut grid = Grid::new(16, 16);
+            let mut plain = Grid::new(16, 16);
+            grid.generate_weighted_by_image(algorithm, &image, Some(3));
+            plain.generate(algorithm, Some(3));
+            assert!(grid.is_connected(), "{}", algorithm);
+            assert_eq!(255, grid.edges().len(), "{}", algorithm);
+
+            // The walks stay in the white rows, running along them, while the black
+            // rows are mostly crossed straight through. Prim's jumps around, so it's
+            // only pulled a little.
+            let (steered, unsteered) = (along_white(&grid), along_white(&plain));
+            match algorithm {
+                Algorithm::AldousBroder
+                | Algorithm::Wilsons
+                | Algorithm::HuntAndKill
+                | Algorithm::RecursiveBacktracker => {
+                    assert!(steered > 110 && unsteered < 80, "{}", algorithm)
+                }
+                Algorithm::Prims => assert!(steered > unsteered),
+                _ => assert_eq!(
+                    plain, grid,
+                    "{} doesn't walk, so ignores the image",
+                    algorithm
+                ),
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a 0x0 image has no pixels to steer the maze with")]
+    fn test_generate_weighted_by_image_empty() {
+        let mut grid = Grid::new(4, 4);
+        grid.generate_weighted_by_image(Algorithm::Wilsons, &GrayImage::new(0, 0), Some(1));
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let seeds = [1, 2, 3, 1];
+        let grids = Grid::generate_batch(8, 6, Algorithm::AldousBroder, &seeds);
+
+        assert_eq!(seeds.len(), grids.len());
+        for (grid, &seed) in grids.iter().zip(seeds.iter()) {
+            let mut expected = Grid::new(8, 6);
+            expected.aldous_broder(Some(seed));
+            assert_eq!(&expected, grid);
+        }
+        assert_eq!(grids[0], grids[3]);
+        assert_ne!(grids[0], grids[1]);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let mut mask = Mask::new(12, 10);
+        mask.set(0, false);
+        mask.set(57, false);
+
+        for &algorithm in Algorithm::ALL.iter() {
+            let mut grid = Grid::new(12, 10);
+            let mut again = Grid::new(12, 10);
+            grid.generate(algorithm, Some(42));
+            again.generate(algorithm, Some(42));
+            assert_eq!(grid, again, "{} is not reproducible", algorithm);
+
+            let mut grid = Grid::with_mask(12, 10, mask.clone());
+            let mut again = Grid::with_mask(12, 10, mask.clone());
+            grid.generate(algorithm, Some(42));
+            again.generate(algorithm, Some(42));
+            assert_eq!(grid, again, "{} is not reproducible", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_generate_with_observer() {
+        for &algorithm in [Algorithm::RecursiveBacktracker, Algorithm::AldousBroder].iter() {
+            let mut links = Vec::new();
+            let mut grid = Grid::new(6, 5);
+            grid.generate_with_observer(algorithm, Some(9), &mut |grid| {
+                let count: u32 = grid.cells.iter().map(|cell| cell.bits().count_ones()).sum();
+                links.push(count);
+            });
+
+            // One call per passage, each with one more passage than the last
+            let expected = (1..30).map(|n| 2 * n).collect::<Vec<u32>>();
+            assert_eq!(expected, links);
+
+            let mut expected = Grid::new(6, 5);
+            expected.generate(algorithm, Some(9));
+            assert_eq!(expected, grid);
+        }
+
+        let mut calls = 0;
+        let mut grid = Grid::new(6, 5);
+        grid.generate_with_observer(Algorithm::Kruskals, Some(9), &mut |_| calls += 1);
+        assert_eq!(1, calls);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{fill_rect, Grid};
+
+use image::RgbImage;
+
+use std::collections::HashMap;
+
+/*
+Annotation marks a cell of a Grid, such as where a game places an item in the maze.
+The image renderers draw a filled square of its color in the middle of the cell.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Annotation {
+    /// Where the maze is entered, in green.
+    Start,
+    /// Where the maze is headed, in red.
+    Goal,
+    /// Anything else, in the given color.
+    Marker(image::Rgb<u8>),
+}
+
+impl Annotation {
+    /// color is what the renderers fill the annotated cell's marker with.
+    pub fn color(&self) -> image::Rgb<u8> {
+        match self {
+            Annotation::Start => image::Rgb([0, 160, 0]),
+            Annotation::Goal => image::Rgb([220, 0, 0]),
+            Annotation::Marker(color) => *color,
+        }
+    }
+}
+
+impl Grid {
+    /// annotate marks cell i with annotation, replacing any it had already.
+    /// Annotations don't change the maze itself, aren't serialized with it,
+    /// and stay in place when it's regenerated.
+    /// Panics if i is out of bounds.
+    pub fn annotate(&mut self, i: usize, annotation: Annotation) {
+        assert!(
+            i < self.cells.len(),
+            "cell {} is out of bounds for a maze of {} cells",
+            i,
+            self.cells.len()
+        );
+        self.annotations.insert(i, annotation);
+    }
+
+    /// remove_annotation takes the annotation off cell i, returning it if there was one.
+    pub fn remove_annotation(&mut self, i: usize) -> Option<Annotation> {
+        self.annotations.remove(&i)
+    }
+
+    /// annotations is every annotated cell, with its annotation.
+    pub fn annotations(&self) -> &HashMap<usize, Annotation> {
+        &self.annotations
+    }
+
+    // Fills a square half the size of the space inside each annotated cell,
+    // in the middle of it, with the annotation's color.
+    pub(crate) fn draw_annotations(
+        &self,
+        image: &mut RgbImage,
+        cell_width: usize,
+        cell_height: usize,
+        wall_size: usize,
+    ) {
+        let inside_width = cell_width.saturating_sub(wall_size);
+        let inside_height = cell_height.saturating_sub(wall_size);
+        let (width, height) = ((inside_width / 2).max(1), (inside_height / 2).max(1));
+        for (&i, annotation) in self.annotations.iter() {
+            let x = (i % self.width) * cell_width + wall_size + (inside_width - width) / 2;
+            let y = (i / self.width) * cell_height + wall_size + (inside_height - height) / 2;
+            fill_rect(image, x, y, width, height, annotation.color());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let mut grid = Grid::new(4, 3);
+        grid.recursive_backtracker(Some(1));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let blue = image::Rgb([0, 0, 255]);
+        let plain = grid.to_image(10, 1, background, wall);
+
+        grid.annotate(0, Annotation::Start);
+        grid.annotate(11, Annotation::Goal);
+        grid.annotate(6, Annotation::Marker(blue));
+        assert_eq!(3, grid.annotations().len());
+        let image = grid.to_image(10, 1, background, wall);
+
+        // The middle of each annotated cell is filled, and everything else is as it was
+        let center = |i: usize| ((i % 4) as u32 * 10 + 5, (i / 4) as u32 * 10 + 5);
+        let colors = [
+            (0, Annotation::Start.color()),
+            (11, Annotation::Goal.color()),
+            (6, blue),
+        ];
+        for &(i, color) in colors.iter() {
+            let (x, y) = center(i);
+            assert_eq!(&color, image.get_pixel(x, y));
+            assert_eq!(&color, image.get_pixel(x - 2, y - 2));
+            assert_eq!(plain.get_pixel(x - 4, y - 4), image.get_pixel(x - 4, y - 4));
+        }
+        let (x, y) = center(5);
+        assert_eq!(&background, image.get_pixel(x, y));
+
+        // Annotations stay put when the maze changes, until they're removed
+        grid.prims(Some(2));
+        assert_eq!(Some(&Annotation::Goal), grid.annotations().get(&11));
+        assert_eq!(Some(Annotation::Marker(blue)), grid.remove_annotation(6));
+        assert_eq!(None, grid.remove_annotation(6));
+        let (x, y) = center(6);
+        assert_eq!(
+            &background,
+            grid.to_image(10, 1, background, wall).get_pixel(x, y)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cell 12 is out of bounds for a maze of 12 cells")]
+    fn test_annotate_out_of_bounds() {
+        Grid::new(4, 3).annotate(12, Annotation::Goal);
+    }
+}
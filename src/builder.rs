@@ -0,0 +1,108 @@
+use crate::{Algorithm, Grid, Mask};
+
+/*
+GridBuilder creates and populates a Grid in one go:
+
+GridBuilder::new(10, 10)
+    .algorithm(Algorithm::Wilsons)
+    .seed(12345)
+    .build()
+
+Unless told otherwise, it uses AldousBroder with a random seed.
+*/
+#[derive(Debug, Clone)]
+pub struct GridBuilder {
+    width: usize,
+    height: usize,
+    algorithm: Algorithm,
+    seed: Option<u64>,
+    mask: Option<Mask>,
+    wrap_horizontal: bool,
+    wrap_vertical: bool,
+}
+
+impl GridBuilder {
+    pub fn new(width: usize, height: usize) -> GridBuilder {
+        GridBuilder {
+            width,
+            height,
+            algorithm: Algorithm::AldousBroder,
+            seed: None,
+            mask: None,
+            wrap_horizontal: false,
+            wrap_vertical: false,
+        }
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> GridBuilder {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> GridBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Only carve the cells that are on in mask. Its dimensions must match the builder's.
+    pub fn mask(mut self, mask: Mask) -> GridBuilder {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Join the east and west edges of the grid, like Grid::set_wrap_horizontal.
+    pub fn wrap_horizontal(mut self) -> GridBuilder {
+        self.wrap_horizontal = true;
+        self
+    }
+
+    /// Join the north and south edges of the grid, like Grid::set_wrap_vertical.
+    /// Together with wrap_horizontal, the maze is on a torus.
+    pub fn wrap_vertical(mut self) -> GridBuilder {
+        self.wrap_vertical = true;
+        self
+    }
+
+    pub fn build(self) -> Grid {
+        let mut grid = match self.mask {
+            Some(mask) => Grid::with_mask(self.width, self.height, mask),
+            None => Grid::new(self.width, self.height),
+        };
+        grid.set_wrap_horizontal(self.wrap_horizontal);
+        grid.set_wrap_vertical(self.wrap_vertical);
+        grid.generate(self.algorithm, self.seed);
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let grid = GridBuilder::new(8, 6)
+            .algorithm(Algorithm::HuntAndKill)
+            .seed(42)
+            .build();
+
+        let mut expected = Grid::new(8, 6);
+        expected.hunt_and_kill(Some(42));
+        assert_eq!(expected, grid);
+    }
+
+    #[test]
+    fn test_build_masked() {
+        let mut mask = Mask::new(4, 4);
+        mask.set(5, false);
+        let grid = GridBuilder::new(4, 4)
+            .algorithm(Algorithm::RecursiveBacktracker)
+            .mask(mask.clone())
+            .build();
+
+        assert_eq!(Some(&mask), grid.mask());
+        assert!(grid.cells[5].is_empty());
+        assert_eq!(15, grid.distances(0).iter().flatten().count());
+    }
+}
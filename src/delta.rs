@@ -0,0 +1,249 @@
+use crate::draw::draw_line;
+use crate::maze::{self, Maze};
+use crate::Grid;
+
+use image::{ImageBuffer, RgbImage};
+
+/*
+DeltaCell is a single triangle in a DeltaGrid.
+Triangles pointing up have neighbors to the east, west and south,
+and triangles pointing down have neighbors to the east, west and north.
+Like Cell, each flag means there is a passage that way.
+*/
+bitflags! {
+    #[derive(Default)]
+    pub struct DeltaCell: u8 {
+        const NORTH = 0b0001;
+        const SOUTH = 0b0010;
+        const EAST =  0b0100;
+        const WEST =  0b1000;
+    }
+}
+
+const DIRECTIONS: [DeltaCell; 4] = [
+    DeltaCell::NORTH,
+    DeltaCell::SOUTH,
+    DeltaCell::EAST,
+    DeltaCell::WEST,
+];
+
+impl DeltaCell {
+    fn opposite(self) -> DeltaCell {
+        match self {
+            DeltaCell::NORTH => DeltaCell::SOUTH,
+            DeltaCell::SOUTH => DeltaCell::NORTH,
+            DeltaCell::EAST => DeltaCell::WEST,
+            DeltaCell::WEST => DeltaCell::EAST,
+            _ => panic!("{:?} is not a single direction", self),
+        }
+    }
+}
+
+/*
+DeltaGrid is a maze made of triangles, stored row by row like Grid.
+Triangles alternate between pointing up and pointing down along each row,
+starting with one pointing up in the top left corner.
+*/
+#[derive(PartialEq, Clone, Debug)]
+pub struct DeltaGrid {
+    pub cells: Vec<DeltaCell>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl DeltaGrid {
+    pub fn new(width: usize, height: usize) -> DeltaGrid {
+        DeltaGrid {
+            cells: vec![DeltaCell::default(); width * height],
+            width,
+            height,
+        }
+    }
+
+    /// points_up is whether cell i is a triangle pointing up, rather than down.
+    pub fn points_up(&self, i: usize) -> bool {
+        (i / self.width + i % self.width).is_multiple_of(2)
+    }
+
+    /// neighbor finds the index of the cell next to cell i in direction,
+    /// or None if that would be off the edge of the grid, or through the point of the triangle.
+    pub fn neighbor(&self, i: usize, direction: DeltaCell) -> Option<usize> {
+        let (row, col) = ((i / self.width) as isize, (i % self.width) as isize);
+
+        let (row, col) = match direction {
+            DeltaCell::NORTH if !self.points_up(i) => (row - 1, col),
+            DeltaCell::SOUTH if self.points_up(i) => (row + 1, col),
+            DeltaCell::NORTH | DeltaCell::SOUTH => return None,
+            DeltaCell::EAST => (row, col + 1),
+            DeltaCell::WEST => (row, col - 1),
+            _ => panic!("{:?} is not a single direction", direction),
+        };
+
+        if row < 0 || col < 0 || row >= self.height as isize || col >= self.width as isize {
+            None
+        } else {
+            Some(row as usize * self.width + col as usize)
+        }
+    }
+
+    /// recursive_backtracker populates the grid with maze::recursive_backtracker.
+    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::recursive_backtracker(self, &mut Grid::get_rng(seed));
+    }
+
+    /// aldous_broder populates the grid with maze::aldous_broder.
+    pub fn aldous_broder(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::aldous_broder(self, &mut Grid::get_rng(seed));
+    }
+
+    fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = DeltaCell::default();
+        }
+    }
+
+    /// to_image renders the maze with each triangle cell_size pixels wide at its base.
+    pub fn to_image(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let half_width = cell_size as f64 / 2.0;
+        let height = cell_size as f64 * 3f64.sqrt() / 2.0;
+        let margin = wall_size as f64 / 2.0;
+
+        // Neighboring triangles overlap by half their width
+        let image_width = half_width * (self.width + 1) as f64 + wall_size as f64;
+        let image_height = height * self.height as f64 + wall_size as f64;
+        let mut image = ImageBuffer::from_pixel(
+            image_width.ceil() as u32,
+            image_height.ceil() as u32,
+            background_pixel,
+        );
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let (row, col) = (i / self.width, i % self.width);
+            let west = margin + half_width * col as f64;
+            let (east, middle) = (west + 2.0 * half_width, west + half_width);
+            let top = margin + height * row as f64;
+            let bottom = top + height;
+
+            let walls = if self.points_up(i) {
+                [
+                    (DeltaCell::SOUTH, (west, bottom), (east, bottom)),
+                    (DeltaCell::EAST, (middle, top), (east, bottom)),
+                    (DeltaCell::WEST, (west, bottom), (middle, top)),
+                ]
+            } else {
+                [
+                    (DeltaCell::NORTH, (west, top), (east, top)),
+                    (DeltaCell::EAST, (east, top), (middle, bottom)),
+                    (DeltaCell::WEST, (middle, bottom), (west, top)),
+                ]
+            };
+            for &(direction, start, end) in &walls {
+                if !cell.contains(direction) {
+                    draw_line(&mut image, start, end, wall_size as f64, wall_pixel);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+impl Maze for DeltaGrid {
+    type Dir = DeltaCell;
+
+    fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(DeltaCell, usize)> {
+        DIRECTIONS
+            .iter()
+            .filter_map(|&direction| {
+                self.neighbor(i, direction)
+                    .map(|neighbor| (direction, neighbor))
+            })
+            .collect()
+    }
+
+    /// Panics if a and b aren't next to each other.
+    fn link(&mut self, a: usize, b: usize) {
+        let direction = DIRECTIONS
+            .iter()
+            .copied()
+            .find(|&direction| self.neighbor(a, direction) == Some(b))
+            .unwrap_or_else(|| panic!("cells {} and {} are not neighbors", a, b));
+        self.cells[a] |= direction;
+        self.cells[b] |= direction.opposite();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::maze::tests::is_spanning_tree;
+
+    #[test]
+    fn test_neighbor() {
+        let grid = DeltaGrid::new(5, 3);
+        // The top left triangle points up, so it has a neighbor below but none above
+        assert!(grid.points_up(0));
+        assert_eq!(None, grid.neighbor(0, DeltaCell::NORTH));
+        assert_eq!(Some(5), grid.neighbor(0, DeltaCell::SOUTH));
+        assert_eq!(Some(1), grid.neighbor(0, DeltaCell::EAST));
+        // Its neighbor to the east points down
+        assert!(!grid.points_up(1));
+        assert_eq!(None, grid.neighbor(1, DeltaCell::SOUTH));
+        assert_eq!(Some(11), grid.neighbor(6, DeltaCell::SOUTH));
+        assert_eq!(None, grid.neighbor(9, DeltaCell::EAST));
+
+        for i in 0..grid.cells.len() {
+            assert!(grid.neighbors(i).len() <= 3);
+            for (direction, neighbor) in grid.neighbors(i) {
+                assert_eq!(Some(i), grid.neighbor(neighbor, direction.opposite()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursive_backtracker() {
+        let mut grid = DeltaGrid::new(12, 9);
+        grid.recursive_backtracker(None);
+        assert!(is_spanning_tree(&grid, |i, direction, _| grid.cells[i].contains(direction)));
+    }
+
+    #[test]
+    fn test_aldous_broder() {
+        let mut grid = DeltaGrid::new(12, 9);
+        grid.aldous_broder(None);
+        assert!(is_spanning_tree(&grid, |i, direction, _| grid.cells[i].contains(direction)));
+
+        let mut again = DeltaGrid::new(12, 9);
+        grid.aldous_broder(Some(7));
+        again.aldous_broder(Some(7));
+        assert_eq!(grid, again);
+    }
+
+    #[test]
+    fn test_to_image() {
+        let mut grid = DeltaGrid::new(3, 2);
+        grid.recursive_backtracker(Some(1));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let image = grid.to_image(20, 2, background, wall);
+
+        // 3 triangles 20 pixels wide overlap to 40 pixels, plus the wall
+        assert_eq!((42, 37), image.dimensions());
+        // The base of the bottom middle triangle is a wall, and the middle of the first is open
+        assert_eq!(wall, *image.get_pixel(21, 35));
+        assert_eq!(background, *image.get_pixel(11, 12));
+    }
+}
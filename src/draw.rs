@@ -0,0 +1,171 @@
+// Drawing helpers shared by the image and terminal renderers.
+
+use image::RgbImage;
+
+/*
+WallStyle is how Grid::to_image_styled draws the walls of a maze.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallStyle {
+    /// Filled in with the wall color, like to_image.
+    Solid,
+    /// Just their outlines, two thin lines with the background between them,
+    /// as in a lot of printed mazes. Walls need to be at least 3 pixels thick
+    /// to leave any background between the lines.
+    Double,
+}
+
+// Clears every wall_pixel in image that only has other wall pixels around it,
+// including diagonally, leaving the outlines of the walls. Pixels past the
+// edge of the image count as background, so walls along it keep their edge.
+pub(crate) fn hollow_out(
+    image: &mut RgbImage,
+    wall_pixel: image::Rgb<u8>,
+    background_pixel: image::Rgb<u8>,
+) {
+    let (width, height) = image.dimensions();
+    let is_wall = |image: &RgbImage, x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < i64::from(width)
+            && y < i64::from(height)
+            && *image.get_pixel(x as u32, y as u32) == wall_pixel
+    };
+    let mut inside = Vec::new();
+    for y in 0..i64::from(height) {
+        for x in 0..i64::from(width) {
+            let surrounded = (-1..=1).all(|dy| (-1..=1).all(|dx| is_wall(image, x + dx, y + dy)));
+            if surrounded {
+                inside.push((x as u32, y as u32));
+            }
+        }
+    }
+    for (x, y) in inside {
+        image.put_pixel(x, y, background_pixel);
+    }
+}
+
+// The color of a cell distance steps from the root, going from blue for the
+// closest cells to red for cells max_distance away.
+pub(crate) fn heat(distance: usize, max_distance: usize) -> [u8; 3] {
+    let intensity = if max_distance == 0 {
+        0.0
+    } else {
+        distance as f64 / max_distance as f64
+    };
+    let red = (255.0 * intensity).round() as u8;
+    [red, 0, 255 - red]
+}
+
+// Paints every pixel within thickness / 2 of the line segment from start to end.
+pub(crate) fn draw_line(
+    image: &mut RgbImage,
+    start: (f64, f64),
+    end: (f64, f64),
+    thickness: f64,
+    pixel: image::Rgb<u8>,
+) {
+    let radius = thickness.max(1.0) / 2.0;
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = dx * dx + dy * dy;
+
+    let min_x = (start.0.min(end.0) - radius).floor().max(0.0) as u32;
+    let min_y = (start.1.min(end.1) - radius).floor().max(0.0) as u32;
+    let max_x = ((start.0.max(end.0) + radius).ceil() as u32).min(image.width());
+    let max_y = ((start.1.max(end.1) + radius).ceil() as u32).min(image.height());
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            // Measure from the center of the pixel to the closest point on the segment
+            let (px, py) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+            let t = if length_squared == 0.0 {
+                0.0
+            } else {
+                (((px - start.0) * dx + (py - start.1) * dy) / length_squared).clamp(0.0, 1.0)
+            };
+            let (closest_x, closest_y) = (start.0 + t * dx, start.1 + t * dy);
+            let distance_squared = (px - closest_x).powi(2) + (py - closest_y).powi(2);
+            if distance_squared <= radius * radius {
+                image.put_pixel(x, y, pixel);
+            }
+        }
+    }
+}
+
+// Paints every pixel within thickness / 2 of the quarter circle of radius around center
+// that bulges towards corner, e.g. to round off the outside of a turn.
+pub(crate) fn draw_arc(
+    image: &mut RgbImage,
+    center: (f64, f64),
+    corner: (f64, f64),
+    radius: f64,
+    thickness: f64,
+    pixel: image::Rgb<u8>,
+) {
+    let half = thickness.max(1.0) / 2.0;
+    let min_x = (center.0.min(corner.0) - half).floor().max(0.0) as u32;
+    let min_y = (center.1.min(corner.1) - half).floor().max(0.0) as u32;
+    let max_x = ((center.0.max(corner.0) + half).ceil() as u32).min(image.width());
+    let max_y = ((center.1.max(corner.1) + half).ceil() as u32).min(image.height());
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (dx, dy) = (f64::from(x) + 0.5 - center.0, f64::from(y) + 0.5 - center.1);
+            // Only the quarter facing the corner
+            let in_quarter = dx * (corner.0 - center.0) >= 0.0 && dy * (corner.1 - center.1) >= 0.0;
+            if in_quarter && ((dx * dx + dy * dy).sqrt() - radius).abs() <= half {
+                image.put_pixel(x, y, pixel);
+            }
+        }
+    }
+}
+
+// A tiny bitmap font for labelling cells: each digit is 3 pixels wide and 5 high,
+// one row per entry, with the leftmost pixel in the highest of the 3 bits.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+// The width and height in pixels of number drawn by draw_number at scale 1.
+// Digits are separated by a pixel of space.
+pub(crate) fn number_size(number: usize) -> (usize, usize) {
+    let digits = number.to_string().len();
+    (4 * digits - 1, 5)
+}
+
+// Draws number with its top left corner at x, y, with each pixel of the font
+// scaled up to a scale x scale square.
+pub(crate) fn draw_number(
+    image: &mut RgbImage,
+    number: usize,
+    (x, y): (usize, usize),
+    scale: usize,
+    pixel: image::Rgb<u8>,
+) {
+    for (position, digit) in number.to_string().bytes().enumerate() {
+        let glyph = DIGITS[usize::from(digit - b'0')];
+        let left = x + position * 4 * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (left + col * scale + dx, y + row * scale + dy);
+                        image.put_pixel(px as u32, py as u32, pixel);
+                    }
+                }
+            }
+        }
+    }
+}
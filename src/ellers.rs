@@ -0,0 +1,211 @@
+use crate::{Cell, Direction, Grid};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+impl Grid {
+    /// ellers populates the maze one row at a time, only ever remembering which
+    /// "set" each cell of the current row belongs to. Cells in the same set are
+    /// already connected by some path.
+    ///
+    /// For every row except the last:
+    /// Randomly link EAST between adjacent cells that are in different sets, merging the sets.
+    /// Then, for every set, link SOUTH from at least one (randomly chosen) of its cells.
+    /// Cells in the next row that were not linked from above start out in a new set of their own.
+    ///
+    /// On the last row, link EAST between all adjacent cells in different sets so
+    /// that everything ends up connected.
+    pub fn ellers(&mut self, seed: Option<u64>) {
+        self.ellers_with_rng(&mut Grid::get_rng(seed));
+    }
+
+    /// ellers_with_rng works like ellers, but draws its random numbers from rng.
+    pub fn ellers_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let mut sets = RowSets::new(self.width);
+        for row in 0..self.height {
+            let row_start = row * self.width;
+            let last_row = row + 1 == self.height;
+
+            // With a mask, some cells can't be linked
+            let east: Vec<bool> = (row_start..row_start + self.width)
+                .map(|i| self.valid_direction(i, Direction::East))
+                .collect();
+            let south: Vec<bool> = (row_start..row_start + self.width)
+                .map(|i| self.valid_direction(i, Direction::South))
+                .collect();
+
+            let (above, below) = self.cells[row_start..].split_at_mut(self.width);
+            let below = if last_row { None } else { Some(below) };
+            sets.carve(rng, above, below, &east, &south);
+        }
+        self.join_masked_pieces(rng);
+    }
+
+    /// ellers_streaming generates a width x height maze the same way as ellers,
+    /// but without ever holding the whole maze in memory: only the row being carved
+    /// and the one below it. As each row is finished, sink is called with its cells,
+    /// from the top row down, so even mazes with millions of rows can be written
+    /// out as they're made. The rows are exactly those of ellers with the same seed.
+    ///
+    /// For example, to save a maze as a PNG image a row of pixels at a time,
+    /// see examples/ellers_png.rs.
+    pub fn ellers_streaming(
+        width: usize,
+        height: usize,
+        seed: Option<u64>,
+        mut sink: impl FnMut(&[Cell]),
+    ) {
+        if width == 0 {
+            return;
+        }
+
+        let mut rng = Grid::get_rng(seed);
+        let mut sets = RowSets::new(width);
+        let east = vec![true; width];
+        let south = vec![true; width];
+
+        let mut row = vec![Cell::default(); width];
+        let mut below = vec![Cell::default(); width];
+        for row_index in 0..height {
+            if row_index + 1 == height {
+                sets.carve(&mut rng, &mut row, None, &east, &south);
+            } else {
+                sets.carve(&mut rng, &mut row, Some(&mut below), &east, &south);
+            }
+            sink(&row);
+
+            std::mem::swap(&mut row, &mut below);
+            below.iter_mut().for_each(|cell| *cell = Cell::default());
+        }
+    }
+}
+
+// RowSets keeps track of which cells of the current row of Eller's algorithm are
+// already connected. Set ids are always in 0..width, since a row can never hold more
+// sets than cells. set_of maps a column to its set, members maps a set to its columns.
+struct RowSets {
+    set_of: Vec<usize>,
+    members: Vec<Vec<usize>>,
+}
+
+impl RowSets {
+    // Every cell of the first row is in a set of its own.
+    fn new(width: usize) -> RowSets {
+        RowSets {
+            set_of: (0..width).collect(),
+            members: (0..width).map(|col| vec![col]).collect(),
+        }
+    }
+
+    // Carves the passages of one row, and down into the row below it, which is None
+    // for the last row. east and south say which cells of the row can be linked that way.
+    // Afterwards, the sets are those of the row below.
+    fn carve<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        row: &mut [Cell],
+        below: Option<&mut [Cell]>,
+        east: &[bool],
+        south: &[bool],
+    ) {
+        let width = row.len();
+        let (set_of, members) = (&mut self.set_of, &mut self.members);
+
+        for col in 1..width {
+            let (west_set, east_set) = (set_of[col - 1], set_of[col]);
+            if west_set != east_set && east[col - 1] && (below.is_none() || rng.gen()) {
+                row[col - 1] |= Cell::EAST;
+                row[col] |= Cell::WEST;
+
+                // Merge the smaller set into the larger one
+                let (keep, merged) = if members[west_set].len() >= members[east_set].len() {
+                    (west_set, east_set)
+                } else {
+                    (east_set, west_set)
+                };
+                let moved = std::mem::take(&mut members[merged]);
+                for &moved_col in moved.iter() {
+                    set_of[moved_col] = keep;
+                }
+                members[keep].extend(moved);
+            }
+        }
+
+        let below = match below {
+            Some(below) => below,
+            None => return,
+        };
+
+        // Every set needs at least one passage SOUTH, otherwise it would be cut off.
+        let mut next_members = vec![Vec::new(); width];
+        for (set, cols) in members.iter_mut().enumerate() {
+            if cols.is_empty() {
+                continue;
+            }
+            cols.shuffle(rng);
+            let num_south = rng.gen_range(1, cols.len() + 1);
+            // With a mask, some cells may not be able to go SOUTH
+            let south_cols = cols
+                .iter()
+                .copied()
+                .filter(|&col| south[col])
+                .take(num_south)
+                .collect::<Vec<usize>>();
+            for col in south_cols {
+                row[col] |= Cell::SOUTH;
+                below[col] |= Cell::NORTH;
+                next_members[set].push(col);
+            }
+        }
+
+        // Remaining cells of the next row each get a fresh set
+        let mut free_sets = (0..width)
+            .filter(|&set| next_members[set].is_empty())
+            .collect::<Vec<usize>>()
+            .into_iter();
+        for (col, cell) in row.iter().enumerate() {
+            if !cell.contains(Cell::SOUTH) {
+                let set = free_sets.next().unwrap();
+                next_members[set].push(col);
+            }
+        }
+        for (set, cols) in next_members.iter().enumerate() {
+            for &col in cols.iter() {
+                set_of[col] = set;
+            }
+        }
+        *members = next_members;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_ellers_streaming() {
+        for &(width, height) in [(1, 1), (1, 8), (8, 1), (13, 9), (40, 40)].iter() {
+            let mut grid = Grid::new(width, height);
+            grid.ellers(Some(17));
+
+            let mut rows = Vec::new();
+            Grid::ellers_streaming(width, height, Some(17), |row| rows.push(row.to_vec()));
+            assert_eq!(height, rows.len());
+            assert!(rows.iter().all(|row| row.len() == width));
+            assert_eq!(grid.cells, rows.concat());
+        }
+
+        // Nothing to stream
+        let mut rows = 0;
+        Grid::ellers_streaming(0, 5, None, |_| rows += 1);
+        Grid::ellers_streaming(5, 0, None, |_| rows += 1);
+        assert_eq!(0, rows);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{DisjointSet, Grid};
+
+// The most cells enumerate_all takes on: a 4x4 maze has 100352 variations,
+// a 5x4 one already has over two million.
+const MAX_CELLS: usize = 16;
+
+impl Grid {
+    /// enumerate_all produces every distinct perfect maze of width x height cells,
+    /// each exactly once and always in the same order, e.g. for tests to check that
+    /// an algorithm can make every maze there is without relying on luck.
+    /// There are 192 of them for a 3x3 maze.
+    ///
+    /// A perfect maze is a spanning tree of the grid: width * height - 1 passages
+    /// joining every cell without any loops. Every choice of that many passages is
+    /// tried, keeping those that leave no loops.
+    ///
+    /// The number of mazes grows very quickly with their size, so this panics if
+    /// there are more than 16 cells.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// assert_eq!(192, Grid::enumerate_all(3, 3).count());
+    /// assert!(Grid::enumerate_all(2, 3).all(|grid| grid.is_connected()));
+    /// ```
+    pub fn enumerate_all(width: usize, height: usize) -> impl Iterator<Item = Grid> {
+        assert!(
+            width * height <= MAX_CELLS,
+            "enumerate_all only goes up to {} cells, not a {}x{} maze",
+            MAX_CELLS,
+            width,
+            height
+        );
+        let cells = width * height;
+
+        // Every pair of neighboring cells, which a passage could join
+        let mut edges = Vec::new();
+        for i in 0..cells {
+            if !(i + 1).is_multiple_of(width) {
+                edges.push((i, i + 1));
+            }
+            if i + width < cells {
+                edges.push((i, i + width));
+            }
+        }
+
+        // Each choice of passages is the set bits of a number, going through all the
+        // numbers with the right number of bits set in increasing order
+        let passages = cells.saturating_sub(1);
+        let first: u64 = (1 << passages) - 1;
+        let end: u64 = 1 << edges.len();
+        let choices = std::iter::successors(Some(first), move |&choice| {
+            if choice == 0 {
+                return None;
+            }
+            let lowest = choice & choice.wrapping_neg();
+            let carried = choice + lowest;
+            let next = (((carried ^ choice) >> 2) / lowest) | carried;
+            if next < end {
+                Some(next)
+            } else {
+                None
+            }
+        });
+
+        choices.filter_map(move |choice| {
+            let mut joined = DisjointSet::new(cells);
+            let chosen = edges
+                .iter()
+                .enumerate()
+                .filter(|&(bit, _)| choice & 1 << bit != 0);
+            let mut grid = Grid::new(width, height);
+            for (_, &(a, b)) in chosen {
+                if !joined.union(a, b) {
+                    return None;
+                }
+                grid.link(a, b).unwrap();
+            }
+            Some(grid)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_enumerate_all() {
+        // The number of spanning trees of each grid
+        let sizes = [
+            (0, 0, 1),
+            (1, 1, 1),
+            (1, 5, 1),
+            (2, 2, 4),
+            (3, 2, 15),
+            (3, 3, 192),
+            (4, 3, 2415),
+        ];
+        for &(width, height, count) in sizes.iter() {
+            let mut seen = HashSet::new();
+            for grid in Grid::enumerate_all(width, height) {
+                assert_eq!(Ok(()), grid.validate());
+                assert!(grid.is_connected());
+                assert_eq!((width * height).saturating_sub(1), grid.edges().len());
+                assert!(seen.insert(grid.cells));
+            }
+            assert_eq!(count, seen.len(), "{}x{}", width, height);
+        }
+
+        // Always in the same order
+        let first: Vec<Grid> = Grid::enumerate_all(3, 2).collect();
+        let again: Vec<Grid> = Grid::enumerate_all(3, 2).collect();
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    #[should_panic(expected = "enumerate_all only goes up to 16 cells, not a 5x4 maze")]
+    fn test_enumerate_all_too_big() {
+        Grid::enumerate_all(5, 4).count();
+    }
+}
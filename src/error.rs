@@ -0,0 +1,191 @@
+use crate::Cell;
+
+use std::fmt;
+
+/*
+GridError describes why a Grid isn't a valid maze.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GridError {
+    /// There should be width * height cells, but there aren't.
+    /// expected is usize::MAX when width * height is too big to count.
+    WrongCellCount { expected: usize, found: usize },
+    /// The mask doesn't have the same dimensions as the grid.
+    WrongMaskSize {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// There should be one weight per cell, but there aren't.
+    WrongWeightCount { expected: usize, found: usize },
+    /// A cell has a link into a masked off cell or to a neighbor that doesn't link back,
+    /// or a masked off cell has a link.
+    InvalidLink { cell: usize, direction: Cell },
+    /// A cell has a passage tunneling beneath it, but its own passage doesn't go straight across.
+    InvalidCrossing { cell: usize },
+    /// An edge given to Grid::from_edges joins cells that aren't next to each other,
+    /// or that aren't in the grid.
+    InvalidEdge { a: usize, b: usize },
+    /// An edge was given to Grid::from_edges twice, either way round.
+    DuplicateEdge { a: usize, b: usize },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::WrongCellCount { expected, found } => write!(
+                f,
+                "Expected {} cells for the grid's dimensions, but found {}",
+                expected, found
+            ),
+            GridError::WrongMaskSize { expected, found } => write!(
+                f,
+                "Expected a {}x{} mask, but found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            GridError::WrongWeightCount { expected, found } => write!(
+                f,
+                "Expected {} weights, one for each cell, but found {}",
+                expected, found
+            ),
+            GridError::InvalidLink { cell, direction } => write!(
+                f,
+                "Cell {} is linked {:?}, but there is no cell there that links back",
+                cell, direction
+            ),
+            GridError::InvalidCrossing { cell } => write!(
+                f,
+                "Cell {} has a passage beneath it, but isn't a straight passage across it",
+                cell
+            ),
+            GridError::InvalidEdge { a, b } => {
+                write!(f, "Cells {} and {} aren't neighbors in the grid", a, b)
+            }
+            GridError::DuplicateEdge { a, b } => {
+                write!(f, "The edge between cells {} and {} is there twice", a, b)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/*
+ParseError describes why text couldn't be read as a maze by Grid::from_ascii.
+Lines and columns count from 1.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    /// A maze needs an odd number of lines, all 4 * width + 1 characters long.
+    WrongShape { line: usize },
+    /// There should be a wall or a gap at this position, but there's something else.
+    UnexpectedCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::WrongShape { line } => write!(
+                f,
+                "Line {} doesn't fit the shape of a maze. Every line must be the same length, \
+                 four characters per cell and one more, and there must be an odd number of lines",
+                line
+            ),
+            ParseError::UnexpectedCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "Unexpected {:?} at line {}, column {}",
+                found, line, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/*
+UnpackError describes why bytes couldn't be read as a maze by Grid::from_packed,
+Grid::from_bytes_le or Grid::from_mz.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UnpackError {
+    /// There aren't enough bytes for the width and height.
+    MissingHeader,
+    /// There are the wrong number of bytes after the header for the maze's dimensions.
+    WrongLength { expected: usize, found: usize },
+    /// The cells don't make a valid maze.
+    InvalidGrid(GridError),
+    /// The .mz file was written by a newer version of the format.
+    UnsupportedVersion { version: u8 },
+    /// The bytes aren't a maze in any version of the .mz format.
+    Malformed,
+}
+
+impl fmt::Display for UnpackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnpackError::MissingHeader => write!(f, "Too short to hold the maze's dimensions"),
+            UnpackError::WrongLength { expected, found } => write!(
+                f,
+                "Expected {} bytes for the maze's dimensions, but found {}",
+                expected, found
+            ),
+            // This is a wrapper, so defer to the underlying type's implementation of `fmt`.
+            UnpackError::InvalidGrid(e) => e.fmt(f),
+            UnpackError::UnsupportedVersion { version } => {
+                write!(f, "Version {} of the .mz format isn't supported", version)
+            }
+            UnpackError::Malformed => write!(f, "Not a maze in any version of the .mz format"),
+        }
+    }
+}
+
+impl std::error::Error for UnpackError {}
+
+/*
+LinkError describes why two cells couldn't be linked or unlinked by Grid::link and Grid::unlink.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LinkError {
+    /// The cell isn't in the grid.
+    OutOfBounds { cell: usize },
+    /// The cell is masked off, so it isn't part of the maze.
+    Masked { cell: usize },
+    /// The cells aren't next to each other.
+    NotNeighbors { a: usize, b: usize },
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkError::OutOfBounds { cell } => write!(f, "Cell {} is outside the grid", cell),
+            LinkError::Masked { cell } => write!(f, "Cell {} is masked off", cell),
+            LinkError::NotNeighbors { a, b } => {
+                write!(f, "Cells {} and {} are not neighbors", a, b)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+/*
+Cancelled is returned by the cancellable generators, such as
+Grid::aldous_broder_cancellable, when they're stopped before finishing.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Maze generation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
@@ -0,0 +1,78 @@
+use crate::Grid;
+
+use petgraph::graph::{NodeIndex, UnGraph};
+
+impl Grid {
+    /// to_petgraph converts the maze to a petgraph graph, with a node for each cell
+    /// that isn't masked off and an edge for each open passage, so that petgraph's
+    /// algorithms can be run on it. Each node's weight is the index of its cell.
+    ///
+    /// Needs the "petgraph" feature.
+    pub fn to_petgraph(&self) -> UnGraph<usize, ()> {
+        let mut graph = UnGraph::new_undirected();
+        let nodes: Vec<Option<NodeIndex>> = (0..self.cells.len())
+            .map(|i| {
+                if self.is_masked(i) {
+                    None
+                } else {
+                    Some(graph.add_node(i))
+                }
+            })
+            .collect();
+
+        for (i, &node) in nodes.iter().enumerate() {
+            if let Some(node) = node {
+                // Each passage is seen from both ends, so only add it from the lower one
+                for j in self.links(i).filter(|&j| j > i) {
+                    graph.add_edge(node, nodes[j].unwrap(), ());
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{Cell, Mask};
+
+    use petgraph::algo::{connected_components, dijkstra};
+
+    #[test]
+    fn test_to_petgraph() {
+        let mut grid = Grid::new(6, 4);
+        grid.kruskals(Some(9));
+
+        let graph = grid.to_petgraph();
+        assert_eq!(24, graph.node_count());
+        assert_eq!(23, graph.edge_count());
+        assert_eq!(grid.components(), connected_components(&graph));
+
+        // The node weights are the cells, so petgraph's distances match the maze's
+        let costs = dijkstra(&graph, NodeIndex::new(0), None, |_| 1);
+        for (node, cost) in costs {
+            assert_eq!(grid.distances(0)[graph[node]], Some(cost));
+        }
+    }
+
+    #[test]
+    fn test_to_petgraph_components() {
+        // With no passages, every cell is on its own
+        let mut mask = Mask::new(5, 5);
+        mask.set(12, false);
+        let mut grid = Grid::with_mask(5, 5, mask);
+        let graph = grid.to_petgraph();
+        assert_eq!(24, graph.node_count());
+        assert_eq!(24, connected_components(&graph));
+        assert_eq!(grid.components(), connected_components(&graph));
+
+        grid.recursive_backtracker(Some(3));
+        grid.cells[0] = Cell::default();
+        grid.cells[1].remove(Cell::WEST);
+        grid.cells[5].remove(Cell::NORTH);
+        assert_eq!(2, grid.components());
+        assert_eq!(grid.components(), connected_components(&grid.to_petgraph()));
+    }
+}
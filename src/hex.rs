@@ -0,0 +1,256 @@
+use crate::draw::draw_line;
+use crate::maze::{self, Maze};
+use crate::Grid;
+
+use image::{ImageBuffer, RgbImage};
+
+/*
+HexCell is a single hexagon in a HexGrid.
+The hexagons have flat tops, so every cell has six neighbors:
+one above, one below, and two on each side.
+Like Cell, each flag means there is a passage that way.
+*/
+bitflags! {
+    #[derive(Default)]
+    pub struct HexCell: u8 {
+        const NORTH =      0b00_0001;
+        const SOUTH =      0b00_0010;
+        const NORTH_EAST = 0b00_0100;
+        const NORTH_WEST = 0b00_1000;
+        const SOUTH_EAST = 0b01_0000;
+        const SOUTH_WEST = 0b10_0000;
+    }
+}
+
+const DIRECTIONS: [HexCell; 6] = [
+    HexCell::NORTH,
+    HexCell::SOUTH,
+    HexCell::NORTH_EAST,
+    HexCell::NORTH_WEST,
+    HexCell::SOUTH_EAST,
+    HexCell::SOUTH_WEST,
+];
+
+impl HexCell {
+    fn opposite(self) -> HexCell {
+        match self {
+            HexCell::NORTH => HexCell::SOUTH,
+            HexCell::SOUTH => HexCell::NORTH,
+            HexCell::NORTH_EAST => HexCell::SOUTH_WEST,
+            HexCell::NORTH_WEST => HexCell::SOUTH_EAST,
+            HexCell::SOUTH_EAST => HexCell::NORTH_WEST,
+            HexCell::SOUTH_WEST => HexCell::NORTH_EAST,
+            _ => panic!("{:?} is not a single direction", self),
+        }
+    }
+}
+
+/*
+HexGrid is a maze made of hexagons, stored row by row like Grid.
+Odd columns sit half a cell lower than even columns, so the
+cells in row 0 zigzag across the top of the maze.
+*/
+#[derive(PartialEq, Clone, Debug)]
+pub struct HexGrid {
+    pub cells: Vec<HexCell>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl HexGrid {
+    pub fn new(width: usize, height: usize) -> HexGrid {
+        HexGrid {
+            cells: vec![HexCell::default(); width * height],
+            width,
+            height,
+        }
+    }
+
+    /// neighbor finds the index of the cell next to cell i in direction,
+    /// or None if that would be off the edge of the grid.
+    pub fn neighbor(&self, i: usize, direction: HexCell) -> Option<usize> {
+        let (row, col) = ((i / self.width) as isize, (i % self.width) as isize);
+        // Columns that are shifted down reach one row further south on their sides
+        let side_row = if col % 2 == 0 { row - 1 } else { row };
+
+        let (row, col) = match direction {
+            HexCell::NORTH => (row - 1, col),
+            HexCell::SOUTH => (row + 1, col),
+            HexCell::NORTH_EAST => (side_row, col + 1),
+            HexCell::NORTH_WEST => (side_row, col - 1),
+            HexCell::SOUTH_EAST => (side_row + 1, col + 1),
+            HexCell::SOUTH_WEST => (side_row + 1, col - 1),
+            _ => panic!("{:?} is not a single direction", direction),
+        };
+
+        if row < 0 || col < 0 || row >= self.height as isize || col >= self.width as isize {
+            None
+        } else {
+            Some(row as usize * self.width + col as usize)
+        }
+    }
+
+    /// recursive_backtracker populates the grid with maze::recursive_backtracker.
+    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::recursive_backtracker(self, &mut Grid::get_rng(seed));
+    }
+
+    /// aldous_broder populates the grid with maze::aldous_broder.
+    pub fn aldous_broder(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::aldous_broder(self, &mut Grid::get_rng(seed));
+    }
+
+    fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = HexCell::default();
+        }
+    }
+
+    /// to_image renders the maze with each hexagon cell_size pixels wide, corner to corner.
+    pub fn to_image(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        // Distance from a hexagon's center to a corner, and to the middle of its top edge
+        let radius = cell_size as f64 / 2.0;
+        let half_height = radius * 3f64.sqrt() / 2.0;
+        let margin = wall_size as f64 / 2.0;
+
+        let image_width = radius * (1.5 * self.width as f64 + 0.5) + wall_size as f64;
+        let mut image_height = 2.0 * half_height * self.height as f64 + wall_size as f64;
+        if self.width > 1 {
+            image_height += half_height;
+        }
+
+        let mut image = ImageBuffer::from_pixel(
+            image_width.ceil() as u32,
+            image_height.ceil() as u32,
+            background_pixel,
+        );
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let (row, col) = (i / self.width, i % self.width);
+            let x = margin + radius + 1.5 * radius * col as f64;
+            let mut y = margin + half_height + 2.0 * half_height * row as f64;
+            if col % 2 == 1 {
+                y += half_height;
+            }
+
+            let east = (x + radius, y);
+            let west = (x - radius, y);
+            let north_east = (x + radius / 2.0, y - half_height);
+            let north_west = (x - radius / 2.0, y - half_height);
+            let south_east = (x + radius / 2.0, y + half_height);
+            let south_west = (x - radius / 2.0, y + half_height);
+
+            let walls = [
+                (HexCell::NORTH, north_west, north_east),
+                (HexCell::SOUTH, south_west, south_east),
+                (HexCell::NORTH_EAST, north_east, east),
+                (HexCell::NORTH_WEST, west, north_west),
+                (HexCell::SOUTH_EAST, east, south_east),
+                (HexCell::SOUTH_WEST, south_west, west),
+            ];
+            for &(direction, start, end) in &walls {
+                if !cell.contains(direction) {
+                    draw_line(&mut image, start, end, wall_size as f64, wall_pixel);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+impl Maze for HexGrid {
+    type Dir = HexCell;
+
+    fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(HexCell, usize)> {
+        DIRECTIONS
+            .iter()
+            .filter_map(|&direction| {
+                self.neighbor(i, direction)
+                    .map(|neighbor| (direction, neighbor))
+            })
+            .collect()
+    }
+
+    /// Panics if a and b aren't next to each other.
+    fn link(&mut self, a: usize, b: usize) {
+        let direction = DIRECTIONS
+            .iter()
+            .copied()
+            .find(|&direction| self.neighbor(a, direction) == Some(b))
+            .unwrap_or_else(|| panic!("cells {} and {} are not neighbors", a, b));
+        self.cells[a] |= direction;
+        self.cells[b] |= direction.opposite();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::maze::tests::is_spanning_tree;
+
+    #[test]
+    fn test_neighbor() {
+        let grid = HexGrid::new(4, 3);
+        // Even column, so its side neighbors are half a row up
+        assert_eq!(None, grid.neighbor(0, HexCell::NORTH_EAST));
+        assert_eq!(Some(1), grid.neighbor(0, HexCell::SOUTH_EAST));
+        // Odd column, so its side neighbors are half a row down
+        assert_eq!(Some(6), grid.neighbor(5, HexCell::NORTH_EAST));
+        assert_eq!(Some(10), grid.neighbor(5, HexCell::SOUTH_EAST));
+        assert_eq!(Some(8), grid.neighbor(5, HexCell::SOUTH_WEST));
+        assert_eq!(None, grid.neighbor(9, HexCell::SOUTH));
+
+        for i in 0..grid.cells.len() {
+            for (direction, neighbor) in grid.neighbors(i) {
+                assert_eq!(Some(i), grid.neighbor(neighbor, direction.opposite()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursive_backtracker() {
+        let mut grid = HexGrid::new(12, 9);
+        grid.recursive_backtracker(None);
+        assert!(is_spanning_tree(&grid, |i, direction, _| grid.cells[i].contains(direction)));
+    }
+
+    #[test]
+    fn test_aldous_broder() {
+        let mut grid = HexGrid::new(12, 9);
+        grid.aldous_broder(None);
+        assert!(is_spanning_tree(&grid, |i, direction, _| grid.cells[i].contains(direction)));
+
+        let mut again = HexGrid::new(12, 9);
+        grid.aldous_broder(Some(7));
+        again.aldous_broder(Some(7));
+        assert_eq!(grid, again);
+    }
+
+    #[test]
+    fn test_to_image() {
+        let mut grid = HexGrid::new(3, 2);
+        grid.recursive_backtracker(Some(1));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let image = grid.to_image(20, 2, background, wall);
+
+        // 3 columns of 20 pixel hexagons overlap to 50 pixels, plus the wall
+        assert_eq!(52, image.width());
+        assert_eq!(wall, *image.get_pixel(1, 9));
+        assert_eq!(background, *image.get_pixel(11, 9));
+    }
+}
@@ -1,18 +1,103 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_code)]
 
 #[macro_use]
 extern crate bitflags;
+#[cfg(feature = "std")]
 extern crate image;
 
+#[cfg(feature = "std")]
 use image::{ImageBuffer, RgbImage};
+#[cfg(feature = "std")]
 use rand::seq::SliceRandom;
+#[cfg(feature = "std")]
 use rand::{Rng, SeedableRng};
+#[cfg(feature = "std")]
 use rand_pcg::Lcg64Xsh32;
-use serde::{Deserialize, Serialize};
-
-use std::cmp::Reverse;
-use std::collections::BinaryHeap;
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::atomic::AtomicBool;
+
+#[cfg(feature = "std")]
+mod algorithm;
+#[cfg(feature = "std")]
+mod annotation;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod delta;
+#[cfg(feature = "std")]
+mod draw;
+#[cfg(feature = "std")]
+mod ellers;
+#[cfg(feature = "std")]
+mod enumerate;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(all(feature = "petgraph", feature = "std"))]
+mod graph;
+#[cfg(feature = "std")]
+mod hex;
+#[cfg(feature = "std")]
+mod mask;
+#[cfg(feature = "std")]
+pub mod maze;
+#[cfg(feature = "std")]
+mod mz;
+#[cfg(feature = "std")]
+mod packed;
+#[cfg(feature = "std")]
+mod packed_grid;
+#[cfg(feature = "std")]
+mod polar;
+#[cfg(feature = "std")]
+mod region;
+mod slice;
+#[cfg(feature = "std")]
+mod solve;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod svg;
+#[cfg(feature = "std")]
+mod text;
+#[cfg(feature = "std")]
+mod transform;
+#[cfg(feature = "std")]
+mod weave;
+
+#[cfg(feature = "std")]
+pub use algorithm::Algorithm;
+#[cfg(feature = "std")]
+pub use annotation::Annotation;
+#[cfg(feature = "std")]
+pub use builder::GridBuilder;
+#[cfg(feature = "std")]
+pub use delta::{DeltaCell, DeltaGrid};
+#[cfg(feature = "std")]
+pub use draw::WallStyle;
+#[cfg(feature = "std")]
+pub use error::{Cancelled, GridError, LinkError, ParseError, UnpackError};
+#[cfg(feature = "std")]
+pub use hex::{HexCell, HexGrid};
+#[cfg(feature = "std")]
+pub use mask::Mask;
+#[cfg(feature = "std")]
+pub use maze::{GrowingTreeStrategy, Maze};
+#[cfg(feature = "std")]
+pub use packed_grid::PackedGrid;
+#[cfg(feature = "std")]
+pub use polar::{PolarCell, PolarDirection, PolarGrid};
+#[cfg(feature = "std")]
+pub use region::Rect;
+pub use slice::SliceGrid;
+#[cfg(feature = "std")]
+pub use stats::{CellKind, MazeStats};
+#[cfg(feature = "std")]
+pub use text::AsciiCharset;
 
 /*
 Cell represents a single square in a maze's Grid.
@@ -23,27 +108,199 @@ Otherwise, there is a wall between the two Cells.
 
 It would be a logic error if this Cell had
 NORTH, but its northern neighbor did not have SOUTH.
+
+The one exception is in weave mazes, where a passage can tunnel
+beneath a Cell. That Cell has UNDER, and its own passage must run
+straight across the tunnel. The Cells at either end of the tunnel
+link towards it, but it doesn't link back.
 */
 bitflags! {
-    #[derive(Default, Serialize, Deserialize)]
+    #[derive(Default)]
     pub struct Cell: u8 {
-        const NORTH = 0b0001;
-        const SOUTH = 0b0010;
-        const EAST =  0b0100;
-        const WEST =  0b1000;
+        const NORTH = 0b0_0001;
+        const SOUTH = 0b0_0010;
+        const EAST =  0b0_0100;
+        const WEST =  0b0_1000;
+        const UNDER = 0b1_0000;
+    }
+}
+
+// A Cell is stored as its raw bits, so formats like JSON get a plain number.
+#[cfg(feature = "std")]
+impl Serialize for Cell {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Cell, D::Error> {
+        let bits = u8::deserialize(deserializer)?;
+        Cell::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid cell bits {:#06b}", bits)))
+    }
+}
+
+/*
+Direction is one of the four ways out of a Cell, towards one of its neighbors.
+A Cell is a set of flags that can hold any number of passages at once,
+whereas a Direction is always exactly one of them.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    /// all is every direction, in the order North, South, East, West.
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::North,
+            Direction::South,
+            Direction::East,
+            Direction::West,
+        ]
+    }
+
+    /// opposite is the direction back the other way.
+    ///
+    /// ```
+    /// use minotaur::Direction;
+    ///
+    /// assert_eq!(Direction::South, Direction::North.opposite());
+    /// ```
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// to_flag is the flag a Cell has when it has a passage in this direction.
+    pub fn to_flag(&self) -> Cell {
+        match self {
+            Direction::North => Cell::NORTH,
+            Direction::South => Cell::SOUTH,
+            Direction::East => Cell::EAST,
+            Direction::West => Cell::WEST,
+        }
+    }
+
+    /// from_flag is the direction of flag, or None if flag isn't exactly one of
+    /// NORTH, SOUTH, EAST and WEST.
+    pub fn from_flag(flag: Cell) -> Option<Direction> {
+        Direction::all()
+            .iter()
+            .copied()
+            .find(|direction| direction.to_flag() == flag)
+    }
+}
+
+/*
+DisjointSet is a union-find structure over the indices 0..n.
+It uses path compression and union by rank so that both
+operations are effectively constant time.
+*/
+#[cfg(feature = "std")]
+struct DisjointSet {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl DisjointSet {
+    fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..n).collect(),
+            ranks: vec![0; n],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Path compression: point everything we walked through at the root
+        let mut current = i;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing a and b. Returns false if they were already the same set.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.ranks[a] < self.ranks[b] {
+            self.parents[a] = b;
+        } else if self.ranks[a] > self.ranks[b] {
+            self.parents[b] = a;
+        } else {
+            self.parents[b] = a;
+            self.ranks[a] += 1;
+        }
+        true
     }
 }
 
 /*
 Grid represents a maze.
+If it has a Mask, the cells that are masked off are not part of the maze.
+
+Serialized with serde, e.g. to JSON, a Grid looks like:
+
+{
+  "cells": [6, 10, ...],
+  "width": 5,
+  "height": 5,
+  "mask": null,
+  "weights": null,
+  "wrap_horizontal": false,
+  "wrap_vertical": false
+}
+
+cells holds width * height numbers, row by row starting from the top left.
+Each is the bits of a Cell: 1 for NORTH, 2 for SOUTH, 4 for EAST, 8 for WEST
+and 16 for UNDER, when a weave passage tunnels beneath the cell.
+mask is either null or {"width": 5, "height": 5, "on": [true, false, ...]}.
+weights is either null or one cost per cell, in the same order as cells.
+wrap_horizontal is whether the east and west edges are joined, as on a cylinder,
+and wrap_vertical whether the north and south edges are. With both, the maze is on a torus.
+Annotations aren't serialized.
 */
+#[cfg(feature = "std")]
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Grid {
     pub cells: Vec<Cell>,
     pub width: usize,
     pub height: usize,
+    #[serde(default)]
+    mask: Option<Mask>,
+    #[serde(default)]
+    weights: Option<Vec<u32>>,
+    #[serde(default)]
+    wrap_horizontal: bool,
+    #[serde(default)]
+    wrap_vertical: bool,
+    #[serde(skip)]
+    annotations: HashMap<usize, Annotation>,
 }
 
+#[cfg(feature = "std")]
 impl Grid {
     pub fn new(width: usize, height: usize) -> Grid {
         let cells = vec![Cell::default(); height * width];
@@ -51,7 +308,290 @@ impl Grid {
             cells,
             width,
             height,
+            mask: None,
+            weights: None,
+            wrap_horizontal: false,
+            wrap_vertical: false,
+            annotations: HashMap::new(),
+        }
+    }
+
+    /// from_buffer makes a width x height Grid out of cells, keeping their passages as they are,
+    /// e.g. to reuse the buffer from into_cells of a maze that's done with.
+    /// Generating a maze in it doesn't allocate, since reset clears the cells in place.
+    /// Fails if there aren't exactly width * height cells.
+    pub fn from_buffer(cells: Vec<Cell>, width: usize, height: usize) -> Result<Grid, GridError> {
+        let expected = width.checked_mul(height);
+        if expected != Some(cells.len()) {
+            return Err(GridError::WrongCellCount {
+                expected: expected.unwrap_or(usize::MAX),
+                found: cells.len(),
+            });
+        }
+        Ok(Grid {
+            cells,
+            width,
+            height,
+            mask: None,
+            weights: None,
+            wrap_horizontal: false,
+            wrap_vertical: false,
+            annotations: HashMap::new(),
+        })
+    }
+
+    /// into_cells gives up the Grid for its cells, e.g. to pass the buffer to from_buffer
+    /// for the next maze.
+    pub fn into_cells(self) -> Vec<Cell> {
+        self.cells
+    }
+
+    /// reset removes every passage, leaving each cell walled in, ready to generate a new maze.
+    /// The cells are cleared in place, so regenerating the same Grid doesn't allocate.
+    pub fn reset(&mut self) {
+        self.cells.clear();
+        self.cells.resize(self.width * self.height, Cell::default());
+    }
+
+    /// with_mask creates a Grid where only the cells that are on in mask are part of the maze.
+    /// Masked off cells are never linked by the generators, and are left blank when rendered.
+    ///
+    /// The unmasked cells should all be connected to each other. aldous_broder and wilsons
+    /// never finish otherwise, and the other generators produce disconnected regions.
+    /// binary_tree and sidewinder can leave regions disconnected even when the unmasked
+    /// cells are connected. ellers and recursive_division join up the pieces the mask
+    /// cuts their maze into, so they always produce a perfect maze on a connected mask.
+    pub fn with_mask(width: usize, height: usize, mask: Mask) -> Grid {
+        assert_eq!(
+            (width, height),
+            (mask.width, mask.height),
+            "mask dimensions must match the grid"
+        );
+        let mut grid = Grid::new(width, height);
+        grid.mask = Some(mask);
+        grid
+    }
+
+    /// validate checks that the Grid is consistent, such as one loaded from a file:
+    /// there must be exactly width * height cells, a mask must have the same dimensions,
+    /// weights must have one entry per cell, and every link must lead to an unmasked cell
+    /// that links back. Links off the edge of the grid are openings made by add_opening.
+    pub fn validate(&self) -> Result<(), GridError> {
+        // The dimensions may have come from a corrupt file, so they can be too big to multiply
+        let expected = self.width.checked_mul(self.height);
+        if expected != Some(self.cells.len()) {
+            return Err(GridError::WrongCellCount {
+                expected: expected.unwrap_or(usize::MAX),
+                found: self.cells.len(),
+            });
+        }
+
+        if let Some(mask) = &self.mask {
+            if (mask.width, mask.height) != (self.width, self.height) || !mask.has_valid_size() {
+                return Err(GridError::WrongMaskSize {
+                    expected: (self.width, self.height),
+                    found: (mask.width, mask.height),
+                });
+            }
         }
+
+        if let Some(weights) = &self.weights {
+            if weights.len() != self.cells.len() {
+                return Err(GridError::WrongWeightCount {
+                    expected: self.cells.len(),
+                    found: weights.len(),
+                });
+            }
+        }
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            for &direction in Direction::all().iter() {
+                if !cell.contains(direction.to_flag()) {
+                    continue;
+                }
+                // Links off the edge of the grid are openings, and fine unless the cell is masked
+                let valid = if self.in_bounds(i, direction) {
+                    self.valid_direction(i, direction) && {
+                        let end = self.passage_end(i, direction);
+                        !self.is_masked(end)
+                            && self.cells[end].contains(direction.opposite().to_flag())
+                    }
+                } else {
+                    !self.is_masked(i)
+                };
+                if !valid {
+                    return Err(GridError::InvalidLink {
+                        cell: i,
+                        direction: direction.to_flag(),
+                    });
+                }
+            }
+
+            let bridge = *cell - Cell::UNDER;
+            if cell.contains(Cell::UNDER)
+                && bridge != Cell::NORTH | Cell::SOUTH
+                && bridge != Cell::EAST | Cell::WEST
+            {
+                return Err(GridError::InvalidCrossing { cell: i });
+            }
+        }
+        Ok(())
+    }
+
+    /// check_symmetry finds every cell with a link to a neighbor that doesn't link back,
+    /// returning their indices in order. Unlike validate, it doesn't stop at the first one,
+    /// so it can show everything that's wrong with a hand-made or imported maze.
+    /// Openings off the edge of the grid don't need a neighbor to link back.
+    ///
+    /// Panics if there aren't width * height cells, which validate checks for.
+    ///
+    /// ```
+    /// use minotaur::{Cell, Grid};
+    ///
+    /// let mut grid = Grid::new(3, 3);
+    /// grid.recursive_backtracker(Some(2));
+    /// assert_eq!(Ok(()), grid.check_symmetry());
+    ///
+    /// grid.cells[4] |= Cell::NORTH | Cell::SOUTH | Cell::EAST | Cell::WEST;
+    /// assert!(grid.check_symmetry().is_err());
+    /// ```
+    pub fn check_symmetry(&self) -> Result<(), Vec<usize>> {
+        let asymmetric: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| {
+                Direction::all().iter().any(|&direction| {
+                    self.cells[i].contains(direction.to_flag())
+                        && self.in_bounds(i, direction)
+                        && !self.cells[self.passage_end(i, direction)]
+                            .contains(direction.opposite().to_flag())
+                })
+            })
+            .collect();
+        if asymmetric.is_empty() {
+            Ok(())
+        } else {
+            Err(asymmetric)
+        }
+    }
+
+    /// set_wrap_horizontal joins the east and west edges of the grid, as if it were wrapped
+    /// around a cylinder: the last cell of each row is then the western neighbor of the first.
+    /// The generators carve passages across the join like anywhere else, except binary_tree,
+    /// sidewinder and recursive_division, which leave it walled.
+    pub fn set_wrap_horizontal(&mut self, wrap: bool) {
+        self.wrap_horizontal = wrap;
+    }
+
+    /// wraps_horizontally is whether the east and west edges of the grid are joined.
+    /// A grid less than 3 cells wide never wraps, since its cells would be
+    /// neighbors of themselves, or neighbors of each other twice over.
+    pub fn wraps_horizontally(&self) -> bool {
+        self.wrap_horizontal && self.width > 2
+    }
+
+    /// set_wrap_vertical joins the north and south edges of the grid, the same way
+    /// set_wrap_horizontal joins the east and west. With both, the maze is on a torus,
+    /// with no outer wall at all, so it tiles seamlessly.
+    pub fn set_wrap_vertical(&mut self, wrap: bool) {
+        self.wrap_vertical = wrap;
+    }
+
+    /// wraps_vertically is whether the north and south edges of the grid are joined.
+    /// Like wraps_horizontally, a grid less than 3 cells high never wraps.
+    pub fn wraps_vertically(&self) -> bool {
+        self.wrap_vertical && self.height > 2
+    }
+
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// index converts a row and column to the index of that cell in cells.
+    /// Rows count down from 0 at the top, and columns count right from 0 at the left,
+    /// so cells are stored one row at a time.
+    /// Panics if (row, col) is outside the grid.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let grid = Grid::new(4, 3);
+    /// assert_eq!(6, grid.index(1, 2));
+    /// assert_eq!((1, 2), grid.coords(6));
+    /// ```
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.height && col < self.width,
+            "({}, {}) is outside the {}x{} grid",
+            row,
+            col,
+            self.width,
+            self.height
+        );
+        row * self.width + col
+    }
+
+    /// coords converts the index of a cell to its (row, column), the reverse of index.
+    /// Panics if i is outside the grid.
+    pub fn coords(&self, i: usize) -> (usize, usize) {
+        assert!(
+            i < self.cells.len(),
+            "cell {} is outside the grid of {} cells",
+            i,
+            self.cells.len()
+        );
+        (i / self.width, i % self.width)
+    }
+
+    /// rows iterates over the rows of the maze from the top down, each a slice of
+    /// width cells from west to east, e.g. for renderers and exporters that work a row
+    /// at a time. A grid with no columns has no rows.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(4, 3);
+    /// grid.recursive_backtracker(Some(1));
+    ///
+    /// // Passages leading out of each cell, row by row
+    /// let passages: Vec<u32> = grid
+    ///     .rows()
+    ///     .map(|row| row.iter().map(|cell| cell.bits().count_ones()).sum())
+    ///     .collect();
+    /// assert_eq!(3, passages.len());
+    /// // Every passage is counted from both of its ends
+    /// assert_eq!(2 * 11, passages.iter().sum::<u32>());
+    /// ```
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> + '_ {
+        // Without columns there are no cells, so any chunk size will do
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// weight is the cost of moving into cell i, used by solve_astar.
+    /// Every cell costs 1 until it's given a weight with set_weight.
+    pub fn weight(&self, i: usize) -> u32 {
+        match &self.weights {
+            Some(weights) => weights[i],
+            None => 1,
+        }
+    }
+
+    /// set_weight makes moving into cell i cost weight, e.g. for mud or water that's slow to cross.
+    pub fn set_weight(&mut self, i: usize, weight: u32) {
+        let len = self.cells.len();
+        self.weights.get_or_insert_with(|| vec![1; len])[i] = weight;
+    }
+
+    /// Whether cell i has been masked off, and so isn't part of the maze.
+    pub fn is_masked(&self, i: usize) -> bool {
+        match &self.mask {
+            Some(mask) => !mask.is_on(i),
+            None => false,
+        }
+    }
+
+    // Whether (row, col) is inside the grid and not masked off.
+    // Renderers draw walls around these cells.
+    fn in_maze(&self, row: usize, col: usize) -> bool {
+        row < self.height && col < self.width && !self.is_masked(row * self.width + col)
     }
 
     fn get_rng(seed: Option<u64>) -> Lcg64Xsh32 {
@@ -61,46 +601,355 @@ impl Grid {
         }
     }
 
-    fn link_cells(&mut self, i: usize, direction: Cell) {
-        match direction {
-            Cell::NORTH => {
-                self.cells[i] |= Cell::NORTH;
-                self.cells[i - self.width] |= Cell::SOUTH;
-            }
+    fn link_cells(&mut self, i: usize, direction: Direction) {
+        let neighbor = self.neighbor(i, direction);
+        self.cells[i] |= direction.to_flag();
+        self.cells[neighbor] |= direction.opposite().to_flag();
+    }
+
+    fn unlink_cells(&mut self, i: usize, direction: Direction) {
+        let neighbor = self.neighbor(i, direction);
+        self.cells[i].remove(direction.to_flag());
+        self.cells[neighbor].remove(direction.opposite().to_flag());
+    }
 
-            Cell::SOUTH => {
-                self.cells[i] |= Cell::SOUTH;
-                self.cells[i + self.width] |= Cell::NORTH;
+    /// link opens a passage between cells a and b, which must be next to each other,
+    /// for building mazes by hand or with generators of your own.
+    /// Returns an error if either cell is outside the grid or masked off,
+    /// or if they aren't neighbors.
+    pub fn link(&mut self, a: usize, b: usize) -> Result<(), LinkError> {
+        let direction = self.direction_between(a, b)?;
+        self.link_cells(a, direction);
+        Ok(())
+    }
+
+    /// unlink closes the passage between cells a and b, putting the wall back.
+    /// It returns the same errors as link, and does nothing if there was no passage.
+    pub fn unlink(&mut self, a: usize, b: usize) -> Result<(), LinkError> {
+        let direction = self.direction_between(a, b)?;
+        self.unlink_cells(a, direction);
+        Ok(())
+    }
+
+    // Which way cell b is from its neighbor a.
+    fn direction_between(&self, a: usize, b: usize) -> Result<Direction, LinkError> {
+        for &cell in [a, b].iter() {
+            if cell >= self.cells.len() {
+                return Err(LinkError::OutOfBounds { cell });
             }
-            Cell::EAST => {
-                self.cells[i] |= Cell::EAST;
-                self.cells[i + 1] |= Cell::WEST;
+            if self.is_masked(cell) {
+                return Err(LinkError::Masked { cell });
+            }
+        }
+        self.neighbors_towards(a)
+            .find(|&(_, neighbor)| neighbor == b)
+            .map(|(direction, _)| direction)
+            .ok_or(LinkError::NotNeighbors { a, b })
+    }
+
+    /// add_opening removes the wall on the outer border of cell i in direction,
+    /// making a way into or out of the maze.
+    /// Panics if cell i is masked off, or if there is a cell in direction.
+    pub fn add_opening(&mut self, i: usize, direction: Cell) {
+        assert!(!self.is_masked(i), "cell {} is masked off", i);
+        assert!(
+            Direction::from_flag(direction).is_none_or(|d| !self.in_bounds(i, d)),
+            "cell {} is not on the {:?} border",
+            i,
+            direction
+        );
+        self.cells[i] |= direction;
+    }
+
+    /// carve_entrance_exit adds an entrance in the north wall of the first cell of the
+    /// top row, and an exit in the south wall of the last cell of the bottom row.
+    /// With a mask, these are the first and last cells in those rows that aren't masked off.
+    /// A grid that wraps vertically has no north or south wall, so it is left alone.
+    pub fn carve_entrance_exit(&mut self) {
+        if self.cells.is_empty() || self.wraps_vertically() {
+            return;
+        }
+
+        let top_row = 0..self.width;
+        if let Some(entrance) = top_row.clone().find(|&i| !self.is_masked(i)) {
+            self.add_opening(entrance, Cell::NORTH);
+        }
+        let bottom_row = self.cells.len() - self.width..self.cells.len();
+        if let Some(exit) = bottom_row.rev().find(|&i| !self.is_masked(i)) {
+            self.add_opening(exit, Cell::SOUTH);
+        }
+    }
+
+    // Link every cell with all of its neighbors, leaving no internal walls.
+    // The seam of a wrapping grid is left walled.
+    fn link_all(&mut self) {
+        for i in 0..self.cells.len() {
+            for &direction in [Direction::South, Direction::East].iter() {
+                if self.valid_direction(i, direction) && !self.crosses_seam(i, direction) {
+                    self.link_cells(i, direction);
+                }
             }
-            Cell::WEST => {
-                self.cells[i] |= Cell::WEST;
-                self.cells[i - 1] |= Cell::EAST;
+        }
+    }
+
+    // Link the pieces that a mask cut a finished maze into, so that every
+    // connected stretch of unmasked cells ends up as one spanning tree.
+    // Without a mask there is nothing to join, and the random numbers aren't touched.
+    fn join_masked_pieces<R: Rng>(&mut self, rng: &mut R) {
+        if self.mask.is_none() {
+            return;
+        }
+        let mut walls = Vec::new();
+        for i in 0..self.cells.len() {
+            for &direction in [Direction::South, Direction::East].iter() {
+                if self.valid_direction(i, direction)
+                    && !self.crosses_seam(i, direction)
+                    && !self.cells[i].contains(direction.to_flag())
+                {
+                    walls.push((i, direction, self.neighbor(i, direction)));
+                }
             }
-            _ => panic!(),
-        };
+        }
+        self.join_across(rng, &mut walls);
+    }
+
+    // Whether there is a cell in direction from cell i, masked or not.
+    fn in_bounds(&self, i: usize, direction: Direction) -> bool {
+        match direction {
+            Direction::North => self.wraps_vertically() || i >= self.width,
+            Direction::South => self.wraps_vertically() || i + self.width < self.cells.len(),
+            Direction::East => self.wraps_horizontally() || !(i + 1).is_multiple_of(self.width),
+            Direction::West => self.wraps_horizontally() || !i.is_multiple_of(self.width),
+        }
     }
 
-    fn valid_direction(&self, i: usize, direction: Cell) -> bool {
+    // Whether going from cell i in direction wraps around from one edge of the grid to the other.
+    fn crosses_seam(&self, i: usize, direction: Direction) -> bool {
         match direction {
-            Cell::NORTH => i >= self.width,
-            Cell::SOUTH => i + self.width < self.cells.len(),
-            Cell::EAST => (i + 1) % self.width != 0,
-            Cell::WEST => i % self.width != 0,
-            _ => false,
+            Direction::North => self.wraps_vertically() && i < self.width,
+            Direction::South => self.wraps_vertically() && i + self.width >= self.cells.len(),
+            Direction::East => self.wraps_horizontally() && (i + 1).is_multiple_of(self.width),
+            Direction::West => self.wraps_horizontally() && i.is_multiple_of(self.width),
         }
     }
 
-    fn neighbor(&self, i: usize, direction: Cell) -> usize {
+    fn valid_direction(&self, i: usize, direction: Direction) -> bool {
+        self.in_bounds(i, direction)
+            && !self.is_masked(i)
+            && !self.is_masked(self.neighbor(i, direction))
+    }
+
+    fn neighbor(&self, i: usize, direction: Direction) -> usize {
         match direction {
-            Cell::NORTH => i - self.width,
-            Cell::SOUTH => i + self.width,
-            Cell::EAST => i + 1,
-            Cell::WEST => i - 1,
-            _ => panic!(),
+            Direction::North if self.crosses_seam(i, direction) => {
+                i + self.cells.len() - self.width
+            }
+            Direction::South if self.crosses_seam(i, direction) => {
+                i + self.width - self.cells.len()
+            }
+            Direction::North => i - self.width,
+            Direction::South => i + self.width,
+            Direction::East if self.crosses_seam(i, direction) => i + 1 - self.width,
+            Direction::West if self.crosses_seam(i, direction) => i + self.width - 1,
+            Direction::East => i + 1,
+            Direction::West => i - 1,
+        }
+    }
+
+    // The cell that a passage from cell i in direction leads to.
+    // That's the neighbor, unless the passage tunnels beneath it.
+    fn passage_end(&self, i: usize, direction: Direction) -> usize {
+        let neighbor = self.neighbor(i, direction);
+        let cell = self.cells[neighbor];
+        if cell.contains(Cell::UNDER)
+            && !cell.contains(direction.opposite().to_flag())
+            && self.in_bounds(neighbor, direction)
+        {
+            self.neighbor(neighbor, direction)
+        } else {
+            neighbor
+        }
+    }
+
+    /// links returns the indices of the cells that cell i has an open passage to.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(5, 5);
+    /// grid.recursive_backtracker(Some(1));
+    ///
+    /// // A perfect maze connects every cell, so a traversal reaches them all
+    /// let mut visited = vec![false; grid.cells.len()];
+    /// let mut stack = vec![0];
+    /// visited[0] = true;
+    /// while let Some(cell) = stack.pop() {
+    ///     for next in grid.links(cell) {
+    ///         if !visited[next] {
+    ///             visited[next] = true;
+    ///             stack.push(next);
+    ///         }
+    ///     }
+    /// }
+    /// assert!(visited.iter().all(|&v| v));
+    /// ```
+    pub fn links(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        IntoIterator::into_iter(Direction::all()).filter_map(move |direction| {
+            if self.cells[i].contains(direction.to_flag()) && self.valid_direction(i, direction) {
+                Some(self.passage_end(i, direction))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// are_linked is whether there's a passage straight from cell a to cell b, as links
+    /// gives them, such as between neighbors or across the seam of a wrapping grid.
+    /// Cells that aren't neighbors, or aren't in the grid at all, aren't linked.
+    /// A passage tunneling under a cell links the cells on either side of it instead.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(3, 3);
+    /// grid.link(4, 5).unwrap();
+    /// assert!(grid.are_linked(4, 5));
+    /// assert!(grid.are_linked(5, 4));
+    /// assert!(!grid.are_linked(4, 1));
+    /// ```
+    pub fn are_linked(&self, a: usize, b: usize) -> bool {
+        a < self.cells.len() && self.links(a).any(|neighbor| neighbor == b)
+    }
+
+    /// to_adjacency lists, for every cell, the cells it has an open passage to,
+    /// the same as links. This is the maze as a plain graph, for use with other
+    /// graph algorithms and libraries.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    /// use std::collections::VecDeque;
+    ///
+    /// let mut grid = Grid::new(5, 5);
+    /// grid.recursive_backtracker(Some(1));
+    /// let adjacency = grid.to_adjacency();
+    ///
+    /// // A breadth-first search finds how far each cell is from the first
+    /// let mut distances = vec![None; adjacency.len()];
+    /// let mut queue = VecDeque::new();
+    /// distances[0] = Some(0);
+    /// queue.push_back(0);
+    /// while let Some(cell) = queue.pop_front() {
+    ///     for &next in &adjacency[cell] {
+    ///         if distances[next].is_none() {
+    ///             distances[next] = Some(distances[cell].unwrap() + 1);
+    ///             queue.push_back(next);
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(grid.distances(0), distances);
+    /// ```
+    pub fn to_adjacency(&self) -> Vec<Vec<usize>> {
+        (0..self.cells.len())
+            .map(|i| self.links(i).collect())
+            .collect()
+    }
+
+    /// edges lists every passage of the maze once, as the pair of cells it joins
+    /// with the lower index first, in sorted order. Openings in the outer wall aren't included.
+    /// A perfect maze of n cells has exactly n - 1 edges.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(2, 2);
+    /// grid.link(0, 1).unwrap();
+    /// grid.link(1, 3).unwrap();
+    /// grid.link(3, 2).unwrap();
+    /// assert_eq!(vec![(0, 1), (1, 3), (2, 3)], grid.edges());
+    /// ```
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = (0..self.cells.len())
+            .flat_map(|i| self.links(i).filter(move |&j| i < j).map(move |j| (i, j)))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    /// from_edges creates a width x height maze with a passage for each of edges, and
+    /// no others, e.g. to bring in a maze made by another program as a list of edges.
+    /// Each edge is a pair of neighboring cells, either way round, so the edges of
+    /// a Grid without tunnels make the same maze again.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let grid = Grid::from_edges(2, 2, &[(0, 1), (3, 1), (2, 3)]).unwrap();
+    /// assert!(grid.is_perfect());
+    /// assert_eq!(vec![(0, 1), (1, 3), (2, 3)], grid.edges());
+    /// ```
+    ///
+    /// Fails if an edge joins cells that aren't neighbors, or if an edge is given twice.
+    pub fn from_edges(
+        width: usize,
+        height: usize,
+        edges: &[(usize, usize)],
+    ) -> Result<Grid, GridError> {
+        let mut grid = Grid::new(width, height);
+        for &(a, b) in edges.iter() {
+            let direction = grid
+                .direction_between(a, b)
+                .map_err(|_| GridError::InvalidEdge { a, b })?;
+            if grid.cells[a].contains(direction.to_flag()) {
+                return Err(GridError::DuplicateEdge { a, b });
+            }
+            grid.link_cells(a, direction);
+        }
+        Ok(grid)
+    }
+
+    /// neighbors returns the direction and index of every cell next to cell i,
+    /// whether or not there is a wall in between. Masked off cells are not included.
+    ///
+    /// ```
+    /// use minotaur::{Cell, Grid};
+    ///
+    /// let grid = Grid::new(3, 3);
+    /// // The top left corner only has neighbors to the south and east
+    /// let corner: Vec<(Cell, usize)> = grid.neighbors(0).collect();
+    /// assert_eq!(vec![(Cell::SOUTH, 3), (Cell::EAST, 1)], corner);
+    /// // The center has all four
+    /// assert_eq!(4, grid.neighbors(4).count());
+    /// ```
+    pub fn neighbors(&self, i: usize) -> impl Iterator<Item = (Cell, usize)> + '_ {
+        self.neighbors_towards(i)
+            .map(|(direction, neighbor)| (direction.to_flag(), neighbor))
+    }
+
+    // Like neighbors, but with the Direction of each neighbor.
+    fn neighbors_towards(&self, i: usize) -> impl Iterator<Item = (Direction, usize)> + '_ {
+        IntoIterator::into_iter(Direction::all())
+            .filter(move |&direction| i < self.cells.len() && self.valid_direction(i, direction))
+            .map(move |direction| (direction, self.neighbor(i, direction)))
+    }
+
+    /// try_neighbor returns the index of the cell next to cell i in direction, or None
+    /// if there isn't one: at the edge of the grid, unless it wraps around, if either
+    /// cell is masked off, if i is outside the grid, or if direction isn't just one of
+    /// NORTH, SOUTH, EAST and WEST.
+    ///
+    /// ```
+    /// use minotaur::{Cell, Grid};
+    ///
+    /// let grid = Grid::new(3, 3);
+    /// assert_eq!(Some(5), grid.try_neighbor(4, Cell::EAST));
+    /// assert_eq!(None, grid.try_neighbor(5, Cell::EAST));
+    /// ```
+    pub fn try_neighbor(&self, i: usize, direction: Cell) -> Option<usize> {
+        let direction = Direction::from_flag(direction)?;
+        if i < self.cells.len() && self.valid_direction(i, direction) {
+            Some(self.neighbor(i, direction))
+        } else {
+            None
         }
     }
 
@@ -114,20 +963,45 @@ impl Grid {
     /// After choosing a direction, link this cell with its neighbor in that direction
     ///
     /// The only cell that will not have a valid direction to choose from is the northeastern corner.
+    /// This leaves an unbroken passage along the northern and eastern edges.
     pub fn binary_tree(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
+        self.binary_tree_with_rng(&mut Grid::get_rng(seed));
+    }
 
-        for i in 0..self.cells.len() {
-            let north_valid = self.valid_direction(i, Cell::NORTH);
-            let east_valid = self.valid_direction(i, Cell::EAST);
+    /// binary_tree_with_rng works like binary_tree, but draws its random numbers from rng.
+    pub fn binary_tree_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.biased_binary_tree(rng, Cell::NORTH, Cell::EAST);
+    }
 
-            if north_valid && (!east_valid || rng.gen()) {
-                self.link_cells(i, Cell::NORTH);
-            } else if east_valid {
-                self.link_cells(i, Cell::EAST);
-            }
-        }
+    /// binary_tree_with_bias works like binary_tree, but chooses between vertical and horizontal
+    /// instead of NORTH and EAST. The unbroken passages run along those two edges of the maze,
+    /// so SOUTH and WEST put them along the bottom and left instead.
+    ///
+    /// Panics if vertical isn't NORTH or SOUTH, or horizontal isn't EAST or WEST.
+    pub fn binary_tree_with_bias(&mut self, seed: Option<u64>, vertical: Cell, horizontal: Cell) {
+        self.biased_binary_tree(&mut Grid::get_rng(seed), vertical, horizontal);
+    }
+
+    fn biased_binary_tree<R: Rng>(&mut self, rng: &mut R, vertical: Cell, horizontal: Cell) {
+        let vertical = match vertical {
+            Cell::NORTH => Direction::North,
+            Cell::SOUTH => Direction::South,
+            _ => panic!("{:?} is not NORTH or SOUTH", vertical),
+        };
+        let horizontal = match horizontal {
+            Cell::EAST => Direction::East,
+            Cell::WEST => Direction::West,
+            _ => panic!("{:?} is not EAST or WEST", horizontal),
+        };
+
+        // SliceGrid never links across the seam of a wrapping grid, which would close a loop
+        let mask = self.mask.as_ref();
+        SliceGrid::new(&mut self.cells, self.width, self.height).binary_tree_within(
+            rng,
+            vertical,
+            horizontal,
+            |i| mask.is_none_or(|mask| mask.is_on(i)),
+        );
     }
 
     /// sidewinder populates the maze according to the following algorithm:
@@ -143,483 +1017,2491 @@ impl Grid {
     /// But if NORTH was chosen, then select at random one of the cells from the local run and link
     /// it with its NORTHERN neighbor. The local run is reset. Continue from the EASTERN neighbor.
     pub fn sidewinder(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
+        self.sidewinder_with_rng(&mut Grid::get_rng(seed));
+    }
+
+    /// sidewinder_with_rng works like sidewinder, but draws its random numbers from rng.
+    pub fn sidewinder_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.biased_sidewinder(rng, 0.5);
+    }
 
-        // We start on the Western cell on the second row - this is the first cell that can
-        // be a valid "NORTH"
-        let mut run_start = self.width;
+    /// sidewinder_with_bias works like sidewinder, but when both NORTH and EAST are valid,
+    /// chooses EAST with probability horizontal_prob. Low values close runs quickly,
+    /// giving lots of short vertical passages, and high values give long corridors running east.
+    /// Panics if horizontal_prob isn't between 0 and 1.
+    pub fn sidewinder_with_bias(&mut self, seed: Option<u64>, horizontal_prob: f64) {
+        assert!(
+            (0.0..=1.0).contains(&horizontal_prob),
+            "a horizontal_prob of {} isn't between 0 and 1",
+            horizontal_prob
+        );
+        self.biased_sidewinder(&mut Grid::get_rng(seed), horizontal_prob);
+    }
 
-        for i in 0..self.cells.len() {
-            let north_valid = self.valid_direction(i, Cell::NORTH);
-            let east_valid = self.valid_direction(i, Cell::EAST);
-
-            if north_valid && (!east_valid || rng.gen()) {
-                let chosen_cell = rng.gen_range(run_start, i + 1);
-                self.link_cells(chosen_cell, Cell::NORTH);
-                // Run resets
-                run_start = i + 1;
-            } else if east_valid {
-                self.link_cells(i, Cell::EAST);
-            } else {
-                run_start = i + 1;
-            }
-        }
+    fn biased_sidewinder<R: Rng>(&mut self, rng: &mut R, horizontal_prob: f64) {
+        // Passages stop at the seams of a wrapping grid, so they don't join up into loops
+        let mask = self.mask.as_ref();
+        SliceGrid::new(&mut self.cells, self.width, self.height).sidewinder_within(
+            rng,
+            horizontal_prob,
+            |i| mask.is_none_or(|mask| mask.is_on(i)),
+        );
     }
 
-    /// aldous_broder populates a maze in an unbiased way.
-    /// Basically, first, a cell is chosen at random and considered "visited."
-    /// Travel in a random direction. If the next cell is "unvisited", then
-    /// link the two cells. Continue until all cells have been visited.
+    /// aldous_broder populates the maze with maze::aldous_broder.
     pub fn aldous_broder(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
-        const DIRECTIONS: [Cell; 4] = [Cell::NORTH, Cell::SOUTH, Cell::EAST, Cell::WEST];
-
-        // Keep track of all visited cells.
-        let mut visited = vec![false; self.cells.len()];
+        self.aldous_broder_with_rng(&mut Grid::get_rng(seed));
+    }
 
-        // Starting cell must be chosen at random.
-        let mut current_cell = rng.gen_range(0, self.cells.len());
-        visited[current_cell] = true;
-        let mut num_visited = 1;
+    /// aldous_broder_with_rng works like aldous_broder, but draws its random numbers from rng.
+    pub fn aldous_broder_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        maze::aldous_broder(self, rng);
+    }
 
-        while num_visited < self.cells.len() {
-            // Loop until we've found a valid direction - only an issue at the maze borders
-            let mut direction = Cell::default();
-            while !self.valid_direction(current_cell, direction) {
-                direction = *DIRECTIONS.choose(&mut rng).unwrap();
-            }
+    /// aldous_broder_from populates the maze with maze::aldous_broder_from, walking from cell start.
+    /// Panics if start is out of bounds or masked off.
+    pub fn aldous_broder_from(&mut self, seed: Option<u64>, start: usize) {
+        self.reset();
+        maze::aldous_broder_from(self, &mut Grid::get_rng(seed), start);
+    }
 
-            let next_cell = self.neighbor(current_cell, direction);
+    /// aldous_broder_cancellable populates the maze like aldous_broder, but gives up
+    /// as soon as cancel is set, e.g. from another thread when a user gets tired of waiting.
+    /// The maze is then left partly generated: every passage carved so far links both ways,
+    /// but not every cell is connected.
+    pub fn aldous_broder_cancellable(
+        &mut self,
+        seed: Option<u64>,
+        cancel: &AtomicBool,
+    ) -> Result<(), Cancelled> {
+        self.reset();
+        maze::aldous_broder_cancellable(self, &mut Grid::get_rng(seed), cancel)
+    }
 
-            // If we haven't visited yet, link the cells up.
-            // Either way, keep random walking from here
-            if !visited[next_cell] {
-                self.link_cells(current_cell, direction);
-                visited[next_cell] = true;
-                num_visited += 1;
-            }
-            current_cell = next_cell;
-        }
+    /// aldous_broder_with_progress populates the maze like aldous_broder, calling progress
+    /// with the fraction of cells carved so far, from 0.0 to 1.0, e.g. to drive a progress bar.
+    /// See maze::aldous_broder_with_progress for how often it's called.
+    pub fn aldous_broder_with_progress(&mut self, seed: Option<u64>, progress: impl FnMut(f64)) {
+        self.reset();
+        maze::aldous_broder_with_progress(self, &mut Grid::get_rng(seed), progress);
     }
 
-    /// wilsons populates a maze in an unbiased way.
-    /// First, some random cell is set to be "visited."
-    /// Then, some other random cell is "started." From there,
-    /// travel randomly until you hit a "visited" cell. Once you
-    /// hit a "visited" cell, connect all the links from the "started"
-    /// cell. Then start over, choosing a new "unvisited" cell.
-    ///
-    /// The trick is that there is a "loop removal" step. So while looking
-    /// for a "visited" cell, if you loop back to a cell you've travelling through
-    /// this run, then remove the loop you just made.
+    /// wilsons populates the maze with maze::wilsons.
     pub fn wilsons(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
-        const DIRECTIONS: [Cell; 4] = [Cell::NORTH, Cell::SOUTH, Cell::EAST, Cell::WEST];
+        self.wilsons_with_rng(&mut Grid::get_rng(seed));
+    }
 
-        // Keep track of all unvisited cells.
-        let mut unvisited = HashSet::new();
-        for i in 0..self.cells.len() {
-            unvisited.insert(i);
-        }
+    /// wilsons_with_rng works like wilsons, but draws its random numbers from rng.
+    pub fn wilsons_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        maze::wilsons(self, rng);
+    }
 
-        // Randomly set a single cell to be visited
-        let initial: usize = rng.gen_range(0, self.cells.len());
-        unvisited.remove(&initial);
+    /// wilsons_from populates the maze with maze::wilsons_from, with cell start as the first visited cell.
+    /// Panics if start is out of bounds or masked off.
+    pub fn wilsons_from(&mut self, seed: Option<u64>, start: usize) {
+        self.reset();
+        maze::wilsons_from(self, &mut Grid::get_rng(seed), start);
+    }
 
-        let mut unvisited_to_choose_from = unvisited.clone().into_iter().collect::<Vec<usize>>();
+    /// wilsons_cancellable populates the maze like wilsons, but gives up as soon as cancel
+    /// is set, leaving the maze partly generated like aldous_broder_cancellable.
+    pub fn wilsons_cancellable(
+        &mut self,
+        seed: Option<u64>,
+        cancel: &AtomicBool,
+    ) -> Result<(), Cancelled> {
+        self.reset();
+        maze::wilsons_cancellable(self, &mut Grid::get_rng(seed), cancel)
+    }
 
-        while !unvisited.is_empty() {
-            // Performance optimization heuristic
-            if unvisited.len() * unvisited.len() < unvisited_to_choose_from.len() {
-                unvisited_to_choose_from = unvisited.clone().into_iter().collect::<Vec<usize>>();
-            }
+    /// wilsons_with_progress populates the maze like wilsons, calling progress
+    /// like aldous_broder_with_progress.
+    pub fn wilsons_with_progress(&mut self, seed: Option<u64>, progress: impl FnMut(f64)) {
+        self.reset();
+        maze::wilsons_with_progress(self, &mut Grid::get_rng(seed), progress);
+    }
 
-            let mut path_init = *unvisited_to_choose_from[..].choose(&mut rng).unwrap();
-            while !unvisited.contains(&path_init) {
-                path_init = *unvisited_to_choose_from[..].choose(&mut rng).unwrap();
-            }
+    /// hunt_and_kill populates the maze with maze::hunt_and_kill.
+    pub fn hunt_and_kill(&mut self, seed: Option<u64>) {
+        self.hunt_and_kill_with_rng(&mut Grid::get_rng(seed));
+    }
 
-            let mut current_cell = path_init;
-            let mut path = HashMap::new();
+    /// hunt_and_kill_with_rng works like hunt_and_kill, but draws its random numbers from rng.
+    pub fn hunt_and_kill_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        maze::hunt_and_kill(self, rng);
+    }
 
-            // Loop until we have finally reached a cell that's already visited.
-            while unvisited.contains(&current_cell) {
-                // Loop until we've found a valid direction - only an issue at the maze borders
-                let mut direction = Cell::default();
-                while !self.valid_direction(current_cell, direction) {
-                    direction = *DIRECTIONS.choose(&mut rng).unwrap();
-                }
-                path.insert(current_cell, direction);
-                current_cell = self.neighbor(current_cell, direction);
-            }
+    /// hunt_and_kill_from populates the maze with maze::hunt_and_kill_from, walking from cell start.
+    /// Panics if start is out of bounds or masked off.
+    pub fn hunt_and_kill_from(&mut self, seed: Option<u64>, start: usize) {
+        self.reset();
+        maze::hunt_and_kill_from(self, &mut Grid::get_rng(seed), start);
+    }
 
-            current_cell = path_init;
-            while unvisited.contains(&current_cell) {
-                let direction = *path.get(&current_cell).unwrap();
-                unvisited.remove(&current_cell);
-                self.link_cells(current_cell, direction);
-                current_cell = self.neighbor(current_cell, direction);
-            }
-        }
+    /// recursive_backtracker populates the maze with maze::recursive_backtracker.
+    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
+        self.recursive_backtracker_with_rng(&mut Grid::get_rng(seed));
     }
 
-    pub fn hunt_and_kill(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
-        const DIRECTIONS: [Cell; 4] = [Cell::NORTH, Cell::SOUTH, Cell::EAST, Cell::WEST];
-
-        // Keep track of all visited cells.
-        let mut visited_cells = HashSet::new();
-
-        // Randomly set a single cell to be visited
-        let mut current_cell: usize = rng.gen_range(0, self.cells.len());
-        visited_cells.insert(current_cell);
-        // Optimization: maintain frontier of possible cells that are
-        // potentially adjacent to a visited cell
-        let mut frontier = BinaryHeap::new();
-        frontier.push(Reverse(current_cell));
-
-        while !frontier.is_empty() {
-            // Loop until we boxed ourselves in with visited cells
-            loop {
-                let mut directions = Vec::new();
-                for direction in DIRECTIONS.iter() {
-                    if self.valid_direction(current_cell, *direction) {
-                        let neighbor = self.neighbor(current_cell, *direction);
-                        if !visited_cells.contains(&neighbor) {
-                            directions.push(*direction);
-                            frontier.push(Reverse(neighbor));
-                        }
-                    }
-                }
-                if let Some(direction) = directions[..].choose(&mut rng) {
-                    self.link_cells(current_cell, *direction);
-                    current_cell = self.neighbor(current_cell, *direction);
-                    visited_cells.insert(current_cell);
-                } else {
-                    break;
-                }
-            }
-            // Boxed in! Time to iterate through maze and select first unvisited cell that borders
-            // a visited cell
-            while visited_cells.contains(&current_cell) && !frontier.is_empty() {
-                current_cell = frontier.pop().unwrap().0;
-            }
+    /// recursive_backtracker_with_rng works like recursive_backtracker, but draws its random numbers from rng.
+    pub fn recursive_backtracker_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        maze::recursive_backtracker(self, rng);
+    }
 
-            if frontier.is_empty() {
-                break;
-            }
-            // Now link it with the adjacent cell
-            visited_cells.insert(current_cell);
-            for direction in DIRECTIONS.iter() {
-                if self.valid_direction(current_cell, *direction) {
-                    let neighbor = self.neighbor(current_cell, *direction);
-                    // Found the adjacent visited cell!
-                    if visited_cells.contains(&neighbor) {
-                        self.link_cells(current_cell, *direction);
-                        break;
-                    }
-                }
-            }
-        }
+    /// recursive_backtracker_from populates the maze with maze::recursive_backtracker_from, walking from cell start.
+    /// Panics if start is out of bounds or masked off.
+    pub fn recursive_backtracker_from(&mut self, seed: Option<u64>, start: usize) {
+        self.reset();
+        maze::recursive_backtracker_from(self, &mut Grid::get_rng(seed), start);
     }
 
-    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
-        self.cells = vec![Cell::default(); self.height * self.width];
-        let mut rng = Grid::get_rng(seed);
-        const DIRECTIONS: [Cell; 4] = [Cell::NORTH, Cell::SOUTH, Cell::EAST, Cell::WEST];
-
-        // Keep track of all visited cells.
-        let mut visited_cells = HashSet::new();
-
-        // Randomly set a single cell to be visited
-        let mut current_cell: usize = rng.gen_range(0, self.cells.len());
-        visited_cells.insert(current_cell);
-        // Stack of visited cells
-        let mut cell_stack = Vec::new();
-        cell_stack.push(current_cell);
-
-        while !cell_stack.is_empty() {
-            // Loop until we boxed ourselves in with visited cells
-            loop {
-                let mut directions = Vec::new();
-                for direction in DIRECTIONS.iter() {
-                    if self.valid_direction(current_cell, *direction) {
-                        let neighbor = self.neighbor(current_cell, *direction);
-                        if !visited_cells.contains(&neighbor) {
-                            directions.push(*direction);
-                        }
-                    }
+    /// recursive_backtracker_straight populates the maze with maze::recursive_backtracker_straight,
+    /// carrying on in the same direction with probability straightness wherever it can.
+    /// 0 is as twisty as recursive_backtracker, and values near 1 give long straight corridors.
+    /// Panics if straightness isn't between 0 and 1.
+    pub fn recursive_backtracker_straight(&mut self, seed: Option<u64>, straightness: f64) {
+        assert!(
+            (0.0..=1.0).contains(&straightness),
+            "a straightness of {} isn't between 0 and 1",
+            straightness
+        );
+        self.reset();
+        maze::recursive_backtracker_straight(self, &mut Grid::get_rng(seed), straightness);
+    }
+
+    /// prims populates the maze with maze::prims.
+    pub fn prims(&mut self, seed: Option<u64>) {
+        self.prims_with_rng(&mut Grid::get_rng(seed));
+    }
+
+    /// prims_with_rng works like prims, but draws its random numbers from rng.
+    pub fn prims_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        maze::prims(self, rng);
+    }
+
+    /// growing_tree populates the maze with maze::growing_tree, picking cells to grow from
+    /// according to strategy. See GrowingTreeStrategy for how each one shapes the maze.
+    pub fn growing_tree(&mut self, seed: Option<u64>, strategy: GrowingTreeStrategy) {
+        self.growing_tree_with_rng(&mut Grid::get_rng(seed), strategy);
+    }
+
+    /// growing_tree_with_rng works like growing_tree, but draws its random numbers from rng.
+    pub fn growing_tree_with_rng<R: Rng>(&mut self, rng: &mut R, strategy: GrowingTreeStrategy) {
+        self.reset();
+        maze::growing_tree(self, rng, strategy);
+    }
+
+    /// kruskals populates the maze according to the following algorithm:
+    /// Every cell starts out in its own set. Gather every internal wall between
+    /// two adjacent cells and shuffle them.
+    ///
+    /// Go through the walls in order. If the cells on either side of a wall are in
+    /// different sets, link them and merge their sets. Otherwise, linking them would
+    /// create a loop, so leave the wall in place.
+    pub fn kruskals(&mut self, seed: Option<u64>) {
+        self.kruskals_with_rng(&mut Grid::get_rng(seed));
+    }
+
+    /// kruskals_with_rng works like kruskals, but draws its random numbers from rng.
+    pub fn kruskals_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+
+        // Only SOUTH and EAST are needed to cover every wall exactly once
+        let mut edges = Vec::with_capacity(2 * self.cells.len());
+        for i in 0..self.cells.len() {
+            for &direction in [Direction::South, Direction::East].iter() {
+                if self.valid_direction(i, direction) {
+                    edges.push((i, direction));
+                }
+            }
+        }
+        edges.shuffle(rng);
+
+        let mut sets = DisjointSet::new(self.cells.len());
+        for (i, direction) in edges {
+            let neighbor = self.neighbor(i, direction);
+            if sets.union(i, neighbor) {
+                self.link_cells(i, direction);
+            }
+        }
+    }
+
+    /// recursive_division populates the maze by adding walls rather than carving passages.
+    /// Start with a grid that has no internal walls at all.
+    ///
+    /// Split the area in two with a wall, leaving a single random gap in it.
+    /// Wide areas are split with a vertical wall, tall areas with a horizontal one,
+    /// and square areas are split either way at random.
+    /// Then divide each of the two halves the same way, until the areas are only one cell wide.
+    ///
+    /// This tends to produce long straight corridors and a "boxy" look.
+    pub fn recursive_division(&mut self, seed: Option<u64>) {
+        self.recursive_division_with_rng(&mut Grid::get_rng(seed));
+    }
+
+    /// recursive_division_with_rng works like recursive_division, but draws its random numbers from rng.
+    pub fn recursive_division_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        self.link_all();
+
+        // Areas still to divide, as (column, row, width, height)
+        let mut areas = vec![(0, 0, self.width, self.height)];
+
+        while let Some((x, y, width, height)) = areas.pop() {
+            if width < 2 || height < 2 {
+                continue;
+            }
+
+            let horizontal = if height == width {
+                rng.gen()
+            } else {
+                height > width
+            };
+
+            if horizontal {
+                // The wall runs along the south side of wall_row
+                let wall_row = rng.gen_range(y, y + height - 1);
+                let gap = rng.gen_range(x, x + width);
+                for col in (x..x + width).filter(|&col| col != gap) {
+                    self.unlink_cells(wall_row * self.width + col, Direction::South);
+                }
+                areas.push((x, y, width, wall_row - y + 1));
+                areas.push((x, wall_row + 1, width, y + height - wall_row - 1));
+            } else {
+                // The wall runs along the east side of wall_col
+                let wall_col = rng.gen_range(x, x + width - 1);
+                let gap = rng.gen_range(y, y + height);
+                for row in (y..y + height).filter(|&row| row != gap) {
+                    self.unlink_cells(row * self.width + wall_col, Direction::East);
+                }
+                areas.push((x, y, wall_col - x + 1, height));
+                areas.push((wall_col + 1, y, x + width - wall_col - 1, height));
+            }
+        }
+        self.join_masked_pieces(rng);
+    }
+
+    /// dead_ends returns the cells that have exactly one open passage, in order.
+    pub fn dead_ends(&self) -> Vec<usize> {
+        (0..self.cells.len())
+            .filter(|&i| self.cells[i].bits().count_ones() == 1)
+            .collect()
+    }
+
+    /// braid removes dead ends from the maze, which creates loops.
+    /// Visit the dead ends in a random order. With probability dead_end_ratio,
+    /// link the dead end with one of the neighbors it isn't linked to yet.
+    /// Neighbors that are dead ends themselves are preferred, since that removes two at once.
+    ///
+    /// With a dead_end_ratio of 1.0, no dead ends are left, as long as every
+    /// cell has at least two neighbors.
+    pub fn braid(&mut self, seed: Option<u64>, dead_end_ratio: f64) {
+        self.braid_with_rng(&mut Grid::get_rng(seed), dead_end_ratio);
+    }
+
+    /// braid_with_rng works like braid, but draws its random numbers from rng.
+    pub fn braid_with_rng<R: Rng>(&mut self, rng: &mut R, dead_end_ratio: f64) {
+        let mut dead_ends = self.dead_ends();
+        dead_ends.shuffle(rng);
+
+        for cell in dead_ends {
+            // An earlier link may have already removed this dead end
+            if self.cells[cell].bits().count_ones() != 1 || rng.gen::<f64>() >= dead_end_ratio {
+                continue;
+            }
+
+            let unlinked = Direction::all()
+                .iter()
+                .copied()
+                .filter(|&direction| {
+                    !self.cells[cell].contains(direction.to_flag())
+                        && self.valid_direction(cell, direction)
+                })
+                .collect::<Vec<Direction>>();
+            let dead_end_neighbors = unlinked
+                .iter()
+                .copied()
+                .filter(|&direction| {
+                    let neighbor = self.neighbor(cell, direction);
+                    self.cells[neighbor].bits().count_ones() == 1
+                })
+                .collect::<Vec<Direction>>();
+
+            let candidates = if dead_end_neighbors.is_empty() {
+                unlinked
+            } else {
+                dead_end_neighbors
+            };
+            if let Some(&direction) = candidates.choose(rng) {
+                self.link_cells(cell, direction);
+            }
+        }
+    }
+
+    /// add_loops opens count more walls between neighboring cells, chosen at random from
+    /// all the walls inside the maze, so a perfect maze gets count loops and more than one
+    /// way through. Unlike braid, the walls can be anywhere, not just at dead ends.
+    /// Walls next to masked off cells or cells with a passage tunneling beneath them
+    /// are left alone. If there are fewer than count walls left to open, all of them are.
+    pub fn add_loops(&mut self, seed: Option<u64>, count: usize) {
+        self.add_loops_with_rng(&mut Grid::get_rng(seed), count);
+    }
+
+    /// add_loops_with_rng works like add_loops, but draws its random numbers from rng.
+    pub fn add_loops_with_rng<R: Rng>(&mut self, rng: &mut R, count: usize) {
+        // Each wall is counted once, from the cell on its NORTH or WEST side
+        let mut walls = Vec::new();
+        for i in 0..self.cells.len() {
+            for &direction in [Direction::South, Direction::East].iter() {
+                if self.cells[i].contains(direction.to_flag())
+                    || !self.valid_direction(i, direction)
+                {
+                    continue;
                 }
-                if let Some(direction) = directions[..].choose(&mut rng) {
-                    self.link_cells(current_cell, *direction);
-                    current_cell = self.neighbor(current_cell, *direction);
-                    visited_cells.insert(current_cell);
-                    cell_stack.push(current_cell);
+                let neighbor = self.neighbor(i, direction);
+                if !self.cells[i].contains(Cell::UNDER)
+                    && !self.cells[neighbor].contains(Cell::UNDER)
+                {
+                    walls.push((i, direction));
+                }
+            }
+        }
+
+        let count = count.min(walls.len());
+        let (opened, _) = walls.partial_shuffle(rng, count);
+        for &(i, direction) in opened.iter() {
+            self.link_cells(i, direction);
+        }
+    }
+
+    /// carve_room opens up a room_width by room_height rectangle of the maze, with top_left
+    /// as its top left cell, by linking every cell in it to its neighbors in it.
+    /// Passages leading out of the room are left as they are, so the room stays joined to the
+    /// rest of the maze. Any passage tunneling under a cell in the room now opens into it.
+    ///
+    /// Masked off cells in the rectangle stay walled off.
+    /// Panics if the rectangle doesn't fit in the grid.
+    pub fn carve_room(&mut self, top_left: usize, room_width: usize, room_height: usize) {
+        let (top, left) = self.coords(top_left);
+        assert!(
+            left + room_width <= self.width && top + room_height <= self.height,
+            "a {}x{} room at ({}, {}) doesn't fit in the {}x{} grid",
+            room_width,
+            room_height,
+            top,
+            left,
+            self.width,
+            self.height
+        );
+
+        for row in top..top + room_height {
+            for col in left..left + room_width {
+                let i = self.index(row, col);
+                if self.cells[i].contains(Cell::UNDER) {
+                    // The cells on either side already link to this one
+                    self.cells[i] = Cell::NORTH | Cell::SOUTH | Cell::EAST | Cell::WEST;
+                }
+                if col + 1 < left + room_width && self.valid_direction(i, Direction::East) {
+                    self.link_cells(i, Direction::East);
+                }
+                if row + 1 < top + room_height && self.valid_direction(i, Direction::South) {
+                    self.link_cells(i, Direction::South);
+                }
+            }
+        }
+    }
+
+    pub fn to_image(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        self.to_image_sized(
+            cell_size,
+            cell_size,
+            wall_size,
+            background_pixel,
+            wall_pixel,
+        )
+    }
+
+    /// to_image_with_border renders the maze like to_image, but leaves out the wall around
+    /// the outside of the maze unless draw_border is set, e.g. for a maze that's part of
+    /// a larger image. The image is the same size either way, with the walls inside the
+    /// maze running right up to its edges. For just a way in and out of the maze,
+    /// use carve_entrance_exit or add_opening instead.
+    pub fn to_image_with_border(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        draw_border: bool,
+    ) -> RgbImage {
+        if draw_border {
+            self.to_image(cell_size, wall_size, background_pixel, wall_pixel)
+        } else {
+            self.without_border()
+                .to_image(cell_size, wall_size, background_pixel, wall_pixel)
+        }
+    }
+
+    /// montage renders each of grids with to_image and tiles them into one contact sheet,
+    /// cols to a row in the order they're given, with gap pixels of background between
+    /// them, e.g. to compare algorithms or seeds at a glance. Grids of different sizes
+    /// line up in columns as wide as the widest image in them, and rows as tall as
+    /// the tallest, with each image in the top left of its place.
+    /// Panics if cols is 0.
+    pub fn montage(
+        grids: &[Grid],
+        cols: usize,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        gap: usize,
+    ) -> RgbImage {
+        assert!(cols > 0, "a montage needs at least one column");
+        let images: Vec<RgbImage> = grids
+            .iter()
+            .map(|grid| grid.to_image(cell_size, wall_size, background_pixel, wall_pixel))
+            .collect();
+
+        let rows = images.len().div_ceil(cols);
+        // Fewer mazes than cols leave the columns after them out altogether
+        let (mut col_widths, mut row_heights) = (vec![0; cols.min(images.len())], vec![0; rows]);
+        for (n, image) in images.iter().enumerate() {
+            let (width, height) = image.dimensions();
+            col_widths[n % cols] = col_widths[n % cols].max(width);
+            row_heights[n / cols] = row_heights[n / cols].max(height);
+        }
+        // Where each column and row starts, with the gaps between them
+        let starts = |sizes: &[u32]| -> Vec<u32> {
+            sizes
+                .iter()
+                .scan(0, |start, &size| {
+                    let this = *start;
+                    *start += size + gap as u32;
+                    Some(this)
+                })
+                .collect()
+        };
+        let (xs, ys) = (starts(&col_widths), starts(&row_heights));
+        let total = |sizes: &[u32]| {
+            let gaps = sizes.len().saturating_sub(1) as u32 * gap as u32;
+            sizes.iter().sum::<u32>() + gaps
+        };
+
+        let mut montage =
+            ImageBuffer::from_pixel(total(&col_widths), total(&row_heights), background_pixel);
+        for (n, image) in images.iter().enumerate() {
+            image::imageops::replace(&mut montage, image, xs[n % cols], ys[n / cols]);
+        }
+        montage
+    }
+
+    /// to_image_styled renders the maze like to_image, with its walls drawn in style.
+    /// WallStyle::Double draws each wall as an outline, so walls wall_size thick have
+    /// wall_size - 2 pixels of background down the middle, and where walls meet,
+    /// their outlines join up.
+    pub fn to_image_styled(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        style: WallStyle,
+    ) -> RgbImage {
+        let mut image = self.to_image(cell_size, wall_size, background_pixel, wall_pixel);
+        if style == WallStyle::Double {
+            draw::hollow_out(&mut image, wall_pixel, background_pixel);
+        }
+        image
+    }
+
+    /// to_image_on renders the maze like to_image, but draws the walls over background
+    /// instead of a solid background color, e.g. for walls on a parchment texture.
+    /// The background is tiled from the top left corner to cover the whole image,
+    /// so a small texture repeats and a large image is cropped.
+    ///
+    /// Panics if background has no pixels.
+    pub fn to_image_on(
+        &self,
+        background: &RgbImage,
+        cell_size: usize,
+        wall_size: usize,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let (tile_width, tile_height) = background.dimensions();
+        assert!(
+            tile_width > 0 && tile_height > 0,
+            "a {}x{} background image has no pixels",
+            tile_width,
+            tile_height
+        );
+        let image_width = cell_size * self.width + wall_size;
+        let image_height = cell_size * self.height + wall_size;
+
+        let mut image = ImageBuffer::from_fn(image_width as u32, image_height as u32, |x, y| {
+            *background.get_pixel(x % tile_width, y % tile_height)
+        });
+        self.draw_annotations(&mut image, cell_size, cell_size, wall_size);
+        self.draw_walls(&mut image, cell_size, cell_size, wall_size, wall_pixel);
+        image
+    }
+
+    /// to_image_sized renders the maze like to_image, but with cells that are cell_width
+    /// pixels across and cell_height pixels down, e.g. for printing on paper that isn't square.
+    /// The image is cell_width * width + wall_size pixels wide
+    /// and cell_height * height + wall_size pixels high.
+    pub fn to_image_sized(
+        &self,
+        cell_width: usize,
+        cell_height: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let image_width = cell_width * self.width + wall_size;
+        let image_height = cell_height * self.height + wall_size;
+
+        let mut image =
+            ImageBuffer::from_pixel(image_width as u32, image_height as u32, background_pixel);
+        self.draw_annotations(&mut image, cell_width, cell_height, wall_size);
+        self.draw_walls(&mut image, cell_width, cell_height, wall_size, wall_pixel);
+        image
+    }
+
+    /// to_image_inset renders the maze like to_image, but with the walls of each cell
+    /// pulled inset pixels in from its edges, and short stubs of passage joining linked cells.
+    /// The passages look like corridors with space between them, rather than one open field.
+    /// With an inset of 0, this looks the same as to_image.
+    ///
+    /// Panics if inset isn't less than half of cell_size.
+    pub fn to_image_inset(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        inset: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        self.image_inset(
+            cell_size,
+            wall_size,
+            inset,
+            background_pixel,
+            wall_pixel,
+            false,
+        )
+    }
+
+    /// to_image_rounded renders the maze like to_image_inset, but where a passage turns
+    /// a corner, the wall on the outside of the turn is a quarter circle instead of
+    /// a right angle, for more of a winding cave than a grid of corridors.
+    /// With an inset of 0, the corridors fill their cells, so the curves are as wide as cells.
+    ///
+    /// Panics if inset isn't less than half of cell_size.
+    pub fn to_image_rounded(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        inset: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        self.image_inset(
+            cell_size,
+            wall_size,
+            inset,
+            background_pixel,
+            wall_pixel,
+            true,
+        )
+    }
+
+    // to_image_inset, with the outside of every turn rounded off if rounded is set.
+    fn image_inset(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        inset: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        rounded: bool,
+    ) -> RgbImage {
+        assert!(
+            2 * inset < cell_size,
+            "an inset of {} doesn't fit in cells {} pixels across",
+            inset,
+            cell_size
+        );
+        let image_width = cell_size * self.width + wall_size;
+        let image_height = cell_size * self.height + wall_size;
+        let mut image =
+            ImageBuffer::from_pixel(image_width as u32, image_height as u32, background_pixel);
+        self.draw_annotations(&mut image, cell_size, cell_size, wall_size);
+
+        let horizontal = |image: &mut RgbImage, x1: usize, x2: usize, y: usize| {
+            fill_rect(image, x1, y, x2 - x1 + wall_size, wall_size, wall_pixel)
+        };
+        let vertical = |image: &mut RgbImage, x: usize, y1: usize, y2: usize| {
+            fill_rect(image, x, y1, wall_size, y2 - y1 + wall_size, wall_pixel)
+        };
+
+        for (i, &cell) in self.cells.iter().enumerate() {
+            if self.is_masked(i) {
+                continue;
+            }
+            // The outer edges of the cell, and its walls inside them
+            let (x1, y1) = ((i % self.width) * cell_size, (i / self.width) * cell_size);
+            let (x4, y4) = (x1 + cell_size, y1 + cell_size);
+            let (x2, y2, x3, y3) = (x1 + inset, y1 + inset, x4 - inset, y4 - inset);
+
+            // A passage tunneling beneath the cell shows as stubs on either side of it
+            let mut stubs = cell;
+            if cell.contains(Cell::UNDER) {
+                stubs |= if cell.contains(Cell::NORTH) {
+                    Cell::EAST | Cell::WEST
                 } else {
-                    break;
+                    Cell::NORTH | Cell::SOUTH
+                };
+            }
+
+            if stubs.contains(Cell::NORTH) {
+                vertical(&mut image, x2, y1, y2);
+                vertical(&mut image, x3, y1, y2);
+            }
+            if stubs.contains(Cell::SOUTH) {
+                vertical(&mut image, x2, y3, y4);
+                vertical(&mut image, x3, y3, y4);
+            }
+            if stubs.contains(Cell::EAST) {
+                horizontal(&mut image, x3, x4, y2);
+                horizontal(&mut image, x3, x4, y3);
+            }
+            if stubs.contains(Cell::WEST) {
+                horizontal(&mut image, x1, x2, y2);
+                horizontal(&mut image, x1, x2, y3);
+            }
+
+            if rounded && !cell.contains(Cell::UNDER) {
+                // The passages of a turn meet at its inside corner, and the curve
+                // goes around it through the middle of the outside corner
+                let vertical_passage = cell & (Cell::NORTH | Cell::SOUTH);
+                let horizontal_passage = cell & (Cell::EAST | Cell::WEST);
+                let turn = cell == vertical_passage | horizontal_passage
+                    && vertical_passage.bits().count_ones() == 1
+                    && horizontal_passage.bits().count_ones() == 1;
+                if turn {
+                    let (inside_x, outside_x) = if cell.contains(Cell::EAST) {
+                        (x3, x2)
+                    } else {
+                        (x2, x3)
+                    };
+                    let (inside_y, outside_y) = if cell.contains(Cell::NORTH) {
+                        (y2, y3)
+                    } else {
+                        (y3, y2)
+                    };
+                    let middle = wall_size as f64 / 2.0;
+                    let point = |x: usize, y: usize| (x as f64 + middle, y as f64 + middle);
+                    draw::draw_arc(
+                        &mut image,
+                        point(inside_x, inside_y),
+                        point(outside_x, outside_y),
+                        (x3 - x2) as f64,
+                        wall_size as f64,
+                        wall_pixel,
+                    );
+                    continue;
                 }
             }
-            // Boxed in! Time to pop cells off the stack and find one that has
-            // an unvisited adjacent neighbor
-            'outer: while let Some(next_cell) = cell_stack.pop() {
-                current_cell = next_cell;
-                // break if adjacent unvisited neighbor
-                for direction in DIRECTIONS.iter() {
-                    if self.valid_direction(current_cell, *direction) {
-                        let neighbor = self.neighbor(current_cell, *direction);
-                        // Found the adjacent unvisited cell!
-                        if !visited_cells.contains(&neighbor) {
-                            self.link_cells(current_cell, *direction);
-                            current_cell = neighbor;
-                            visited_cells.insert(current_cell);
-                            cell_stack.push(current_cell);
-                            break 'outer;
-                        }
+
+            if !cell.contains(Cell::NORTH) {
+                horizontal(&mut image, x2, x3, y2);
+            }
+            if !cell.contains(Cell::SOUTH) {
+                horizontal(&mut image, x2, x3, y3);
+            }
+            if !cell.contains(Cell::EAST) {
+                vertical(&mut image, x3, y2, y3);
+            }
+            if !cell.contains(Cell::WEST) {
+                vertical(&mut image, x2, y2, y3);
+            }
+        }
+        image
+    }
+
+    /// to_image_colored renders the maze with each cell filled in by its distance from root,
+    /// going from blue for the closest cells to red for the farthest.
+    /// Cells that can't be reached from root are left white.
+    pub fn to_image_colored(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        wall_pixel: image::Rgb<u8>,
+        root: usize,
+    ) -> RgbImage {
+        let image_width = cell_size * self.width + wall_size;
+        let image_height = cell_size * self.height + wall_size;
+
+        let mut image = ImageBuffer::from_pixel(
+            image_width as u32,
+            image_height as u32,
+            image::Rgb([255, 255, 255]),
+        );
+
+        let distances = self.distances(root);
+        let max_distance = distances.iter().flatten().copied().max().unwrap_or(0);
+
+        for (cell_index, distance) in distances.iter().enumerate() {
+            if let Some(distance) = distance {
+                let pixel = image::Rgb(draw::heat(*distance, max_distance));
+
+                // Also covers the east and south walls, so open passages get filled in too
+                let x = (cell_index % self.width) * cell_size + wall_size;
+                let y = (cell_index / self.width) * cell_size + wall_size;
+                fill_rect(&mut image, x, y, cell_size, cell_size, pixel);
+            }
+        }
+
+        self.draw_walls(&mut image, cell_size, cell_size, wall_size, wall_pixel);
+        image
+    }
+
+    fn draw_walls(
+        &self,
+        image: &mut RgbImage,
+        cell_width: usize,
+        cell_height: usize,
+        wall_size: usize,
+        wall_pixel: image::Rgb<u8>,
+    ) {
+        for (cell_index, cell) in self.cells.iter().enumerate() {
+            if self.is_masked(cell_index) {
+                continue;
+            }
+
+            let x = (cell_index % self.width) * cell_width;
+            let y = (cell_index / self.width) * cell_height;
+
+            if cell.contains(Cell::UNDER) {
+                self.draw_bridge(
+                    image,
+                    *cell,
+                    (x, y),
+                    cell_width,
+                    cell_height,
+                    wall_size,
+                    wall_pixel,
+                );
+                continue;
+            }
+
+            if !cell.contains(Cell::NORTH) {
+                for wall_offset in 0..wall_size {
+                    for cell_offset in 0..=cell_width {
+                        let x_temp = x + cell_offset;
+                        let y_temp = y + wall_offset;
+                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel)
+                    }
+                }
+            }
+
+            if !cell.contains(Cell::SOUTH) {
+                for wall_offset in 0..wall_size {
+                    for cell_offset in 0..(cell_width + wall_size) {
+                        let x_temp = x + cell_offset;
+                        let y_temp = y + cell_height + wall_offset;
+                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel)
+                    }
+                }
+            }
+
+            if !cell.contains(Cell::WEST) {
+                for wall_offset in 0..wall_size {
+                    for cell_offset in 0..=cell_height {
+                        let y_temp = y + cell_offset;
+                        let x_temp = x + wall_offset;
+                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel);
                     }
                 }
             }
+
+            if !cell.contains(Cell::EAST) {
+                for wall_offset in 0..wall_size {
+                    for cell_offset in 0..=cell_height {
+                        let x_temp = x + cell_width + wall_offset;
+                        let y_temp = y + cell_offset;
+                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel);
+                    }
+                }
+            }
+        }
+    }
+
+    /// to_image_with_path renders the maze like to_image, then draws a line in path_pixel
+    /// through the centers of the cells in path, in order.
+    /// Consecutive cells in path are expected to be neighbors, such as a path from solve.
+    pub fn to_image_with_path(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        path: &[usize],
+        path_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let mut image = self.to_image(cell_size, wall_size, background_pixel, wall_pixel);
+
+        let passage_size = cell_size.saturating_sub(wall_size);
+        let thickness = (passage_size / 3).max(1);
+        // Top left corner of the line's square within each cell
+        let corner = |i: usize| {
+            let offset = wall_size + passage_size.saturating_sub(thickness) / 2;
+            (
+                (i % self.width) * cell_size + offset,
+                (i / self.width) * cell_size + offset,
+            )
+        };
+
+        for &i in path.iter() {
+            let (x, y) = corner(i);
+            fill_rect(&mut image, x, y, thickness, thickness, path_pixel);
+        }
+
+        for step in path.windows(2) {
+            let (x1, y1) = corner(step[0]);
+            let (x2, y2) = corner(step[1]);
+            let (left, right) = (x1.min(x2), x1.max(x2));
+            if right - left > cell_size {
+                // A step across a seam of a wrapping grid leaves through one side
+                // of the image and comes back in through the other
+                let image_width = image.width() as usize;
+                fill_rect(&mut image, 0, y1, left + thickness, thickness, path_pixel);
+                fill_rect(
+                    &mut image,
+                    right,
+                    y1,
+                    image_width - right,
+                    thickness,
+                    path_pixel,
+                );
+                continue;
+            }
+            let (top, bottom) = (y1.min(y2), y1.max(y2));
+            if bottom - top > cell_size {
+                let image_height = image.height() as usize;
+                fill_rect(&mut image, x1, 0, thickness, top + thickness, path_pixel);
+                fill_rect(
+                    &mut image,
+                    x1,
+                    bottom,
+                    thickness,
+                    image_height - bottom,
+                    path_pixel,
+                );
+                continue;
+            }
+            let (x, y) = (left, top);
+            let width = right - x + thickness;
+            let height = bottom - y + thickness;
+            fill_rect(&mut image, x, y, width, height, path_pixel);
+        }
+
+        image
+    }
+
+    /// to_image_with_distances renders the maze like to_image, then writes in each cell
+    /// how many steps it is from root, in text_pixel, using a small built-in font.
+    /// Cells that can't be reached from root are left blank.
+    ///
+    /// Every label is drawn at the same size, as large as will fit the longest one inside
+    /// a cell. If the cells are too small for even the smallest text, there are no labels.
+    pub fn to_image_with_distances(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+        root: usize,
+        text_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let mut image = self.to_image(cell_size, wall_size, background_pixel, wall_pixel);
+
+        let distances = self.distances(root);
+        let farthest = match distances.iter().flatten().max() {
+            Some(&farthest) => farthest,
+            None => return image,
+        };
+        // Leave at least a pixel of space between the text and the walls
+        let room = cell_size.saturating_sub(wall_size + 2);
+        let (text_width, text_height) = draw::number_size(farthest);
+        let scale = (room / text_width).min(room / text_height);
+        if scale == 0 {
+            return image;
+        }
+
+        let passage_size = cell_size.saturating_sub(wall_size);
+        for (i, distance) in distances.into_iter().enumerate() {
+            if let Some(distance) = distance {
+                let (width, height) = draw::number_size(distance);
+                let x =
+                    (i % self.width) * cell_size + wall_size + (passage_size - width * scale) / 2;
+                let y =
+                    (i / self.width) * cell_size + wall_size + (passage_size - height * scale) / 2;
+                draw::draw_number(&mut image, distance, (x, y), scale, text_pixel);
+            }
+        }
+        image
+    }
+
+    /// to_string_with_border renders the maze as ASCII art like Display, but leaves
+    /// the wall around the outside of the maze open unless draw_border is set,
+    /// like to_image_with_border. The corners along the edge are still drawn.
+    pub fn to_string_with_border(&self, draw_border: bool) -> String {
+        if draw_border {
+            self.to_string()
+        } else {
+            self.without_border().to_string()
+        }
+    }
+
+    // A copy of the maze with every cell on its outer wall opened up on that side,
+    // so that renderers draw it without a border.
+    fn without_border(&self) -> Grid {
+        let mut open = Grid {
+            cells: self.cells.clone(),
+            width: self.width,
+            height: self.height,
+            mask: self.mask.clone(),
+            weights: None,
+            wrap_horizontal: self.wrap_horizontal,
+            wrap_vertical: self.wrap_vertical,
+            annotations: self.annotations.clone(),
+        };
+        for (i, cell) in open.cells.iter_mut().enumerate() {
+            // A passage tunneling under a cell can't lead out of the maze
+            if self.is_masked(i) || cell.contains(Cell::UNDER) {
+                continue;
+            }
+            for &direction in Direction::all().iter() {
+                if !self.in_bounds(i, direction) {
+                    *cell |= direction.to_flag();
+                }
+            }
+        }
+        open
+    }
+
+    /// to_string_with_path renders the maze as ASCII art like Display,
+    /// marking the cells in path with a "*".
+    /// Indices in path that aren't cells of the maze are ignored.
+    pub fn to_string_with_path(&self, path: &[usize]) -> String {
+        let mut on_path = vec![false; self.cells.len()];
+        for &i in path.iter() {
+            if let Some(marked) = on_path.get_mut(i) {
+                *marked = true;
+            }
+        }
+        self.to_ascii(&AsciiCharset::default(), |i| {
+            if on_path[i] {
+                " * "
+            } else {
+                "   "
+            }
+        })
+    }
+
+    // Renders the maze as ASCII art, using body to fill in the inside of each cell.
+    // Masked off cells are left blank, with no walls between them.
+    // A grid with no cells renders as nothing at all.
+    fn to_ascii<'a>(&self, charset: &AsciiCharset, body: impl Fn(usize) -> &'a str) -> String {
+        if self.cells.is_empty() {
+            return String::new();
+        }
+        let wall = charset
+            .horizontal_wall
+            .to_string()
+            .repeat(charset.cell_width);
+        let blank = " ".repeat(charset.cell_width);
+        let present = |row: usize, col: usize| self.in_maze(row, col);
+        // The corner at the north west of (row, col)
+        let corner = |row: usize, col: usize| {
+            let (above, left) = (row.wrapping_sub(1), col.wrapping_sub(1));
+            if present(above, left)
+                || present(above, col)
+                || present(row, left)
+                || present(row, col)
+            {
+                charset.corner
+            } else {
+                ' '
+            }
+        };
+
+        let mut output = String::new();
+        for col in 0..self.width {
+            output.push(corner(0, col));
+            let open = self.cells[col].contains(Cell::NORTH);
+            output.push_str(if present(0, col) && !open {
+                &wall
+            } else {
+                &blank
+            });
+        }
+        output.push(corner(0, self.width));
+        output.push('\n');
+
+        for row in 0..self.height {
+            let open = self.cells[row * self.width].contains(Cell::WEST);
+            let mut top = if present(row, 0) && !open {
+                charset.vertical_wall
+            } else {
+                ' '
+            }
+            .to_string();
+            let mut bottom = corner(row + 1, 0).to_string();
+
+            for col in 0..self.width {
+                let i = row * self.width + col;
+                let cell = self.cells[i];
+
+                top.push_str(if self.is_masked(i) { &blank } else { body(i) });
+                let east_boundary =
+                    if cell.contains(Cell::EAST) || !(present(row, col) || present(row, col + 1)) {
+                        ' '
+                    } else {
+                        charset.vertical_wall
+                    };
+                top.push(east_boundary);
+
+                let south_boundary = if cell.contains(Cell::SOUTH)
+                    || !(present(row, col) || present(row + 1, col))
+                {
+                    &blank
+                } else {
+                    &wall
+                };
+
+                bottom.push_str(south_boundary);
+                bottom.push(corner(row + 1, col + 1));
+            }
+
+            output.push_str(&top);
+            output.push('\n');
+            output.push_str(&bottom);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+#[cfg(feature = "std")]
+fn fill_rect(
+    image: &mut RgbImage,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    pixel: image::Rgb<u8>,
+) {
+    for x_temp in x..x + width {
+        for y_temp in y..y + height {
+            image.put_pixel(x_temp as u32, y_temp as u32, pixel);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Maze for Grid {
+    type Dir = Cell;
+
+    fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(Cell, usize)> {
+        Grid::neighbors(self, i).collect()
+    }
+
+    /// Panics if a and b aren't next to each other.
+    fn link(&mut self, a: usize, b: usize) {
+        if let Err(e) = Grid::link(self, a, b) {
+            panic!("{}", e);
+        }
+    }
+
+    fn is_masked(&self, i: usize) -> bool {
+        Grid::is_masked(self, i)
+    }
+
+    fn random_neighbor<R: Rng>(&self, i: usize, rng: &mut R) -> Option<usize> {
+        // A cell with nowhere to go would never find a valid direction
+        Grid::neighbors(self, i).next()?;
+
+        // Loop until we've found a valid direction - only an issue at the maze borders
+        loop {
+            let direction = *Direction::all().choose(rng).unwrap();
+            if self.valid_direction(i, direction) {
+                return Some(self.neighbor(i, direction));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_ascii(&AsciiCharset::default(), |_| "   "))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+
+    use super::*;
+    use std::collections::HashSet;
+
+    // A 10x10 mask with a hole in the middle and the corners cut off
+    fn ring_mask() -> Mask {
+        let mut mask = Mask::new(10, 10);
+        for i in 0..100 {
+            let (row, col) = (i / 10, i % 10);
+            if (3..7).contains(&row) && (3..7).contains(&col) {
+                mask.set(i, false);
+            }
+        }
+        for &i in [0, 9, 90, 99].iter() {
+            mask.set(i, false);
+        }
+        mask
+    }
+
+    #[test]
+    fn test_binary_tree() {
+        let width = 50_usize;
+        let height = 50_usize;
+        for _i in 0..10000 {
+            let mut grid = Grid::new(height, width);
+            grid.binary_tree(None);
+
+            assert!(grid.is_perfect());
+        }
+    }
+
+    #[test]
+    fn test_binary_tree_with_bias() {
+        let (width, height) = (8, 6);
+        let biases = [
+            (Cell::NORTH, Cell::EAST),
+            (Cell::NORTH, Cell::WEST),
+            (Cell::SOUTH, Cell::EAST),
+            (Cell::SOUTH, Cell::WEST),
+        ];
+        for &(vertical, horizontal) in &biases {
+            let mut grid = Grid::new(width, height);
+            grid.binary_tree_with_bias(Some(5), vertical, horizontal);
+            assert!(grid.is_perfect());
+
+            // Outside the corner, cells on the edge vertical points at can only go horizontally,
+            // and cells on the edge horizontal points at can only go vertically
+            let row = if vertical == Cell::NORTH {
+                0
+            } else {
+                height - 1
+            };
+            let col = if horizontal == Cell::EAST {
+                width - 1
+            } else {
+                0
+            };
+            for c in (0..width).filter(|&c| c != col) {
+                assert!(grid.cells[grid.index(row, c)].contains(horizontal));
+            }
+            for r in (0..height).filter(|&r| r != row) {
+                assert!(grid.cells[grid.index(r, col)].contains(vertical));
+            }
+        }
+
+        let mut grid = Grid::new(width, height);
+        let mut expected = Grid::new(width, height);
+        grid.binary_tree_with_bias(Some(5), Cell::NORTH, Cell::EAST);
+        expected.binary_tree(Some(5));
+        assert_eq!(expected, grid);
+    }
+
+    #[test]
+    fn test_sidewinder() {
+        let width = 50_usize;
+        let height = 50_usize;
+        for _i in 0..10000 {
+            let mut grid = Grid::new(height, width);
+            grid.sidewinder(None);
+
+            assert!(grid.is_perfect());
+        }
+    }
+
+    #[test]
+    fn test_sidewinder_with_bias() {
+        // Passages EAST, and passages NORTH
+        let passages = |grid: &Grid| {
+            let count = |direction| {
+                let cells = grid.cells.iter();
+                cells.filter(|cell| cell.contains(direction)).count()
+            };
+            (count(Cell::EAST), count(Cell::NORTH))
+        };
+
+        let mut grid = Grid::new(30, 30);
+        grid.sidewinder_with_bias(Some(3), 0.1);
+        assert!(grid.is_perfect());
+        let (east, north) = passages(&grid);
+        assert!(east * 3 < north, "{} east, {} north", east, north);
+
+        grid.sidewinder_with_bias(Some(3), 0.9);
+        assert!(grid.is_perfect());
+        let (east, north) = passages(&grid);
+        assert!(north * 3 < east, "{} east, {} north", east, north);
+
+        // At the extremes, every run is a single cell, or a whole row
+        grid.sidewinder_with_bias(None, 0.0);
+        assert_eq!((29, 30 * 29), passages(&grid));
+        grid.sidewinder_with_bias(None, 1.0);
+        assert_eq!((30 * 29, 29), passages(&grid));
+    }
+
+    #[test]
+    #[should_panic(expected = "a horizontal_prob of 1.5 isn't between 0 and 1")]
+    fn test_sidewinder_with_bias_out_of_range() {
+        Grid::new(3, 3).sidewinder_with_bias(None, 1.5);
+    }
+
+    #[test]
+    fn test_recursive_backtracker_straight() {
+        let corners = |grid: &Grid| {
+            let kinds = grid.classify_cells().into_iter();
+            kinds.filter(|&kind| kind == CellKind::Corner).count()
+        };
+
+        let mut grid = Grid::new(30, 30);
+        let mut counts = Vec::new();
+        for &straightness in [0.0, 0.5, 0.9].iter() {
+            grid.recursive_backtracker_straight(Some(5), straightness);
+            assert!(grid.is_perfect());
+            counts.push(corners(&grid));
+        }
+        assert!(
+            counts[0] > counts[1] && counts[1] > counts[2],
+            "corners: {:?}",
+            counts
+        );
+
+        // Always going straight, every turn is forced by a wall or a visited cell
+        grid.recursive_backtracker_straight(Some(5), 1.0);
+        assert!(grid.is_perfect());
+        assert!(corners(&grid) * 2 < counts[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "a straightness of -0.5 isn't between 0 and 1")]
+    fn test_recursive_backtracker_straight_out_of_range() {
+        Grid::new(3, 3).recursive_backtracker_straight(None, -0.5);
+    }
+
+    #[test]
+    fn test_aldous_broder() {
+        let width = 50_usize;
+        let height = 50_usize;
+        let mut grid = Grid::new(height, width);
+
+        for _i in 0..1000 {
+            grid.aldous_broder(None);
+
+            assert!(grid.is_perfect());
+        }
+    }
+
+    // Every perfect maze of the given size, drawn as ASCII art.
+    fn all_mazes(width: usize, height: usize) -> HashSet<String> {
+        Grid::enumerate_all(width, height)
+            .map(|grid| grid.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_aldous_broder_all_mazes() {
+        let width = 3_usize;
+        let height = 3_usize;
+        let mut grid = Grid::new(height, width);
+
+        let mut mazes = HashSet::new();
+        for _i in 0..100000 {
+            grid.aldous_broder(None);
+            mazes.insert(format!("{}", grid));
+        }
+        assert_eq!(192_usize, mazes.len());
+        assert_eq!(all_mazes(3, 3), mazes);
+    }
+
+    #[test]
+    fn test_wilsons() {
+        let width = 50_usize;
+        let height = 50_usize;
+        let mut grid = Grid::new(height, width);
+
+        for _i in 0..1000 {
+            grid.wilsons(None);
+            assert!(grid.is_perfect());
+        }
+    }
+
+    #[test]
+    fn test_wilsons_all_mazes() {
+        let width = 3_usize;
+        let height = 3_usize;
+        let mut grid = Grid::new(height, width);
+
+        let mut mazes = HashSet::new();
+        for _i in 0..100000 {
+            grid.wilsons(None);
+            mazes.insert(format!("{}", grid));
+        }
+        assert_eq!(192_usize, mazes.len());
+        assert_eq!(all_mazes(3, 3), mazes);
+    }
+
+    #[test]
+    fn test_ellers() {
+        let width = 50_usize;
+        let height = 50_usize;
+        let mut grid = Grid::new(height, width);
+
+        for _i in 0..1000 {
+            grid.ellers(None);
+
+            assert!(grid.is_perfect());
+        }
+    }
+
+    #[test]
+    fn test_kruskals() {
+        for &(width, height) in [(1, 1), (1, 10), (10, 1), (2, 3), (50, 50), (100, 37)].iter() {
+            let mut grid = Grid::new(width, height);
+
+            for _i in 0..100 {
+                grid.kruskals(None);
+
+                assert!(grid.is_perfect());
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursive_division() {
+        for &(width, height) in [(1, 1), (1, 10), (10, 1), (2, 3), (50, 50), (100, 37)].iter() {
+            let mut grid = Grid::new(width, height);
+
+            for _i in 0..100 {
+                grid.recursive_division(None);
+
+                assert!(grid.is_perfect());
+            }
         }
     }
 
-    pub fn to_image(
-        &self,
-        cell_size: usize,
-        wall_size: usize,
-        background_pixel: image::Rgb<u8>,
-        wall_pixel: image::Rgb<u8>,
-    ) -> RgbImage {
-        let image_width = cell_size * self.width + wall_size;
-        let image_height = cell_size * self.height + wall_size;
+    #[test]
+    fn test_to_image_with_path() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+        let path = grid.solve(0, 24).unwrap();
+
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let red = image::Rgb([255, 0, 0]);
+        let image = grid.to_image_with_path(10, 1, background, wall, &path, red);
+
+        for i in 0..grid.cells.len() {
+            let x = (i % grid.width) * 10 + 5;
+            let y = (i / grid.width) * 10 + 5;
+            let expected = if path.contains(&i) { red } else { background };
+            assert_eq!(&expected, image.get_pixel(x as u32, y as u32));
+        }
+    }
 
-        let mut image =
-            ImageBuffer::from_pixel(image_width as u32, image_height as u32, background_pixel);
+    #[test]
+    fn test_montage() {
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let grids: Vec<Grid> = [(3, 2), (5, 5), (4, 1), (2, 3), (1, 1)]
+            .iter()
+            .enumerate()
+            .map(|(seed, &(width, height))| {
+                let mut grid = Grid::new(width, height);
+                grid.prims(Some(seed as u64));
+                grid
+            })
+            .collect();
+
+        // Columns 31 and 51 pixels wide, and rows 51 and 31 high, with 4 pixel gaps
+        let montage = Grid::montage(&grids, 3, 10, 1, background, wall, 4);
+        assert_eq!((31 + 4 + 51 + 4 + 41, 51 + 4 + 31), montage.dimensions());
+        // Each maze is in the top left of its place, and the gaps are background
+        for (n, &(x, y)) in [(0, 0), (35, 0), (90, 0), (0, 55), (35, 55)]
+            .iter()
+            .enumerate()
+        {
+            let image = grids[n].to_image(10, 1, background, wall);
+            for (dx, dy, pixel) in image.enumerate_pixels() {
+                assert_eq!(pixel, montage.get_pixel(x + dx, y + dy));
+            }
+        }
+        assert_eq!(&background, montage.get_pixel(33, 0));
+        assert_eq!(&background, montage.get_pixel(0, 53));
+
+        // One row, or one column
+        let montage = Grid::montage(&grids, 5, 10, 1, background, wall, 0);
+        assert_eq!((31 + 51 + 41 + 21 + 11, 51), montage.dimensions());
+        let montage = Grid::montage(&grids, 1, 10, 1, background, wall, 2);
+        assert_eq!((51, 21 + 51 + 11 + 31 + 11 + 4 * 2), montage.dimensions());
+        let montage = Grid::montage(&grids[..2], 3, 10, 1, background, wall, 2);
+        assert_eq!((31 + 2 + 51, 51), montage.dimensions());
+        let montage = Grid::montage(&[], 2, 10, 1, background, wall, 2);
+        assert_eq!((0, 0), montage.dimensions());
+    }
 
-        for (cell_index, cell) in self.cells.iter().enumerate() {
-            let x = (cell_index % self.width) * cell_size;
-            let y = (cell_index / self.width) * cell_size;
+    #[test]
+    fn test_are_linked() {
+        let mut grid = Grid::new(3, 3);
+        for &neighbor in [1, 3, 5, 7].iter() {
+            assert!(!grid.are_linked(4, neighbor));
+            grid.link(4, neighbor).unwrap();
+        }
+        // NORTH, WEST, EAST and SOUTH, both ways round
+        for &neighbor in [1, 3, 5, 7].iter() {
+            assert!(grid.are_linked(4, neighbor));
+            assert!(grid.are_linked(neighbor, 4));
+        }
 
-            if !cell.contains(Cell::NORTH) {
-                for wall_offset in 0..wall_size {
-                    for cell_offset in 0..=cell_size {
-                        let x_temp = x + cell_offset;
-                        let y_temp = y + wall_offset;
-                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel)
-                    }
-                }
+        // Corners, cells further apart, the same cell and cells off the grid
+        assert!(!grid.are_linked(4, 0));
+        assert!(!grid.are_linked(1, 7));
+        assert!(!grid.are_linked(4, 4));
+        assert!(!grid.are_linked(4, 9));
+        assert!(!grid.are_linked(9, 4));
+
+        // The end of one row isn't next to the start of the next, unless it wraps
+        grid.cells[2] |= Cell::EAST;
+        assert!(!grid.are_linked(2, 3));
+        let mut grid = Grid::new(3, 3);
+        grid.set_wrap_horizontal(true);
+        grid.link_cells(2, Direction::East);
+        assert!(grid.are_linked(2, 0));
+        assert!(!grid.are_linked(2, 3));
+    }
+
+    #[test]
+    fn test_to_image_styled() {
+        let mut grid = Grid::new(4, 3);
+        grid.recursive_backtracker(Some(3));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+
+        let solid = grid.to_image_styled(12, 4, background, wall, WallStyle::Solid);
+        assert_eq!(
+            grid.to_image(12, 4, background, wall).into_raw(),
+            solid.clone().into_raw()
+        );
+
+        // Across the top wall, one line, two pixels of background, then the other line
+        let double = grid.to_image_styled(12, 4, background, wall, WallStyle::Double);
+        assert_eq!(solid.dimensions(), double.dimensions());
+        let column: Vec<_> = (0..5).map(|y| *double.get_pixel(8, y)).collect();
+        assert_eq!(vec![wall, background, background, wall, background], column);
+        // Wall pixels are only ever cleared
+        for (x, y, pixel) in double.enumerate_pixels() {
+            if *pixel == wall {
+                assert_eq!(&wall, solid.get_pixel(x, y));
             }
+        }
 
-            if !cell.contains(Cell::SOUTH) {
-                for wall_offset in 0..wall_size {
-                    for cell_offset in 0..(cell_size + wall_size) {
-                        let x_temp = x + cell_offset;
-                        let y_temp = y + cell_size + wall_offset;
-                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel)
-                    }
-                }
+        // Walls too thin to have a middle stay solid
+        let thin = grid.to_image_styled(12, 2, background, wall, WallStyle::Double);
+        assert_eq!(
+            grid.to_image(12, 2, background, wall).into_raw(),
+            thin.into_raw()
+        );
+    }
+
+    #[test]
+    fn test_to_image_on() {
+        let mut grid = Grid::new(5, 4);
+        grid.prims(Some(6));
+        let wall = image::Rgb([0, 0, 0]);
+        let white = image::Rgb([255, 255, 255]);
+        let texture =
+            ImageBuffer::from_fn(7, 3, |x, y| image::Rgb([100 + x as u8, 200 + y as u8, 50]));
+
+        // The walls are where they'd be on a solid background, with the texture
+        // tiled everywhere else
+        let plain = grid.to_image(10, 2, white, wall);
+        let image = grid.to_image_on(&texture, 10, 2, wall);
+        assert_eq!(plain.dimensions(), image.dimensions());
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if *plain.get_pixel(x, y) == wall {
+                assert_eq!(&wall, pixel);
+            } else {
+                assert_eq!(texture.get_pixel(x % 7, y % 3), pixel);
             }
+        }
 
-            if !cell.contains(Cell::WEST) {
-                for wall_offset in 0..wall_size {
-                    for cell_offset in 0..=cell_size {
-                        let y_temp = y + cell_offset;
-                        let x_temp = x + wall_offset;
-                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel);
-                    }
-                }
+        // A background bigger than the maze is cropped
+        let big = ImageBuffer::from_pixel(200, 200, white);
+        let image = grid.to_image_on(&big, 10, 2, wall);
+        assert_eq!(plain.into_raw(), image.into_raw());
+    }
+
+    #[test]
+    #[should_panic(expected = "a 0x5 background image has no pixels")]
+    fn test_to_image_on_empty() {
+        Grid::new(2, 2).to_image_on(&ImageBuffer::new(0, 5), 10, 2, image::Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_from_edges() {
+        let mut grid = Grid::new(9, 7);
+        grid.hunt_and_kill(Some(4));
+        let copy = Grid::from_edges(9, 7, &grid.edges()).unwrap();
+        assert_eq!(grid, copy);
+
+        // Any order, either way round, and with loops
+        grid.braid(Some(4), 1.0);
+        let mut edges: Vec<(usize, usize)> =
+            grid.edges().into_iter().map(|(a, b)| (b, a)).collect();
+        edges.reverse();
+        assert_eq!(grid, Grid::from_edges(9, 7, &edges).unwrap());
+        assert_eq!(Grid::new(3, 2), Grid::from_edges(3, 2, &[]).unwrap());
+
+        // Cells that aren't neighbors, or aren't in the grid at all
+        for &(a, b) in [(0, 2), (2, 3), (4, 4), (5, 6)].iter() {
+            assert_eq!(
+                Err(GridError::InvalidEdge { a, b }),
+                Grid::from_edges(3, 2, &[(0, 1), (a, b)])
+            );
+        }
+        assert_eq!(
+            Err(GridError::DuplicateEdge { a: 4, b: 1 }),
+            Grid::from_edges(3, 2, &[(1, 4), (0, 1), (4, 1)])
+        );
+    }
+
+    #[test]
+    fn test_direction() {
+        let flags = [Cell::NORTH, Cell::SOUTH, Cell::EAST, Cell::WEST];
+        let opposites = [
+            Direction::South,
+            Direction::North,
+            Direction::West,
+            Direction::East,
+        ];
+        for (i, &direction) in Direction::all().iter().enumerate() {
+            assert_eq!(flags[i], direction.to_flag());
+            assert_eq!(opposites[i], direction.opposite());
+            assert_eq!(direction, direction.opposite().opposite());
+            assert_eq!(Some(direction), Direction::from_flag(flags[i]));
+        }
+
+        // Anything but exactly one of the four
+        assert_eq!(None, Direction::from_flag(Cell::default()));
+        assert_eq!(None, Direction::from_flag(Cell::UNDER));
+        assert_eq!(None, Direction::from_flag(Cell::NORTH | Cell::EAST));
+    }
+
+    #[test]
+    fn test_try_neighbor() {
+        let grid = Grid::new(4, 3);
+        // Inside the grid, every direction leads somewhere
+        assert_eq!(Some(1), grid.try_neighbor(5, Cell::NORTH));
+        assert_eq!(Some(9), grid.try_neighbor(5, Cell::SOUTH));
+        assert_eq!(Some(6), grid.try_neighbor(5, Cell::EAST));
+        assert_eq!(Some(4), grid.try_neighbor(5, Cell::WEST));
+
+        // Off each edge, including across the ends of rows
+        assert_eq!(None, grid.try_neighbor(2, Cell::NORTH));
+        assert_eq!(None, grid.try_neighbor(9, Cell::SOUTH));
+        assert_eq!(None, grid.try_neighbor(7, Cell::EAST));
+        assert_eq!(None, grid.try_neighbor(4, Cell::WEST));
+        assert_eq!(None, grid.try_neighbor(0, Cell::NORTH));
+        assert_eq!(None, grid.try_neighbor(11, Cell::EAST));
+
+        // Outside the grid, or not a single direction
+        assert_eq!(None, grid.try_neighbor(12, Cell::WEST));
+        assert_eq!(None, grid.try_neighbor(5, Cell::NORTH | Cell::EAST));
+        assert_eq!(None, grid.try_neighbor(5, Cell::UNDER));
+
+        // Wrapping around, and masked off cells
+        let mut mask = Mask::new(4, 3);
+        mask.set(6, false);
+        let mut grid = Grid::with_mask(4, 3, mask);
+        grid.set_wrap_horizontal(true);
+        assert_eq!(Some(4), grid.try_neighbor(7, Cell::EAST));
+        assert_eq!(Some(11), grid.try_neighbor(8, Cell::WEST));
+        assert_eq!(None, grid.try_neighbor(2, Cell::NORTH));
+        assert_eq!(None, grid.try_neighbor(5, Cell::EAST));
+        assert_eq!(None, grid.try_neighbor(6, Cell::WEST));
+    }
+
+    #[test]
+    fn test_wrap_horizontal() {
+        let mut grid = Grid::new(5, 3);
+        grid.set_wrap_horizontal(true);
+        assert!(grid.wraps_horizontally());
+
+        // The last cell of a row links east to the first cell of the same row
+        grid.link_cells(9, Direction::East);
+        assert!(grid.cells[5].contains(Cell::WEST));
+        assert_eq!(vec![5], grid.links(9).collect::<Vec<usize>>());
+        assert_eq!(Ok(()), grid.validate());
+        assert_eq!(1, grid.manhattan_distance(5, 9));
+        grid.unlink_cells(5, Direction::West);
+        assert!(grid.cells.iter().all(|cell| cell.is_empty()));
+
+        let generators: [fn(&mut Grid, Option<u64>); 7] = [
+            Grid::binary_tree,
+            Grid::sidewinder,
+            Grid::wilsons,
+            Grid::recursive_backtracker,
+            Grid::ellers,
+            Grid::kruskals,
+            Grid::recursive_division,
+        ];
+        for generate in generators.iter() {
+            for _ in 0..20 {
+                generate(&mut grid, None);
+                assert!(grid.is_perfect());
+                assert_eq!(Ok(()), grid.validate());
+                assert!(grid.distances(0).iter().all(|distance| distance.is_some()));
             }
+        }
 
-            if !cell.contains(Cell::EAST) {
-                for wall_offset in 0..wall_size {
-                    for cell_offset in 0..=cell_size {
-                        let x_temp = x + cell_size + wall_offset;
-                        let y_temp = y + cell_offset;
-                        image.put_pixel(x_temp as u32, y_temp as u32, wall_pixel);
-                    }
-                }
+        // The random walks carve across the seam too
+        let crosses_seam =
+            |grid: &Grid| (0..3).any(|row| grid.cells[row * 5 + 4].contains(Cell::EAST));
+        assert!((0..20).any(|_| {
+            grid.recursive_backtracker(None);
+            crosses_seam(&grid)
+        }));
+
+        // Too narrow to wrap
+        let mut grid = Grid::new(2, 2);
+        grid.set_wrap_horizontal(true);
+        assert!(!grid.wraps_horizontally());
+        grid.recursive_backtracker(None);
+        assert!(grid.is_perfect());
+    }
+
+    #[test]
+    fn test_torus() {
+        let mut grid = GridBuilder::new(6, 5)
+            .wrap_horizontal()
+            .wrap_vertical()
+            .algorithm(Algorithm::Wilsons)
+            .build();
+        assert!(grid.wraps_vertically());
+        assert!(grid
+            .neighbors(2)
+            .any(|neighbor| neighbor == (Cell::NORTH, 26)));
+        assert_eq!(2, grid.manhattan_distance(0, 29));
+
+        // Every cell has four neighbors, and there's no outer wall to open
+        assert!((0..30).all(|i| grid.neighbors(i).count() == 4));
+        grid.carve_entrance_exit();
+        assert!(grid.is_perfect());
+
+        let generators: [fn(&mut Grid, Option<u64>); 7] = [
+            Grid::binary_tree,
+            Grid::sidewinder,
+            Grid::aldous_broder,
+            Grid::hunt_and_kill,
+            Grid::ellers,
+            Grid::kruskals,
+            Grid::recursive_division,
+        ];
+        for generate in generators.iter() {
+            for _ in 0..20 {
+                generate(&mut grid, None);
+                assert!(grid.is_perfect());
+                assert_eq!(Ok(()), grid.validate());
             }
         }
 
-        image
+        // Passages can cross the seams in both directions
+        let crosses_seams = |grid: &Grid| {
+            (0..5).any(|row| grid.cells[row * 6 + 5].contains(Cell::EAST))
+                && (0..6).any(|col| grid.cells[24 + col].contains(Cell::SOUTH))
+        };
+        assert!((0..20).any(|_| {
+            grid.kruskals(None);
+            crosses_seams(&grid)
+        }));
     }
-}
 
-impl std::fmt::Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut output = format!("+{}\n", "---+".to_string().repeat(self.width));
+    #[test]
+    fn test_to_image_with_path_wrapping() {
+        let mut grid = Grid::new(5, 1);
+        grid.set_wrap_horizontal(true);
+        grid.link_cells(4, Direction::East);
+
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let red = image::Rgb([255, 0, 0]);
+        let image = grid.to_image_with_path(10, 1, background, wall, &[4, 0], red);
+
+        // The path runs out of both sides of the image, not back across the middle
+        assert_eq!(&red, image.get_pixel(0, 5));
+        assert_eq!(&red, image.get_pixel(50, 5));
+        assert_eq!(&background, image.get_pixel(25, 5));
+        // The border is open where the passage wraps around
+        assert_eq!(&background, image.get_pixel(0, 2));
+        assert_eq!(&background, image.get_pixel(50, 2));
+    }
 
-        let mut top = "|".to_string();
-        let mut bottom = "+".to_string();
+    #[test]
+    fn test_to_image_with_distances() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let blue = image::Rgb([0, 0, 255]);
+        let count_text = |cell_size: usize, wall_size: usize| {
+            let image =
+                grid.to_image_with_distances(cell_size, wall_size, background, wall, 0, blue);
+            assert_eq!((5 * cell_size + wall_size) as u32, image.width());
+            image.pixels().filter(|&&pixel| pixel == blue).count()
+        };
 
-        for (i, cell) in self.cells.iter().enumerate() {
-            top.push_str("   ");
-            let east_boundary = if cell.contains(Cell::EAST) { " " } else { "|" };
-            top.push_str(east_boundary);
+        // Too small for any text
+        for &(cell_size, wall_size) in [(1, 0), (2, 1), (6, 1), (8, 2)].iter() {
+            assert_eq!(0, count_text(cell_size, wall_size));
+        }
+        for &(cell_size, wall_size) in [(10, 1), (16, 3), (40, 2), (101, 10)].iter() {
+            assert!(count_text(cell_size, wall_size) > 0);
+        }
 
-            let south_boundary = if cell.contains(Cell::SOUTH) {
-                "   "
-            } else {
-                "---"
-            };
+        // The longest distance is 12, which fits at twice the size of the font.
+        // The 0 in the root cell is centered, with a hole in the middle
+        let image = grid.to_image_with_distances(20, 2, background, wall, 0, blue);
+        assert_eq!(&blue, image.get_pixel(8, 6));
+        assert_eq!(&blue, image.get_pixel(13, 15));
+        assert_eq!(&background, image.get_pixel(10, 10));
+        assert_eq!(&background, image.get_pixel(14, 10));
+    }
 
-            bottom.push_str(south_boundary);
-            bottom.push_str("+");
+    #[test]
+    fn test_to_string_with_path() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+        let path = grid.solve(0, 24).unwrap();
+
+        let text = grid.to_string_with_path(&path);
+        assert_eq!(path.len(), text.matches('*').count());
+        assert_eq!(format!("{}", grid), text.replace('*', " "));
+        assert_eq!(format!("{}", grid), grid.to_string_with_path(&[]));
+        assert_eq!(format!("{}", grid), grid.to_string_with_path(&[25, 100]));
+    }
 
-            if (i + 1) % self.width == 0 {
-                output.push_str(&top);
-                output.push_str("\n");
-                output.push_str(&bottom);
-                output.push_str("\n");
+    #[test]
+    fn test_to_image_inset() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+
+        // Without an inset, it's an ordinary rendering
+        assert_eq!(
+            grid.to_image(10, 1, background, wall).into_raw(),
+            grid.to_image_inset(10, 1, 0, background, wall).into_raw()
+        );
+
+        let image = grid.to_image_inset(10, 1, 3, background, wall);
+        assert_ne!(
+            grid.to_image(10, 1, background, wall).into_raw(),
+            image.clone().into_raw()
+        );
+        assert_eq!((51, 51), image.dimensions());
+        // The top left cell is linked east but not south: there are walls across the
+        // bottom of its inset square, and around the stub joining it to the next cell
+        assert!(grid.cells[0].contains(Cell::EAST) && !grid.cells[0].contains(Cell::SOUTH));
+        assert_eq!(&wall, image.get_pixel(5, 7));
+        assert_eq!(&wall, image.get_pixel(10, 3));
+        assert_eq!(&wall, image.get_pixel(10, 7));
+        assert_eq!(&background, image.get_pixel(10, 5));
+        // Outside the corridors there are gaps between the walls
+        assert_eq!(&background, image.get_pixel(1, 1));
+        assert_eq!(&background, image.get_pixel(5, 9));
+    }
 
-                top = "|".to_string();
-                bottom = "+".to_string();
+    #[test]
+    fn test_to_image_rounded() {
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        for &(width, height) in [(1, 1), (1, 5), (6, 1), (9, 7)].iter() {
+            let mut grid = Grid::new(width, height);
+            grid.recursive_backtracker(Some(4));
+            grid.carve_entrance_exit();
+            for &(cell_size, wall_size, inset) in
+                [(10, 1, 0), (10, 1, 3), (3, 1, 1), (20, 4, 6)].iter()
+            {
+                let image = grid.to_image_rounded(cell_size, wall_size, inset, background, wall);
+                let expected = grid.to_image_inset(cell_size, wall_size, inset, background, wall);
+                assert_eq!(expected.dimensions(), image.dimensions());
             }
         }
 
-        write!(f, "{}", output)
+        // A turn from NORTH to EAST: the outside corner at the bottom left is cut off,
+        // while the curve passes through the middle of the corner of the cell
+        let mut grid = Grid::new(2, 2);
+        grid.link(0, 2).unwrap();
+        grid.link(2, 3).unwrap();
+        grid.link(3, 1).unwrap();
+        let inset = grid.to_image_inset(20, 1, 4, background, wall);
+        let rounded = grid.to_image_rounded(20, 1, 4, background, wall);
+        assert_eq!(&wall, inset.get_pixel(4, 35));
+        assert_eq!(&background, rounded.get_pixel(4, 35));
+        assert_eq!(&wall, rounded.get_pixel(8, 33));
+        // The stubs on either side of the turn are still straight
+        assert_eq!(&wall, rounded.get_pixel(4, 22));
+        assert_eq!(&wall, rounded.get_pixel(18, 36));
     }
-}
 
-#[cfg(test)]
-mod tests {
+    #[test]
+    #[should_panic(expected = "an inset of 5 doesn't fit in cells 10 pixels across")]
+    fn test_to_image_inset_too_big() {
+        Grid::new(2, 2).to_image_inset(10, 1, 5, image::Rgb([255; 3]), image::Rgb([0; 3]));
+    }
 
-    use super::*;
-    use std::collections::HashSet;
+    #[test]
+    fn test_to_image_colored() {
+        // A single row is always a straight corridor
+        let mut grid = Grid::new(5, 1);
+        grid.binary_tree(None);
+
+        let image = grid.to_image_colored(10, 1, image::Rgb([0, 0, 0]), 0);
+        assert_eq!(&image::Rgb([0, 0, 255]), image.get_pixel(5, 5));
+        assert_eq!(&image::Rgb([128, 0, 127]), image.get_pixel(25, 5));
+        assert_eq!(&image::Rgb([255, 0, 0]), image.get_pixel(45, 5));
+        // Walls are still drawn on top
+        assert_eq!(&image::Rgb([0, 0, 0]), image.get_pixel(0, 0));
+
+        let image = Grid::new(2, 1).to_image_colored(10, 1, image::Rgb([0, 0, 0]), 0);
+        assert_eq!(&image::Rgb([0, 0, 255]), image.get_pixel(5, 5));
+        assert_eq!(&image::Rgb([255, 255, 255]), image.get_pixel(15, 5));
+    }
 
-    // A perfect maze has 2n - 2 edges
-    fn maze_is_perfect(grid: &Grid) -> bool {
-        let mut edges = 0;
-        for cell in grid.cells.iter() {
-            if cell.contains(Cell::NORTH) {
-                edges += 1;
+    #[test]
+    fn test_masked() {
+        let mut grid = Grid::with_mask(10, 10, ring_mask());
+        let generators: [fn(&mut Grid, Option<u64>); 6] = [
+            Grid::aldous_broder,
+            Grid::wilsons,
+            Grid::hunt_and_kill,
+            Grid::recursive_backtracker,
+            Grid::kruskals,
+            Grid::prims,
+        ];
+
+        for generate in generators.iter() {
+            for _i in 0..100 {
+                generate(&mut grid, None);
+
+                assert!(grid.is_perfect());
+                for i in (0..grid.cells.len()).filter(|&i| grid.is_masked(i)) {
+                    assert!(grid.cells[i].is_empty());
+                }
             }
+        }
+    }
 
-            if cell.contains(Cell::SOUTH) {
-                edges += 1;
-            }
+    #[test]
+    fn test_masked_never_linked() {
+        let mut grid = Grid::with_mask(10, 10, ring_mask());
+        // Each generator, and whether it joins up the pieces the mask leaves
+        type Generate = fn(&mut Grid, Option<u64>);
+        let generators: [(Generate, bool); 4] = [
+            (Grid::binary_tree, false),
+            (Grid::sidewinder, false),
+            (Grid::ellers, true),
+            (Grid::recursive_division, true),
+        ];
+
+        for &(generate, perfect) in generators.iter() {
+            for _i in 0..100 {
+                generate(&mut grid, None);
+                if perfect {
+                    assert!(grid.is_perfect());
+                }
 
-            if cell.contains(Cell::EAST) {
-                edges += 1;
+                for i in 0..grid.cells.len() {
+                    if grid.is_masked(i) {
+                        assert!(grid.cells[i].is_empty());
+                    } else {
+                        for &direction in Direction::all().iter() {
+                            if grid.cells[i].contains(direction.to_flag()) {
+                                assert!(grid.valid_direction(i, direction));
+                            }
+                        }
+                    }
+                }
             }
+        }
+    }
 
-            if cell.contains(Cell::WEST) {
-                edges += 1;
-            }
+    #[test]
+    fn test_masked_display() {
+        let mut mask = Mask::new(3, 2);
+        mask.set(0, false);
+        mask.set(5, false);
+        let grid = Grid::with_mask(3, 2, mask);
+
+        let expected =
+            "    +---+---+\n    |   |   |\n+---+---+---+\n|   |   |    \n+---+---+    \n";
+        assert_eq!(expected, format!("{}", grid));
+    }
+
+    #[test]
+    fn test_braid() {
+        let mut grid = Grid::new(20, 20);
+
+        for _i in 0..100 {
+            grid.recursive_backtracker(None);
+            let before = grid.dead_ends().len();
+            assert!(before > 0);
+
+            grid.braid(None, 0.0);
+            assert!(grid.is_perfect());
+            assert_eq!(before, grid.dead_ends().len());
+
+            grid.braid(None, 0.5);
+            assert!(grid.dead_ends().len() < before);
+
+            grid.braid(None, 1.0);
+            assert!(grid.dead_ends().is_empty());
         }
-        if (2 * grid.height * grid.width - 2) != edges {
-            println!("{}", grid);
-            println!(
-                "expect {:?} got {:?}",
-                2 * grid.height * grid.width - 2,
-                edges
-            );
-            println!("{:?} ", grid.cells)
+    }
+
+    #[test]
+    fn test_add_loops() {
+        let mut grid = Grid::new(12, 10);
+        for seed in 0..20 {
+            grid.kruskals(Some(seed));
+            let before = grid.edges();
+            grid.add_loops(Some(seed), 15);
+            let after = grid.edges();
+            assert_eq!(before.len() + 15, after.len());
+            assert!(before.iter().all(|edge| after.contains(edge)));
+            assert_eq!(Ok(()), grid.validate());
         }
-        (2 * grid.height * grid.width - 2) == edges
+
+        // Every wall inside the grid, and no more
+        grid.add_loops(Some(1), 1000);
+        assert_eq!(11 * 10 + 12 * 9, grid.edges().len());
+        grid.add_loops(Some(1), 10);
+        assert_eq!(11 * 10 + 12 * 9, grid.edges().len());
+
+        // Crossings stay valid
+        let mut grid = Grid::new(10, 10);
+        grid.weave(Some(3), 0.5);
+        grid.add_loops(Some(3), 30);
+        assert_eq!(Ok(()), grid.validate());
     }
 
     #[test]
-    fn test_binary_tree() {
-        let width = 50_usize;
-        let height = 50_usize;
+    fn test_json() {
+        let mut grid = Grid::new(7, 4);
+        grid.recursive_backtracker(None);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        assert!(json.starts_with(r#"{"cells":["#));
+        assert!(json.ends_with(
+            r#"],"width":7,"height":4,"mask":null,"weights":null,"wrap_horizontal":false,"wrap_vertical":false}"#
+        ));
+        assert_eq!(grid, serde_json::from_str(&json).unwrap());
+
+        let mut mask = Mask::new(7, 4);
+        mask.set(3, false);
+        let mut grid = Grid::with_mask(7, 4, mask);
+        grid.recursive_backtracker(None);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(grid, serde_json::from_str(&json).unwrap());
+
+        let json = r#"{"cells":[32],"width":1,"height":1}"#;
+        assert!(serde_json::from_str::<Grid>(json).is_err());
+    }
+
+    #[test]
+    fn test_from_buffer() {
+        let mut grid = Grid::new(6, 4);
+        grid.prims(Some(1));
+        let expected = grid.to_string();
+
+        // The cells come back out as they went in, in the same allocation
+        let cells = grid.into_cells();
+        let pointer = cells.as_ptr();
+        let mut grid = Grid::from_buffer(cells, 6, 4).unwrap();
+        assert_eq!(expected, grid.to_string());
+        grid.kruskals(Some(2));
+        assert!(grid.is_perfect());
+        let cells = grid.into_cells();
+        assert_eq!(pointer, cells.as_ptr());
+
+        // The same number of cells can be a different shape
+        let mut grid = Grid::from_buffer(cells, 3, 8).unwrap();
+        grid.ellers(Some(3));
+        assert!(grid.is_perfect());
+        assert_eq!(
+            Err(GridError::WrongCellCount {
+                expected: 25,
+                found: 24
+            }),
+            Grid::from_buffer(grid.into_cells(), 5, 5)
+        );
+        assert_eq!(
+            Err(GridError::WrongCellCount {
+                expected: usize::MAX,
+                found: 0
+            }),
+            Grid::from_buffer(Vec::new(), usize::MAX, 2)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut grid = Grid::new(4, 3);
+        assert_eq!(Ok(()), grid.validate());
+        grid.recursive_backtracker(None);
+        assert_eq!(Ok(()), grid.validate());
+
+        grid.cells.pop();
+        assert_eq!(
+            Err(GridError::WrongCellCount {
+                expected: 12,
+                found: 11
+            }),
+            grid.validate()
+        );
+
+        // Links off the edge are openings
+        let mut grid = Grid::new(4, 3);
+        grid.cells[2] = Cell::NORTH;
+        grid.cells[7] = Cell::EAST;
+        assert_eq!(Ok(()), grid.validate());
+
+        grid.cells[6] = Cell::SOUTH;
+        assert_eq!(
+            Err(GridError::InvalidLink {
+                cell: 6,
+                direction: Cell::SOUTH
+            }),
+            grid.validate()
+        );
+
+        let mut grid = Grid::new(4, 3);
+        grid.cells[6] = Cell::EAST;
+        assert_eq!(
+            Err(GridError::InvalidLink {
+                cell: 6,
+                direction: Cell::EAST
+            }),
+            grid.validate()
+        );
+
+        let mut mask = Mask::new(4, 3);
+        mask.set(1, false);
+        let mut grid = Grid::with_mask(4, 3, mask);
+        grid.cells[0] = Cell::EAST;
+        assert_eq!(
+            Err(GridError::InvalidLink {
+                cell: 0,
+                direction: Cell::EAST
+            }),
+            grid.validate()
+        );
+
+        let mut grid = Grid::with_mask(4, 3, Mask::new(4, 3));
+        grid.width = 3;
+        grid.cells.truncate(9);
+        assert_eq!(
+            Err(GridError::WrongMaskSize {
+                expected: (3, 3),
+                found: (4, 3)
+            }),
+            grid.validate()
+        );
+
+        let mut grid = Grid::new(4, 3);
+        grid.set_weight(5, 3);
+        assert_eq!(Ok(()), grid.validate());
+        grid.cells.truncate(8);
+        grid.height = 2;
+        assert_eq!(
+            Err(GridError::WrongWeightCount {
+                expected: 8,
+                found: 12
+            }),
+            grid.validate()
+        );
+    }
+
+    #[test]
+    fn test_check_symmetry() {
+        let mut grid = Grid::new(5, 4);
+        grid.set_wrap_horizontal(true);
+        grid.weave(Some(9), 1.0);
+        grid.carve_entrance_exit();
+        assert_eq!(Ok(()), grid.check_symmetry());
+
+        // Dropping one end of a passage leaves the other end pointing nowhere
+        let direction = Direction::all()
+            .iter()
+            .copied()
+            .find(|&direction| grid.cells[7].contains(direction.to_flag()))
+            .unwrap();
+        let end = grid.passage_end(7, direction);
+        grid.cells[7].remove(direction.to_flag());
+        assert_eq!(Err(vec![end]), grid.check_symmetry());
+
+        // Every one-sided link shows up, not just the first
+        let mut grid = Grid::new(4, 3);
+        grid.cells[1] = Cell::SOUTH;
+        grid.cells[6] = Cell::NORTH | Cell::EAST;
+        grid.cells[11] = Cell::SOUTH;
+        assert_eq!(Err(vec![1, 6]), grid.check_symmetry());
+    }
+
+    #[test]
+    fn test_hunt_and_kill() {
+        let width = 3_usize;
+        let height = 3_usize;
         for _i in 0..10000 {
             let mut grid = Grid::new(height, width);
-            grid.binary_tree(None);
+            grid.hunt_and_kill(None);
 
-            assert!(maze_is_perfect(&grid));
+            assert!(grid.is_perfect());
         }
     }
 
     #[test]
-    fn test_sidewinder() {
+    fn test_hunt_and_kill_all_mazes() {
+        let mut grid = Grid::new(3, 3);
+
+        // Hunt and kill is biased, and can't make all 192 mazes. When the hunt always
+        // linked to the first visited neighbor in the same order, it only made 80
+        let mut mazes = HashSet::new();
+        for _i in 0..100000 {
+            grid.hunt_and_kill(None);
+            mazes.insert(format!("{}", grid));
+        }
+        assert_eq!(111_usize, mazes.len());
+        assert!(mazes.is_subset(&all_mazes(3, 3)));
+    }
+
+    #[test]
+    fn test_recursive_backtracker() {
         let width = 50_usize;
         let height = 50_usize;
-        for _i in 0..10000 {
-            let mut grid = Grid::new(height, width);
-            grid.sidewinder(None);
+        let mut grid = Grid::new(height, width);
+
+        for _i in 0..100 {
+            grid.recursive_backtracker(None);
 
-            assert!(maze_is_perfect(&grid));
+            assert!(grid.is_perfect());
         }
     }
 
     #[test]
-    fn test_aldous_broder() {
+    fn test_prims() {
         let width = 50_usize;
         let height = 50_usize;
         let mut grid = Grid::new(height, width);
 
-        for _i in 0..1000 {
-            grid.aldous_broder(None);
+        for _i in 0..100 {
+            grid.prims(None);
 
-            assert!(maze_is_perfect(&grid));
+            assert!(grid.is_perfect());
         }
     }
 
     #[test]
-    fn test_aldous_broder_all_mazes() {
-        let width = 3_usize;
-        let height = 3_usize;
-        let mut grid = Grid::new(height, width);
+    fn test_carve_entrance_exit() {
+        let mut grid = Grid::new(3, 2);
+        grid.link_all();
+        grid.carve_entrance_exit();
+        assert!(grid.cells[0].contains(Cell::NORTH));
+        assert!(grid.cells[5].contains(Cell::SOUTH));
+        assert_eq!(Ok(()), grid.validate());
+        // Openings don't lead anywhere when solving
+        assert_eq!(2, grid.links(0).count());
+
+        let expected = "\
++   +---+---+
+|           |
++   +   +   +
+|           |
++---+---+   +
+";
+        assert_eq!(expected, grid.to_string());
+
+        let image = grid.to_image(10, 2, image::Rgb([255, 255, 255]), image::Rgb([0, 0, 0]));
+        assert_eq!(image::Rgb([255, 255, 255]), *image.get_pixel(6, 0));
+        assert_eq!(image::Rgb([0, 0, 0]), *image.get_pixel(16, 0));
+        assert_eq!(image::Rgb([255, 255, 255]), *image.get_pixel(26, 21));
+
+        grid.add_opening(3, Cell::WEST);
+        assert_eq!(Some("            |"), grid.to_string().lines().nth(3));
+        assert!(grid.to_unicode().lines().nth(3).unwrap().starts_with(' '));
+    }
 
-        let mut mazes = HashSet::new();
-        for _i in 0..100000 {
-            grid.aldous_broder(None);
-            mazes.insert(format!("{}", grid));
+    #[test]
+    fn test_to_adjacency() {
+        let mut grid = Grid::new(8, 6);
+        grid.set_wrap_horizontal(true);
+        grid.weave(Some(2), 0.5);
+        grid.carve_entrance_exit();
+
+        let adjacency = grid.to_adjacency();
+        assert_eq!(48, adjacency.len());
+        for (i, linked) in adjacency.iter().enumerate() {
+            assert_eq!(&grid.links(i).collect::<Vec<usize>>(), linked);
+            // Every passage shows up from both ends, and openings don't show up at all
+            assert!(linked.iter().all(|&j| adjacency[j].contains(&i)));
         }
-        assert_eq!(192_usize, mazes.len());
+        let passages: usize = adjacency.iter().map(|linked| linked.len()).sum();
+        assert_eq!(2 * 47, passages);
     }
 
     #[test]
-    fn test_wilsons() {
-        let width = 50_usize;
-        let height = 50_usize;
-        let mut grid = Grid::new(height, width);
+    fn test_edges() {
+        let mut grid = Grid::new(9, 7);
+        grid.set_wrap_horizontal(true);
+        for &algorithm in Algorithm::ALL.iter() {
+            grid.generate(algorithm, Some(4));
+            let edges = grid.edges();
+            assert_eq!(62, edges.len(), "{:?}", algorithm);
+            assert!(edges.iter().all(|&(a, b)| a < b));
+            assert!(edges.windows(2).all(|pair| pair[0] < pair[1]));
+        }
 
-        for _i in 0..1000 {
-            grid.wilsons(None);
-            assert!(maze_is_perfect(&grid));
+        // Tunnels are edges between the cells at either end
+        let mut mask = Mask::new(9, 7);
+        mask.set(30, false);
+        let mut grid = Grid::with_mask(9, 7, mask);
+        grid.weave(Some(4), 1.0);
+        assert_eq!(61, grid.edges().len());
+        for (a, b) in grid.edges() {
+            assert!(grid.links(a).any(|i| i == b));
+            assert!(grid.links(b).any(|i| i == a));
         }
     }
 
     #[test]
-    fn test_wilsons_all_mazes() {
-        let width = 3_usize;
-        let height = 3_usize;
-        let mut grid = Grid::new(height, width);
+    fn test_link() {
+        let mut mask = Mask::new(3, 3);
+        mask.set(8, false);
+        let mut grid = Grid::with_mask(3, 3, mask);
+
+        assert_eq!(Ok(()), grid.link(4, 1));
+        assert_eq!(Cell::NORTH, grid.cells[4]);
+        assert_eq!(Cell::SOUTH, grid.cells[1]);
+        assert_eq!(Ok(()), grid.link(4, 5));
+        assert_eq!(Ok(()), grid.validate());
+
+        assert_eq!(Err(LinkError::NotNeighbors { a: 0, b: 4 }), grid.link(0, 4));
+        assert_eq!(Err(LinkError::NotNeighbors { a: 2, b: 3 }), grid.link(2, 3));
+        assert_eq!(Err(LinkError::OutOfBounds { cell: 9 }), grid.link(6, 9));
+        assert_eq!(Err(LinkError::Masked { cell: 8 }), grid.unlink(5, 8));
+
+        assert_eq!(Ok(()), grid.unlink(1, 4));
+        assert_eq!(Ok(()), grid.unlink(1, 4));
+        assert_eq!(Cell::EAST, grid.cells[4]);
+        assert!(grid.cells[1].is_empty());
+    }
 
-        let mut mazes = HashSet::new();
-        for _i in 0..100000 {
-            grid.wilsons(None);
-            mazes.insert(format!("{}", grid));
+    #[test]
+    #[should_panic(expected = "not on the SOUTH border")]
+    fn test_add_opening_inside() {
+        let mut grid = Grid::new(3, 2);
+        grid.add_opening(0, Cell::SOUTH);
+    }
+
+    #[test]
+    fn test_index_coords() {
+        let grid = Grid::new(4, 3);
+        for i in 0..grid.cells.len() {
+            let (row, col) = grid.coords(i);
+            assert_eq!(i, grid.index(row, col));
         }
-        assert_eq!(192_usize, mazes.len());
+        assert_eq!((2, 3), grid.coords(11));
+        assert_eq!(4, grid.index(1, 0));
     }
 
     #[test]
-    fn test_hunt_and_kill() {
-        let width = 3_usize;
-        let height = 3_usize;
-        for _i in 0..10000 {
-            let mut grid = Grid::new(height, width);
-            grid.hunt_and_kill(None);
+    fn test_reset() {
+        let mut grid = Grid::new(6, 4);
+        grid.recursive_backtracker(None);
+        let cells = grid.cells.as_ptr();
+
+        grid.reset();
+        assert!(grid.cells.iter().all(|cell| cell.is_empty()));
+        assert_eq!(24, grid.cells.len());
+        assert_eq!(cells, grid.cells.as_ptr());
+
+        grid.kruskals(None);
+        assert_eq!(cells, grid.cells.as_ptr());
+    }
 
-            assert!(maze_is_perfect(&grid));
+    #[test]
+    fn test_carve_room() {
+        let mut grid = Grid::new(10, 8);
+        grid.recursive_backtracker(Some(11));
+        grid.carve_room(grid.index(2, 3), 4, 3);
+        assert_eq!(Ok(()), grid.validate());
+
+        for row in 2..5 {
+            for col in 3..7 {
+                let cell = grid.cells[grid.index(row, col)];
+                assert!(col == 6 || cell.contains(Cell::EAST));
+                assert!(col == 3 || cell.contains(Cell::WEST));
+                assert!(row == 2 || cell.contains(Cell::NORTH));
+                assert!(row == 4 || cell.contains(Cell::SOUTH));
+            }
         }
+
+        // Every cell can still be reached
+        let distances = grid.distances(0);
+        assert!(distances.iter().all(|distance| distance.is_some()));
     }
 
     #[test]
-    fn test_recursive_backtracker() {
-        let width = 50_usize;
-        let height = 50_usize;
-        let mut grid = Grid::new(height, width);
+    #[should_panic(expected = "a 4x3 room at (6, 3) doesn't fit in the 10x8 grid")]
+    fn test_carve_room_outside() {
+        Grid::new(10, 8).carve_room(63, 4, 3);
+    }
 
-        for _i in 0..100 {
-            grid.recursive_backtracker(None);
+    #[test]
+    #[should_panic(expected = "(0, 4) is outside the 4x3 grid")]
+    fn test_index_outside() {
+        Grid::new(4, 3).index(0, 4);
+    }
+
+    #[test]
+    fn test_to_image_sized() {
+        let mut grid = Grid::new(5, 3);
+        grid.recursive_backtracker(Some(4));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+
+        let image = grid.to_image_sized(20, 8, 2, background, wall);
+        assert_eq!((20 * 5 + 2, 8 * 3 + 2), image.dimensions());
+        // The outer walls are all there
+        assert_eq!(wall, *image.get_pixel(101, 25));
+        assert_eq!(wall, *image.get_pixel(50, 0));
+
+        let square = grid.to_image(10, 2, background, wall);
+        let sized = grid.to_image_sized(10, 10, 2, background, wall);
+        assert_eq!(square.into_raw(), sized.into_raw());
+    }
+
+    #[test]
+    fn test_to_image_with_border() {
+        let mut grid = Grid::new(6, 4);
+        grid.recursive_backtracker(Some(9));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let wall_pixels = |image: &RgbImage| image.pixels().filter(|&&pixel| pixel == wall).count();
+
+        let framed = grid.to_image_with_border(10, 2, background, wall, true);
+        assert_eq!(
+            grid.to_image(10, 2, background, wall).into_raw(),
+            framed.clone().into_raw()
+        );
+
+        let borderless = grid.to_image_with_border(10, 2, background, wall, false);
+        assert_eq!(framed.dimensions(), borderless.dimensions());
+        assert!(wall_pixels(&borderless) < wall_pixels(&framed));
+        // The corners of the frame are gone
+        for &(x, y) in [(0, 0), (61, 0), (0, 41), (61, 41)].iter() {
+            assert_eq!(wall, *framed.get_pixel(x, y));
+            assert_eq!(background, *borderless.get_pixel(x, y));
+        }
+        // Walls inside the maze are all still there
+        for y in 2..40 {
+            for x in 2..60 {
+                assert_eq!(framed.get_pixel(x, y), borderless.get_pixel(x, y));
+            }
+        }
+        // The grid itself is left as it was
+        assert_eq!(Ok(()), grid.validate());
+        assert_eq!(
+            2 * 23,
+            grid.cells
+                .iter()
+                .map(|cell| cell.bits().count_ones())
+                .sum::<u32>()
+        );
+    }
+
+    #[test]
+    fn test_to_string_empty() {
+        for &(width, height) in [(3, 0), (0, 3), (0, 0)].iter() {
+            let grid = Grid::new(width, height);
+            assert_eq!("", grid.to_string());
+            assert_eq!("", grid.to_string_with_border(false));
+        }
+    }
+
+    #[test]
+    fn test_to_string_with_border() {
+        let grid = Grid::new(2, 2);
+        assert_eq!(grid.to_string(), grid.to_string_with_border(true));
+        assert_eq!(
+            "+   +   +\n    |    \n+---+---+\n    |    \n+   +   +\n",
+            grid.to_string_with_border(false)
+        );
+    }
+
+    #[test]
+    fn test_growing_tree() {
+        let strategies = [
+            GrowingTreeStrategy::Newest,
+            GrowingTreeStrategy::Random,
+            GrowingTreeStrategy::Oldest,
+            GrowingTreeStrategy::Mixed(0.75),
+        ];
+        let mut grid = Grid::new(30, 30);
+
+        for &strategy in strategies.iter() {
+            for _i in 0..20 {
+                grid.growing_tree(None, strategy);
+                assert!(grid.is_perfect());
+            }
+        }
 
-            assert!(maze_is_perfect(&grid));
+        let mut grid = Grid::with_mask(10, 10, ring_mask());
+        for &strategy in strategies.iter() {
+            grid.growing_tree(None, strategy);
+            assert!(grid.is_perfect());
         }
     }
 }
@@ -2,28 +2,18 @@
 
 extern crate structopt;
 
-use bincode;
+use gif::SetParameter;
+use minotaur::Algorithm;
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
-use structopt::clap::{arg_enum, AppSettings};
+use std::str::FromStr;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
-arg_enum! {
-    #[derive(Debug)]
-    enum Algorithm {
-        BinaryTree,
-        Sidewinder,
-        AldousBroder,
-        Wilsons,
-        HuntAndKill,
-        RecursiveBacktracker,
-    }
-}
-
 fn parse_hex_to_rgb(src: &str) -> Result<image::Rgb<u8>, ParseHexError> {
-    let src = if src.starts_with('#') { &src[1..] } else { src };
+    let src = src.strip_prefix('#').unwrap_or(src);
 
     if src.len() != 6 {
         return Err(ParseHexError::Length(src.to_string()));
@@ -62,6 +52,51 @@ impl From<std::num::ParseIntError> for ParseHexError {
     }
 }
 
+// The ways --output can be saved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    Unicode,
+    Png,
+    Svg,
+    Mz,
+    Json,
+    Grid,
+}
+
+impl Format {
+    const NAMES: [&'static str; 7] = ["ascii", "unicode", "png", "svg", "mz", "json", "grid"];
+
+    // Without --format, the extension of the output file decides, falling back to ASCII art.
+    fn from_extension(path: &Path) -> Format {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("png") => Format::Png,
+            Some("svg") => Format::Svg,
+            Some("mz") => Format::Mz,
+            Some("json") => Format::Json,
+            Some("grid") => Format::Grid,
+            _ => Format::Ascii,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "ascii" => Ok(Format::Ascii),
+            "unicode" => Ok(Format::Unicode),
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            "mz" => Ok(Format::Mz),
+            "json" => Ok(Format::Json),
+            "grid" => Ok(Format::Grid),
+            _ => Err(format!("Unknown format: {:?}", s)),
+        }
+    }
+}
+
 #[derive(Debug, StructOpt)]
 #[structopt(
     rename_all = "kebab-case",
@@ -78,6 +113,9 @@ struct Opt {
         display_order = 0_usize
     )]
     algorithm: Algorithm,
+    /// List the algorithms --algorithm can be, with what their mazes look like, and exit
+    #[structopt(long = "list-algorithms", display_order = 0_usize)]
+    list_algorithms: bool,
     /// Maze width in number of cells
     #[structopt(
         short = "x",
@@ -94,21 +132,50 @@ struct Opt {
         display_order = 2_usize
     )]
     height: usize,
-    /// Output file. Can be ".png" for an image, ".mz" to store the maze inself for later loading, otherwise, saves as ASCII art
+    /// Output file. Can be ".png" for an image, ".svg" for a vector image, ".mz" or ".json" to store the maze inself for later loading, ".grid" for rows of "#" walls and "." floor tiles as games use, otherwise, saves as ASCII art
     #[structopt(short = "o", long = "output", default_value = "/dev/stdout")]
     output: String,
-    /// Input file of ".mz" stored from a previous run
+    /// Format to save --output in, whatever its extension. Lets an image go to stdout, e.g. to pipe it into another program [default: from the extension]
+    #[structopt(
+        short = "f",
+        long = "format",
+        case_insensitive = true,
+        raw(possible_values = "&Format::NAMES")
+    )]
+    format: Option<Format>,
+    /// Input file of ".mz" or ".json" stored from a previous run, or ".txt" ASCII art
     #[structopt(short = "i", long = "input")]
     input: Option<String>,
+    /// Image whose black pixels mark the cells to carve, for mazes that aren't rectangular. Overrides --width and --height
+    #[structopt(long = "mask")]
+    mask: Option<String>,
+    /// Save an animated ".gif" of the maze being carved, one frame per passage
+    #[structopt(long = "animate")]
+    animate: Option<String>,
     /// Seed for random number generator
     #[structopt(short = "s", long = "seed")]
     seed: Option<u64>,
+    /// Text to seed the random number generator with instead of --seed, e.g. a word that's easier to remember. The same text always makes the same maze
+    #[structopt(long = "seed-text", conflicts_with = "seed")]
+    seed_text: Option<String>,
     /// Cell size when saving to an image file
     #[structopt(long = "cell-size", default_value = "10")]
     cell_size: usize,
+    /// Cell width when saving to a .png image, for cells that aren't square [default: --cell-size]
+    #[structopt(long = "cell-width")]
+    cell_width: Option<usize>,
+    /// Cell height when saving to a .png image, for cells that aren't square [default: --cell-size]
+    #[structopt(long = "cell-height")]
+    cell_height: Option<usize>,
     /// Wall size when saving to an image file
     #[structopt(long = "wall-size", default_value = "1")]
     wall_size: usize,
+    /// Pull the walls of each cell in by this many pixels when saving to a .png image, so passages look like corridors
+    #[structopt(long = "inset")]
+    inset: Option<usize>,
+    /// Round off the outside of every turn with a curve when saving with --inset, for a maze that looks more like a cave
+    #[structopt(long = "rounded")]
+    rounded: bool,
     /// Background color when saving to an image file
     #[structopt(
         long = "background-color",
@@ -123,74 +190,387 @@ struct Opt {
         parse(try_from_str = "parse_hex_to_rgb")
     )]
     wall_color: image::Rgb<u8>,
+    /// Open the outer wall with an entrance at the top-left and an exit at the bottom-right
+    #[structopt(long = "openings")]
+    openings: bool,
+    /// Print statistics about the maze, such as how many dead ends it has, to stderr
+    #[structopt(long = "stats")]
+    stats: bool,
+    /// Draw walls with box drawing characters when saving as text
+    #[structopt(long = "unicode")]
+    unicode: bool,
+    /// Draw the maze in color with ANSI escape codes when saving as text, shaded by distance with --color-distances. Ignored if the NO_COLOR environment variable is set
+    #[structopt(long = "color")]
+    color: bool,
+    /// Draw the maze with block characters when saving as text, about one character per cell
+    #[structopt(long = "compact")]
+    compact: bool,
+    /// Shrink text output to fit the width of the terminal, drawing walls with blocks if it's too wide
+    #[cfg(feature = "terminal")]
+    #[structopt(long = "fit-terminal")]
+    fit_terminal: bool,
+    /// Solve the maze, marking the path from --start to --goal. Works on a maze loaded with --input too. Exits with an error if there is no path
+    #[structopt(long = "solve")]
+    solve: bool,
+    /// Cell index the solution starts from [default: first cell that isn't masked off]
+    #[structopt(long = "start")]
+    start: Option<usize>,
+    /// Cell index the solution ends at [default: last cell that isn't masked off]
+    #[structopt(long = "goal")]
+    goal: Option<usize>,
+    /// Path color when solving and saving to an image file
+    #[structopt(
+        long = "path-color",
+        default_value = "#FF0000",
+        parse(try_from_str = "parse_hex_to_rgb")
+    )]
+    path_color: image::Rgb<u8>,
+    /// Color each cell by its distance from --color-root when saving to an image file, or as text with --color. Takes precedence over --solve
+    #[structopt(long = "color-distances")]
+    color_distances: bool,
+    /// Cell index that distances are measured from with --color-distances
+    #[structopt(long = "color-root", default_value = "0")]
+    color_root: usize,
+}
+
+// Columns of the terminal that text output has to fit in, with --fit-terminal.
+#[cfg(feature = "terminal")]
+fn fit_columns(opt: &Opt) -> Option<usize> {
+    if !opt.fit_terminal {
+        return None;
+    }
+    // Without a terminal to measure, such as when piping, assume the usual 80 columns
+    let columns = terminal_size::terminal_size().map_or(80, |(width, _)| width.0);
+    Some(usize::from(columns))
+}
+
+#[cfg(not(feature = "terminal"))]
+fn fit_columns(_opt: &Opt) -> Option<usize> {
+    None
+}
+
+// Delays between frames of an animation, in hundredths of a second
+const FRAME_DELAY: u16 = 5;
+const FINAL_FRAME_DELAY: u16 = 300;
+
+// Animation writes snapshots of a maze to a GIF file as it's generated.
+struct Animation {
+    encoder: gif::Encoder<BufWriter<File>>,
+    cell_width: usize,
+    cell_height: usize,
+    wall_size: usize,
+    background_color: image::Rgb<u8>,
+    wall_color: image::Rgb<u8>,
+}
+
+impl Animation {
+    fn create(path: &str, grid: &minotaur::Grid, opt: &Opt) -> std::io::Result<Animation> {
+        let cell_width = opt.cell_width.unwrap_or(opt.cell_size);
+        let cell_height = opt.cell_height.unwrap_or(opt.cell_size);
+        let width = grid.width * cell_width + opt.wall_size;
+        let height = grid.height * cell_height + opt.wall_size;
+        if width > usize::from(u16::MAX) || height > usize::from(u16::MAX) {
+            return Err(std::io::Error::other("The maze is too big to animate"));
+        }
+
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &[])?;
+        encoder.set(gif::Repeat::Infinite)?;
+        Ok(Animation {
+            encoder,
+            cell_width,
+            cell_height,
+            wall_size: opt.wall_size,
+            background_color: opt.background_color,
+            wall_color: opt.wall_color,
+        })
+    }
+
+    fn render(&self, grid: &minotaur::Grid) -> image::RgbImage {
+        grid.to_image_sized(
+            self.cell_width,
+            self.cell_height,
+            self.wall_size,
+            self.background_color,
+            self.wall_color,
+        )
+    }
+
+    // Adds a frame showing grid, held for delay hundredths of a second.
+    fn add_frame(&mut self, grid: &minotaur::Grid, delay: u16) -> std::io::Result<()> {
+        let image = self.render(grid);
+        let (width, height) = image.dimensions();
+        let pixels: Vec<u8> = image
+            .pixels()
+            .map(|&pixel| (pixel == self.wall_color) as u8)
+            .collect();
+        let palette: Vec<u8> = [self.background_color, self.wall_color]
+            .iter()
+            .flat_map(|color| color.data.to_vec())
+            .collect();
+
+        let mut frame =
+            gif::Frame::from_palette_pixels(width as u16, height as u16, &pixels, &palette, None);
+        frame.delay = delay;
+        self.encoder.write_frame(&frame)
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
 
-fn main() -> std::io::Result<()> {
-    use minotaur::Grid;
-    use Algorithm::*;
+fn run() -> std::io::Result<()> {
+    use minotaur::{Grid, GridBuilder, Mask};
 
     let opt = Opt::from_args();
 
-    let grid = if let Some(input) = opt.input {
+    if opt.list_algorithms {
+        for algorithm in Algorithm::ALL.iter() {
+            let uniform = if algorithm.is_uniform() {
+                " (uniform)"
+            } else {
+                ""
+            };
+            println!(
+                "{:<22}{}{}",
+                algorithm.to_string(),
+                algorithm.description(),
+                uniform
+            );
+        }
+        return Ok(());
+    }
+
+    if opt.animate.is_some() && opt.input.is_some() {
+        return Err(std::io::Error::other(
+            "--animate shows a maze being generated, so it can't be used with --input",
+        ));
+    }
+
+    let seed = opt
+        .seed
+        .or_else(|| opt.seed_text.as_deref().map(Grid::seed_from_str));
+
+    let mut grid = if let Some(input) = &opt.input {
         let f = File::open(input)?;
-        bincode::deserialize_from(f).expect("Could not parse .mz file")
+        let grid: Grid = match Path::new(input).extension().and_then(OsStr::to_str) {
+            Some("json") => serde_json::from_reader(f)
+                .map_err(|e| std::io::Error::other(format!("Could not parse .json file: {}", e)))?,
+            Some("txt") => Grid::from_ascii(&std::fs::read_to_string(input)?)
+                .map_err(|e| std::io::Error::other(format!("Could not parse .txt file: {}", e)))?,
+            _ => Grid::from_mz(&std::fs::read(input)?)
+                .map_err(|e| std::io::Error::other(format!("Could not parse .mz file: {}", e)))?,
+        };
+        grid.validate()
+            .map_err(|e| std::io::Error::other(format!("Invalid maze in {}: {}", input, e)))?;
+        grid
     } else {
-        match opt.algorithm {
-            BinaryTree => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.binary_tree(opt.seed);
-                grid
-            }
-            Sidewinder => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.sidewinder(opt.seed);
-                grid
-            }
-            AldousBroder => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.aldous_broder(opt.seed);
-                grid
+        let mask = match &opt.mask {
+            Some(path) => Some(Mask::from_png(path).map_err(std::io::Error::other)?),
+            None => None,
+        };
+        if let Some(animate) = &opt.animate {
+            let mut grid = match mask {
+                Some(mask) => Grid::with_mask(mask.width, mask.height, mask),
+                None => Grid::new(opt.width, opt.height),
+            };
+            let mut animation = Animation::create(animate, &grid, &opt)?;
+            animation.add_frame(&grid, FRAME_DELAY)?;
+
+            let mut result = Ok(());
+            grid.generate_with_observer(opt.algorithm, seed, &mut |grid| {
+                if result.is_ok() {
+                    result = animation.add_frame(grid, FRAME_DELAY);
+                }
+            });
+            result?;
+            // Hold the finished maze on screen before the animation loops
+            animation.add_frame(&grid, FINAL_FRAME_DELAY)?;
+            grid
+        } else {
+            let builder = match mask {
+                Some(mask) => GridBuilder::new(mask.width, mask.height).mask(mask),
+                None => GridBuilder::new(opt.width, opt.height),
+            };
+            let builder = builder.algorithm(opt.algorithm);
+            match seed {
+                Some(seed) => builder.seed(seed).build(),
+                None => builder.build(),
             }
-            Wilsons => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.wilsons(opt.seed);
-                grid
+        }
+    };
+
+    if opt.openings {
+        grid.carve_entrance_exit();
+    }
+
+    if opt.stats {
+        eprint!("{}", grid.stats());
+    }
+
+    let path = if opt.solve {
+        // Like the entrance and exit, the defaults skip cells that are masked off
+        let mut unmasked = (0..grid.cells.len()).filter(|&i| !grid.is_masked(i));
+        let start = opt.start.or_else(|| unmasked.next()).unwrap_or(0);
+        let goal = opt.goal.or_else(|| unmasked.next_back()).unwrap_or(start);
+        for &cell in &[start, goal] {
+            if cell >= grid.cells.len() {
+                return Err(std::io::Error::other(format!(
+                    "Cell {} is outside the maze, which has {} cells",
+                    cell,
+                    grid.cells.len()
+                )));
             }
-            HuntAndKill => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.hunt_and_kill(opt.seed);
-                grid
+            if grid.is_masked(cell) {
+                return Err(std::io::Error::other(format!(
+                    "Cell {} is masked off, so it isn't part of the maze",
+                    cell
+                )));
             }
-            RecursiveBacktracker => {
-                let mut grid = Grid::new(opt.width, opt.height);
-                grid.recursive_backtracker(opt.seed);
-                grid
+        }
+        match grid.solve(start, goal) {
+            Some(path) => path,
+            None => {
+                return Err(std::io::Error::other(format!(
+                    "No path from cell {} to cell {}",
+                    start, goal
+                )))
             }
         }
+    } else {
+        Vec::new()
     };
 
     let filepath = Path::new(&opt.output);
+    let format = opt
+        .format
+        .unwrap_or_else(|| Format::from_extension(filepath));
 
-    match filepath.extension().and_then(OsStr::to_str) {
-        Some("png") => {
-            let image = grid.to_image(
-                opt.cell_size,
-                opt.wall_size,
-                opt.background_color,
-                opt.wall_color,
-            );
-            image.save(opt.output)?;
+    match format {
+        Format::Png => {
+            let cell_width = opt.cell_width.unwrap_or(opt.cell_size);
+            let cell_height = opt.cell_height.unwrap_or(opt.cell_size);
+            if cell_width != cell_height && (opt.color_distances || opt.solve) {
+                return Err(std::io::Error::other(
+                    "Cells that aren't square can't be used with --solve or --color-distances",
+                ));
+            }
+            if opt.rounded && opt.inset.is_none() {
+                return Err(std::io::Error::other("--rounded needs --inset"));
+            }
+            if let Some(inset) = opt.inset {
+                if cell_width != cell_height || opt.color_distances || opt.solve {
+                    return Err(std::io::Error::other(
+                        "--inset can't be used with --solve, --color-distances or cells that aren't square",
+                    ));
+                }
+                if 2 * inset >= cell_width {
+                    return Err(std::io::Error::other(format!(
+                        "--inset must be less than half of the cell size, which is {}",
+                        cell_width
+                    )));
+                }
+            }
+
+            // Past the checks above, only to_image_sized can get cells that aren't square
+            let image = if opt.color_distances {
+                grid.to_image_colored(cell_width, opt.wall_size, opt.wall_color, opt.color_root)
+            } else if opt.solve {
+                grid.to_image_with_path(
+                    cell_width,
+                    opt.wall_size,
+                    opt.background_color,
+                    opt.wall_color,
+                    &path,
+                    opt.path_color,
+                )
+            } else if let Some(inset) = opt.inset {
+                let render = if opt.rounded {
+                    Grid::to_image_rounded
+                } else {
+                    Grid::to_image_inset
+                };
+                render(
+                    &grid,
+                    cell_width,
+                    opt.wall_size,
+                    inset,
+                    opt.background_color,
+                    opt.wall_color,
+                )
+            } else {
+                grid.to_image_sized(
+                    cell_width,
+                    cell_height,
+                    opt.wall_size,
+                    opt.background_color,
+                    opt.wall_color,
+                )
+            };
+            // Encode to the file ourselves, since image would pick the format from the extension
+            let file = File::create(filepath)?;
+            let mut file_writer = BufWriter::new(file);
+            image::DynamicImage::ImageRgb8(image)
+                .write_to(&mut file_writer, image::ImageOutputFormat::PNG)
+                .map_err(std::io::Error::other)?;
+        }
+        Format::Svg => {
+            let [red, green, blue] = opt.wall_color.data;
+            let wall_color = format!("#{:02X}{:02X}{:02X}", red, green, blue);
+            let svg = grid.to_svg(opt.cell_size, opt.wall_size as f64, &wall_color);
+            let file = File::create(filepath)?;
+            let mut file_writer = BufWriter::new(file);
+            file_writer.write_all(svg.as_bytes())?;
+        }
+        Format::Json => {
+            let file = File::create(filepath)?;
+            let file_writer = BufWriter::new(file);
+            serde_json::to_writer(file_writer, &grid)?;
+        }
+        Format::Grid => {
+            let file = File::create(filepath)?;
+            let mut file_writer = BufWriter::new(file);
+            for line in grid.to_wall_grid() {
+                let tiles: String = line
+                    .iter()
+                    .map(|&wall| if wall { '#' } else { '.' })
+                    .collect();
+                writeln!(file_writer, "{}", tiles)?;
+            }
         }
-        Some("mz") => {
-            let encoded = bincode::serialize(&grid).unwrap();
+        Format::Mz => {
             let file = File::create(filepath)?;
             let mut file_writer = BufWriter::new(file);
-            file_writer.write_all(&encoded)?;
+            file_writer.write_all(&grid.to_mz())?;
         }
-        _ => {
+        Format::Ascii | Format::Unicode => {
             let file = File::create(filepath)?;
             let mut file_writer = BufWriter::new(file);
-            file_writer.write_all(format!("{}", grid).as_bytes())?;
+            // https://no-color.org: any value but an empty one turns color off
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            let text = if let Some(columns) = fit_columns(&opt) {
+                grid.to_string_scaled(columns)
+            } else if opt.color && !no_color {
+                let root = if opt.color_distances {
+                    Some(opt.color_root)
+                } else {
+                    None
+                };
+                grid.to_ansi(root)
+            } else if opt.compact {
+                grid.to_compact()
+            } else if opt.unicode || format == Format::Unicode {
+                grid.to_unicode()
+            } else if opt.solve {
+                grid.to_string_with_path(&path)
+            } else {
+                format!("{}", grid)
+            };
+            file_writer.write_all(text.as_bytes())?;
         }
     };
 
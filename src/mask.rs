@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+/*
+Mask marks which cells of a Grid are part of the maze.
+Cells that are off are never linked to anything, which
+lets mazes take on shapes other than a rectangle.
+*/
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    on: Vec<bool>,
+}
+
+impl Mask {
+    /// Creates a mask with every cell on.
+    pub fn new(width: usize, height: usize) -> Mask {
+        Mask {
+            width,
+            height,
+            on: vec![true; width * height],
+        }
+    }
+
+    /// Creates a mask from one bool per cell, in the same row-major order as Grid's cells.
+    /// Panics if on doesn't have exactly width * height entries.
+    pub fn from_vec(width: usize, height: usize, on: Vec<bool>) -> Mask {
+        assert_eq!(
+            width * height,
+            on.len(),
+            "a {}x{} mask needs {} cells",
+            width,
+            height,
+            width * height
+        );
+        Mask { width, height, on }
+    }
+
+    /// Creates a mask from an image, one cell per pixel. Black (dark) pixels are
+    /// on and get carved into the maze, everything else is off.
+    pub fn from_png<P: AsRef<Path>>(path: P) -> image::ImageResult<Mask> {
+        let image = image::open(path)?.to_luma();
+        let (width, height) = image.dimensions();
+        let on = image.pixels().map(|pixel| pixel[0] < 128).collect();
+        Ok(Mask::from_vec(width as usize, height as usize, on))
+    }
+
+    // Whether there is exactly one entry per cell, which deserializing doesn't guarantee.
+    pub(crate) fn has_valid_size(&self) -> bool {
+        self.width.checked_mul(self.height) == Some(self.on.len())
+    }
+
+    pub fn is_on(&self, i: usize) -> bool {
+        self.on[i]
+    }
+
+    pub fn set(&mut self, i: usize, on: bool) {
+        self.on[i] = on;
+    }
+
+    /// Number of cells that are on.
+    pub fn count(&self) -> usize {
+        self.on.iter().filter(|&&on| on).count()
+    }
+
+    /// Indices of the cells that are on, in order.
+    pub fn on_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.on
+            .iter()
+            .enumerate()
+            .filter(|(_, &on)| on)
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        let mut mask = Mask::new(3, 2);
+        assert_eq!(6, mask.count());
+
+        mask.set(1, false);
+        mask.set(4, false);
+        assert!(!mask.is_on(1));
+        assert_eq!(4, mask.count());
+        assert_eq!(vec![0, 2, 3, 5], mask.on_cells().collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_from_png() {
+        let mut image = image::GrayImage::from_pixel(4, 3, image::Luma([255]));
+        image.put_pixel(0, 0, image::Luma([0]));
+        image.put_pixel(3, 2, image::Luma([10]));
+        let path = std::env::temp_dir().join("minotaur_test_from_png.png");
+        image.save(&path).unwrap();
+
+        let mask = Mask::from_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((4, 3), (mask.width, mask.height));
+        assert_eq!(vec![0, 11], mask.on_cells().collect::<Vec<usize>>());
+    }
+}
@@ -0,0 +1,788 @@
+/*!
+The Maze trait lets the generating algorithms that only need to walk
+between neighboring cells work on any shape of grid.
+
+The algorithms here expect a maze with no links yet, and leave every
+cell that isn't masked off linked into a single perfect maze.
+*/
+
+use crate::Cancelled;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub trait Maze {
+    /// Which way a neighbor lies from a cell.
+    type Dir: Copy + PartialEq;
+
+    fn cell_count(&self) -> usize;
+
+    /// The direction and index of every cell next to cell i, whether or not they are linked.
+    /// Masked off cells have no neighbors, and are nobody's neighbor.
+    fn neighbors(&self, i: usize) -> Vec<(Self::Dir, usize)>;
+
+    /// Opens a passage between the neighboring cells a and b.
+    fn link(&mut self, a: usize, b: usize);
+
+    /// Whether cell i has been left out of the maze.
+    fn is_masked(&self, _i: usize) -> bool {
+        false
+    }
+
+    /// A uniformly random neighbor of cell i, or None if it has none.
+    /// The random walks call this a lot, so grids can override it to avoid building the Vec.
+    fn random_neighbor<R: Rng>(&self, i: usize, rng: &mut R) -> Option<usize> {
+        self.neighbors(i).choose(rng).map(|&(_, neighbor)| neighbor)
+    }
+
+    /// Which of candidates, unvisited neighbors of cell i, to carve a passage into next,
+    /// or None if there are none. Uniformly random unless a grid overrides it to steer
+    /// the algorithms that carve as they walk.
+    fn choose_next<R: Rng>(&self, _i: usize, candidates: &[usize], rng: &mut R) -> Option<usize> {
+        candidates.choose(rng).copied()
+    }
+}
+
+// Indices of the cells that are part of the maze.
+fn unmasked_cells<M: Maze>(maze: &M) -> Vec<usize> {
+    (0..maze.cell_count())
+        .filter(|&i| !maze.is_masked(i))
+        .collect()
+}
+
+// The neighbors of cell i in a random order, so that taking the first one that
+// will do doesn't always favour the same direction.
+fn shuffled_neighbors<M: Maze, R: Rng>(maze: &M, i: usize, rng: &mut R) -> Vec<usize> {
+    let mut neighbors = maze
+        .neighbors(i)
+        .into_iter()
+        .map(|(_, neighbor)| neighbor)
+        .collect::<Vec<usize>>();
+    neighbors.shuffle(rng);
+    neighbors
+}
+
+// Panics unless start is one of the cells of the maze.
+fn check_start<M: Maze>(maze: &M, start: usize) {
+    assert!(
+        start < maze.cell_count(),
+        "start cell {} is out of bounds for a maze of {} cells",
+        start,
+        maze.cell_count()
+    );
+    assert!(!maze.is_masked(start), "start cell {} is masked off", start);
+}
+
+/// aldous_broder populates a maze in an unbiased way.
+/// Basically, first, a cell is chosen at random and considered "visited."
+/// Travel to a random neighbor. If it is "unvisited", then
+/// link the two cells. Continue until all cells have been visited.
+pub fn aldous_broder<M: Maze, R: Rng>(maze: &mut M, rng: &mut R) {
+    if let Some(&start) = unmasked_cells(maze).choose(rng) {
+        aldous_broder_from(maze, rng, start);
+    }
+}
+
+/// aldous_broder_from works like aldous_broder, but starts the walk from cell start.
+/// Panics if start is out of bounds or masked off.
+pub fn aldous_broder_from<M: Maze, R: Rng>(maze: &mut M, rng: &mut R, start: usize) {
+    // Nothing can set it, so the walk always finishes
+    let _ = aldous_broder_walk(maze, rng, start, &AtomicBool::new(false), &mut |_| {});
+}
+
+/// aldous_broder_cancellable works like aldous_broder, but stops as soon as cancel is set.
+/// The passages carved until then are left in place.
+pub fn aldous_broder_cancellable<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    cancel: &AtomicBool,
+) -> Result<(), Cancelled> {
+    match unmasked_cells(maze).choose(rng) {
+        Some(&start) => aldous_broder_walk(maze, rng, start, cancel, &mut |_| {}),
+        None => Ok(()),
+    }
+}
+
+/// aldous_broder_with_progress works like aldous_broder, calling progress with the fraction
+/// of the cells that have joined the maze so far, from 0.0 to 1.0, e.g. to show a progress bar.
+/// It's only called when the fraction has gone up by at least a hundredth, and always
+/// with 1.0 once the maze is done.
+pub fn aldous_broder_with_progress<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    mut progress: impl FnMut(f64),
+) {
+    match unmasked_cells(maze).choose(rng) {
+        Some(&start) => {
+            let _ = aldous_broder_walk(maze, rng, start, &AtomicBool::new(false), &mut progress);
+        }
+        None => progress(1.0),
+    }
+}
+
+fn aldous_broder_walk<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    start: usize,
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(f64),
+) -> Result<(), Cancelled> {
+    check_start(maze, start);
+    let cells = unmasked_cells(maze);
+    let mut current_cell = start;
+
+    let mut visited = vec![false; maze.cell_count()];
+    visited[current_cell] = true;
+    let mut num_visited = 1;
+    let mut progress = Progress::new(progress, cells.len());
+    progress.update(num_visited);
+
+    while num_visited < cells.len() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Cancelled);
+        }
+        let next_cell = maze.random_neighbor(current_cell, rng).unwrap();
+
+        // If we haven't visited yet, link the cells up.
+        // Either way, keep random walking from here
+        if !visited[next_cell] {
+            maze.link(current_cell, next_cell);
+            visited[next_cell] = true;
+            num_visited += 1;
+            progress.update(num_visited);
+        }
+        current_cell = next_cell;
+    }
+    Ok(())
+}
+
+/// wilsons populates a maze in an unbiased way.
+/// First, some random cell is set to be "visited."
+/// Then, the first other cell is "started." From there,
+/// travel randomly until you hit a "visited" cell. Once you
+/// hit a "visited" cell, connect all the links from the "started"
+/// cell. Then start over from the next "unvisited" cell, in index order.
+///
+/// The trick is that there is a "loop removal" step. So while looking
+/// for a "visited" cell, if you loop back to a cell you've travelling through
+/// this run, then remove the loop you just made.
+pub fn wilsons<M: Maze, R: Rng>(maze: &mut M, rng: &mut R) {
+    if let Some(&start) = unmasked_cells(maze).choose(rng) {
+        wilsons_from(maze, rng, start);
+    }
+}
+
+/// wilsons_from works like wilsons, but with cell start as the first "visited" cell,
+/// which every walk heads for until the maze grows.
+/// Panics if start is out of bounds or masked off.
+pub fn wilsons_from<M: Maze, R: Rng>(maze: &mut M, rng: &mut R, start: usize) {
+    // Nothing can set it, so the walks always finish
+    let _ = wilsons_walks(maze, rng, start, &AtomicBool::new(false), &mut |_| {});
+}
+
+/// wilsons_cancellable works like wilsons, but stops as soon as cancel is set.
+/// The passages carved until then are left in place, while a walk that
+/// hasn't reached the maze yet is thrown away.
+pub fn wilsons_cancellable<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    cancel: &AtomicBool,
+) -> Result<(), Cancelled> {
+    match unmasked_cells(maze).choose(rng) {
+        Some(&start) => wilsons_walks(maze, rng, start, cancel, &mut |_| {}),
+        None => Ok(()),
+    }
+}
+
+/// wilsons_with_progress works like wilsons, calling progress with the fraction of the
+/// cells that have joined the maze so far, like aldous_broder_with_progress.
+/// The first walks take the longest, so it starts slowly and speeds up.
+pub fn wilsons_with_progress<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    mut progress: impl FnMut(f64),
+) {
+    match unmasked_cells(maze).choose(rng) {
+        Some(&start) => {
+            let _ = wilsons_walks(maze, rng, start, &AtomicBool::new(false), &mut progress);
+        }
+        None => progress(1.0),
+    }
+}
+
+fn wilsons_walks<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    start: usize,
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(f64),
+) -> Result<(), Cancelled> {
+    check_start(maze, start);
+
+    // Whether each cell has joined the maze yet
+    let mut in_maze = vec![false; maze.cell_count()];
+    in_maze[start] = true;
+    let mut joined = 1;
+    let mut progress = Progress::new(progress, unmasked_cells(maze).len());
+    progress.update(joined);
+    // Where the walk last left each cell for. Revisiting a cell overwrites
+    // its exit, which erases the loop.
+    let mut exits = vec![0; maze.cell_count()];
+
+    // Any order of starting cells makes every maze equally likely,
+    // so each walk starts from the first cell that isn't in the maze yet
+    for path_init in 0..maze.cell_count() {
+        if in_maze[path_init] || maze.is_masked(path_init) {
+            continue;
+        }
+
+        // Loop until we have finally reached a cell that's already in the maze.
+        let mut current_cell = path_init;
+        while !in_maze[current_cell] {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Cancelled);
+            }
+            let next_cell = maze.random_neighbor(current_cell, rng).unwrap();
+            exits[current_cell] = next_cell;
+            current_cell = next_cell;
+        }
+
+        current_cell = path_init;
+        while !in_maze[current_cell] {
+            let next_cell = exits[current_cell];
+            in_maze[current_cell] = true;
+            maze.link(current_cell, next_cell);
+            current_cell = next_cell;
+            joined += 1;
+        }
+        progress.update(joined);
+    }
+    Ok(())
+}
+
+// Progress passes on the fraction of a maze's cells that have joined it to report,
+// but only when it has gone up by at least a hundredth since the last time,
+// so that reporting doesn't slow generating down.
+struct Progress<'a> {
+    report: &'a mut dyn FnMut(f64),
+    cells: usize,
+    percent: Option<usize>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(report: &'a mut dyn FnMut(f64), cells: usize) -> Progress<'a> {
+        Progress {
+            report,
+            cells,
+            percent: None,
+        }
+    }
+
+    fn update(&mut self, joined: usize) {
+        let percent = joined * 100 / self.cells;
+        if self.percent.is_none_or(|reported| percent > reported) {
+            self.percent = Some(percent);
+            (self.report)(joined as f64 / self.cells as f64);
+        }
+    }
+}
+
+/// hunt_and_kill populates a maze with a random walk that never revisits a cell.
+/// When the walk boxes itself in, hunt for the first unvisited cell, in index order,
+/// that borders a visited one. Link it to one of its visited neighbors, chosen at random,
+/// and start walking again from there.
+pub fn hunt_and_kill<M: Maze, R: Rng>(maze: &mut M, rng: &mut R) {
+    if let Some(&start) = unmasked_cells(maze).choose(rng) {
+        hunt_and_kill_from(maze, rng, start);
+    }
+}
+
+/// hunt_and_kill_from works like hunt_and_kill, but starts the first walk from cell start.
+/// Panics if start is out of bounds or masked off.
+pub fn hunt_and_kill_from<M: Maze, R: Rng>(maze: &mut M, rng: &mut R, start: usize) {
+    check_start(maze, start);
+    let mut current_cell = start;
+
+    // Keep track of all visited cells.
+    let mut visited = vec![false; maze.cell_count()];
+    visited[current_cell] = true;
+    // Optimization: maintain frontier of possible cells that are
+    // potentially adjacent to a visited cell
+    let mut frontier = BinaryHeap::new();
+    frontier.push(Reverse(current_cell));
+
+    while !frontier.is_empty() {
+        // Loop until we boxed ourselves in with visited cells
+        loop {
+            let mut unvisited = Vec::new();
+            for (_, neighbor) in maze.neighbors(current_cell) {
+                if !visited[neighbor] {
+                    unvisited.push(neighbor);
+                    frontier.push(Reverse(neighbor));
+                }
+            }
+            if let Some(next_cell) = maze.choose_next(current_cell, &unvisited, rng) {
+                maze.link(current_cell, next_cell);
+                current_cell = next_cell;
+                visited[current_cell] = true;
+            } else {
+                break;
+            }
+        }
+        // Boxed in! Time to iterate through maze and select first unvisited cell that borders
+        // a visited cell
+        while visited[current_cell] && !frontier.is_empty() {
+            current_cell = frontier.pop().unwrap().0;
+        }
+
+        if visited[current_cell] {
+            break;
+        }
+        // Now link it with a random visited neighbor
+        visited[current_cell] = true;
+        let adjacent = shuffled_neighbors(maze, current_cell, rng)
+            .into_iter()
+            .find(|&neighbor| visited[neighbor]);
+        if let Some(neighbor) = adjacent {
+            maze.link(current_cell, neighbor);
+        }
+    }
+}
+
+/// recursive_backtracker populates a maze with a random walk that never revisits a cell.
+/// When the walk boxes itself in, back up along the walk until reaching a cell
+/// that still has an unvisited neighbor, and continue from there.
+pub fn recursive_backtracker<M: Maze, R: Rng>(maze: &mut M, rng: &mut R) {
+    if let Some(&start) = unmasked_cells(maze).choose(rng) {
+        recursive_backtracker_from(maze, rng, start);
+    }
+}
+
+/// recursive_backtracker_from works like recursive_backtracker, but starts the walk from cell start.
+/// Panics if start is out of bounds or masked off.
+pub fn recursive_backtracker_from<M: Maze, R: Rng>(maze: &mut M, rng: &mut R, start: usize) {
+    check_start(maze, start);
+
+    let mut visited = vec![false; maze.cell_count()];
+    visited[start] = true;
+    let mut cell_stack = vec![start];
+
+    while let Some(&current_cell) = cell_stack.last() {
+        let unvisited = maze
+            .neighbors(current_cell)
+            .into_iter()
+            .map(|(_, neighbor)| neighbor)
+            .filter(|&neighbor| !visited[neighbor])
+            .collect::<Vec<usize>>();
+
+        match maze.choose_next(current_cell, &unvisited, rng) {
+            Some(next_cell) => {
+                maze.link(current_cell, next_cell);
+                visited[next_cell] = true;
+                cell_stack.push(next_cell);
+            }
+            None => {
+                cell_stack.pop();
+            }
+        }
+    }
+}
+
+/// recursive_backtracker_straight works like recursive_backtracker, but with probability
+/// straightness, carries on in the same direction as the step that reached the current
+/// cell whenever that neighbor is unvisited, instead of picking among them all at random.
+/// The higher straightness is, the longer the corridors and the fewer the turns.
+pub fn recursive_backtracker_straight<M: Maze, R: Rng>(
+    maze: &mut M,
+    rng: &mut R,
+    straightness: f64,
+) {
+    let start = match unmasked_cells(maze).choose(rng) {
+        Some(&cell) => cell,
+        None => return,
+    };
+
+    let mut visited = vec![false; maze.cell_count()];
+    visited[start] = true;
+    // Each cell on the stack, with the direction of the step that reached it
+    let mut cell_stack: Vec<(usize, Option<M::Dir>)> = vec![(start, None)];
+
+    while let Some(&(current_cell, last_direction)) = cell_stack.last() {
+        let unvisited = maze
+            .neighbors(current_cell)
+            .into_iter()
+            .filter(|&(_, neighbor)| !visited[neighbor])
+            .collect::<Vec<(M::Dir, usize)>>();
+        if unvisited.is_empty() {
+            cell_stack.pop();
+            continue;
+        }
+
+        let straight = unvisited
+            .iter()
+            .find(|&&(direction, _)| Some(direction) == last_direction);
+        let next = match straight {
+            Some(&next) if rng.gen_bool(straightness) => next,
+            _ => *unvisited.choose(rng).unwrap(),
+        };
+        maze.link(current_cell, next.1);
+        visited[next.1] = true;
+        cell_stack.push((next.1, Some(next.0)));
+    }
+}
+
+/*
+GrowingTreeStrategy picks which active cell growing_tree carves from next,
+which decides what the maze looks like.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrowingTreeStrategy {
+    /// The most recently added cell. Long, winding passages with few dead ends,
+    /// the same as recursive_backtracker.
+    Newest,
+    /// A random cell. Lots of short dead ends branching off everywhere, like prims.
+    Random,
+    /// The cell that has been active the longest. Long straight passages
+    /// radiating out from the starting cell.
+    Oldest,
+    /// Newest with the given probability, otherwise Random.
+    /// Blends the long passages of Newest with the branching of Random.
+    Mixed(f64),
+}
+
+/// growing_tree populates a maze by keeping a list of "active" cells, starting with
+/// a random one. Repeatedly pick an active cell according to strategy and link it
+/// to one of its unvisited neighbors, which becomes active too. Once an active cell
+/// has no unvisited neighbors left, it is removed from the list.
+pub fn growing_tree<M: Maze, R: Rng>(maze: &mut M, rng: &mut R, strategy: GrowingTreeStrategy) {
+    let start = match unmasked_cells(maze).choose(rng) {
+        Some(&cell) => cell,
+        None => return,
+    };
+
+    let mut visited = vec![false; maze.cell_count()];
+    visited[start] = true;
+    // Oldest first, newest last
+    let mut active = vec![start];
+
+    while !active.is_empty() {
+        let index = match strategy {
+            GrowingTreeStrategy::Newest => active.len() - 1,
+            GrowingTreeStrategy::Random => rng.gen_range(0, active.len()),
+            GrowingTreeStrategy::Oldest => 0,
+            GrowingTreeStrategy::Mixed(newest) => {
+                if rng.gen_bool(newest.clamp(0.0, 1.0)) {
+                    active.len() - 1
+                } else {
+                    rng.gen_range(0, active.len())
+                }
+            }
+        };
+        let current_cell = active[index];
+
+        let unvisited = maze
+            .neighbors(current_cell)
+            .into_iter()
+            .map(|(_, neighbor)| neighbor)
+            .filter(|&neighbor| !visited[neighbor])
+            .collect::<Vec<usize>>();
+
+        match maze.choose_next(current_cell, &unvisited, rng) {
+            Some(next_cell) => {
+                maze.link(current_cell, next_cell);
+                visited[next_cell] = true;
+                active.push(next_cell);
+            }
+            None => {
+                active.remove(index);
+            }
+        }
+    }
+}
+
+/// prims populates a maze by growing it outwards from a random cell.
+/// Keep a list of "active" cells, starting with just that one. Repeatedly choose
+/// a random active cell and link it to one of its unvisited neighbors, which
+/// becomes active too. Once an active cell has no unvisited neighbors left,
+/// it is removed from the list. This is the "simplified" version of Prim's,
+/// which treats every passage as equally good.
+pub fn prims<M: Maze, R: Rng>(maze: &mut M, rng: &mut R) {
+    let start = match unmasked_cells(maze).choose(rng) {
+        Some(&cell) => cell,
+        None => return,
+    };
+
+    let mut visited = vec![false; maze.cell_count()];
+    visited[start] = true;
+    let mut active = vec![start];
+
+    while !active.is_empty() {
+        let index = rng.gen_range(0, active.len());
+        let current_cell = active[index];
+
+        let unvisited = maze
+            .neighbors(current_cell)
+            .into_iter()
+            .map(|(_, neighbor)| neighbor)
+            .filter(|&neighbor| !visited[neighbor])
+            .collect::<Vec<usize>>();
+
+        match maze.choose_next(current_cell, &unvisited, rng) {
+            Some(next_cell) => {
+                maze.link(current_cell, next_cell);
+                visited[next_cell] = true;
+                active.push(next_cell);
+            }
+            None => {
+                active.swap_remove(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use super::*;
+    use crate::{DeltaGrid, DisjointSet, Grid, HexGrid, Mask, PolarGrid};
+
+    use rand::SeedableRng;
+    use rand_pcg::Lcg64Xsh32;
+
+    // Whether the passages of maze form a spanning tree, so that every cell can be reached
+    // from every other by exactly one path. linked tells whether cell i has a passage
+    // in direction to its neighbor.
+    pub(crate) fn is_spanning_tree<M: Maze>(
+        maze: &M,
+        linked: impl Fn(usize, M::Dir, usize) -> bool,
+    ) -> bool {
+        let mut pieces = DisjointSet::new(maze.cell_count());
+        let mut passages = 0;
+        for i in 0..maze.cell_count() {
+            for (direction, neighbor) in maze.neighbors(i) {
+                if i < neighbor && linked(i, direction, neighbor) {
+                    if !pieces.union(i, neighbor) {
+                        return false;
+                    }
+                    passages += 1;
+                }
+            }
+        }
+        maze.cell_count() == 0 || passages == maze.cell_count() - 1
+    }
+
+    type Generator<M> = fn(&mut M, &mut Lcg64Xsh32);
+
+    fn generators<M: Maze>() -> [Generator<M>; 6] {
+        [
+            aldous_broder,
+            wilsons,
+            hunt_and_kill,
+            recursive_backtracker,
+            prims,
+            |maze, rng| growing_tree(maze, rng, GrowingTreeStrategy::Mixed(0.5)),
+        ]
+    }
+
+    #[test]
+    fn test_every_shape() {
+        let mut rng = Lcg64Xsh32::seed_from_u64(1);
+
+        for generate in generators::<Grid>().iter() {
+            let mut grid = Grid::new(9, 7);
+            generate(&mut grid, &mut rng);
+            let links = grid.cells.iter().map(|cell| cell.bits().count_ones());
+            assert_eq!(2 * 62, links.sum::<u32>());
+        }
+
+        for generate in generators::<HexGrid>().iter() {
+            let mut grid = HexGrid::new(9, 7);
+            generate(&mut grid, &mut rng);
+            let links = grid.cells.iter().map(|cell| cell.bits().count_ones());
+            assert_eq!(2 * 62, links.sum::<u32>());
+        }
+
+        for generate in generators::<DeltaGrid>().iter() {
+            let mut grid = DeltaGrid::new(9, 7);
+            generate(&mut grid, &mut rng);
+            let links = grid.cells.iter().map(|cell| cell.bits().count_ones());
+            assert_eq!(2 * 62, links.sum::<u32>());
+        }
+
+        for generate in generators::<PolarGrid>().iter() {
+            let mut grid = PolarGrid::new(5);
+            generate(&mut grid, &mut rng);
+            // Each passage is only recorded by one of its cells
+            let links = grid.cells.iter().map(|cell| cell.bits().count_ones());
+            assert_eq!(grid.cell_count() - 1, links.sum::<u32>() as usize);
+            assert!((0..grid.cell_count()).all(|i| !grid.links(i).is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_from_start() {
+        let mut mask = Mask::new(8, 6);
+        mask.set(0, false);
+        let starters: [fn(&mut Grid, Option<u64>, usize); 4] = [
+            Grid::aldous_broder_from,
+            Grid::wilsons_from,
+            Grid::hunt_and_kill_from,
+            Grid::recursive_backtracker_from,
+        ];
+
+        for generate in starters.iter() {
+            for &start in [1, 20, 47].iter() {
+                let mut grid = Grid::with_mask(8, 6, mask.clone());
+                generate(&mut grid, Some(3), start);
+                let links = (0..48).map(|i| grid.links(i).count());
+                assert_eq!(2 * 46, links.sum::<usize>());
+                assert!(grid.is_connected());
+
+                let mut again = Grid::with_mask(8, 6, mask.clone());
+                generate(&mut again, Some(3), start);
+                assert_eq!(grid, again);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_progress() {
+        type WithProgress = fn(&mut Grid, Option<u64>, &mut dyn FnMut(f64));
+        type Without = fn(&mut Grid, Option<u64>);
+        let with_progress: [(WithProgress, Without); 2] = [
+            (
+                |grid, seed, progress| grid.aldous_broder_with_progress(seed, progress),
+                Grid::aldous_broder,
+            ),
+            (
+                |grid, seed, progress| grid.wilsons_with_progress(seed, progress),
+                Grid::wilsons,
+            ),
+        ];
+        for (generate, without) in with_progress.iter() {
+            let mut reports = Vec::new();
+            let mut grid = Grid::new(40, 30);
+            generate(&mut grid, Some(3), &mut |fraction| reports.push(fraction));
+            assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(Some(&1.0), reports.last());
+            // At most once for each hundredth, rather than once per cell
+            assert!(reports.len() <= 101, "{} reports", reports.len());
+            assert!(reports.len() > 10);
+
+            // The same maze as without
+            let mut expected = Grid::new(40, 30);
+            without(&mut expected, Some(3));
+            assert_eq!(expected, grid);
+
+            // Done straight away
+            for &(width, height) in [(0, 0), (1, 1)].iter() {
+                let mut reports = Vec::new();
+                let mut grid = Grid::new(width, height);
+                generate(&mut grid, None, &mut |fraction| reports.push(fraction));
+                assert_eq!(vec![1.0], reports);
+            }
+        }
+    }
+
+    // A Grid that sets cancel once it has been linked links times, so the
+    // algorithms are cancelled at the same point every time.
+    struct CancelAfter<'a> {
+        grid: &'a mut Grid,
+        cancel: &'a AtomicBool,
+        links: usize,
+    }
+
+    impl Maze for CancelAfter<'_> {
+        type Dir = <Grid as Maze>::Dir;
+
+        fn cell_count(&self) -> usize {
+            self.grid.cell_count()
+        }
+
+        fn neighbors(&self, i: usize) -> Vec<(Self::Dir, usize)> {
+            Maze::neighbors(&*self.grid, i)
+        }
+
+        fn link(&mut self, a: usize, b: usize) {
+            Maze::link(&mut *self.grid, a, b);
+            self.links = self.links.saturating_sub(1);
+            if self.links == 0 {
+                self.cancel.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancellable() {
+        type Cancellable = fn(&mut Grid, Option<u64>, &AtomicBool) -> Result<(), Cancelled>;
+        let cancellable: [Cancellable; 2] =
+            [Grid::aldous_broder_cancellable, Grid::wilsons_cancellable];
+        for generate in cancellable.iter() {
+            // Without cancelling, it's the usual maze
+            let mut grid = Grid::new(9, 8);
+            assert_eq!(
+                Ok(()),
+                generate(&mut grid, Some(2), &AtomicBool::new(false))
+            );
+            assert!(grid.is_connected());
+
+            // Cancelled before it starts, nothing gets carved
+            assert_eq!(
+                Err(Cancelled),
+                generate(&mut grid, Some(2), &AtomicBool::new(true))
+            );
+            assert_eq!(Ok(()), grid.check_symmetry());
+            assert_eq!(72, grid.components());
+        }
+
+        // Cancelled part way through, once 20 of the 71 passages are carved
+        for &wilsons in [false, true].iter() {
+            let mut grid = Grid::new(9, 8);
+            let cancel = AtomicBool::new(false);
+            let mut maze = CancelAfter {
+                grid: &mut grid,
+                cancel: &cancel,
+                links: 20,
+            };
+            let rng = &mut Lcg64Xsh32::seed_from_u64(3);
+            let result = if wilsons {
+                wilsons_cancellable(&mut maze, rng, &cancel)
+            } else {
+                aldous_broder_cancellable(&mut maze, rng, &cancel)
+            };
+            assert_eq!(Err(Cancelled), result);
+            assert_eq!(Ok(()), grid.validate());
+            assert!(grid.edges().len() >= 20);
+            assert!(grid.components() > 1);
+        }
+
+        let mut grid = Grid::new(9, 8);
+        aldous_broder(&mut grid, &mut Lcg64Xsh32::seed_from_u64(5));
+        let mut cancellable = Grid::new(9, 8);
+        let result = aldous_broder_cancellable(
+            &mut cancellable,
+            &mut Lcg64Xsh32::seed_from_u64(5),
+            &AtomicBool::new(false),
+        );
+        assert_eq!(Ok(()), result);
+        assert_eq!(grid, cancellable);
+    }
+
+    #[test]
+    #[should_panic(expected = "start cell 48 is out of bounds for a maze of 48 cells")]
+    fn test_from_start_out_of_bounds() {
+        Grid::new(8, 6).recursive_backtracker_from(None, 48);
+    }
+
+    #[test]
+    #[should_panic(expected = "start cell 0 is masked off")]
+    fn test_from_start_masked() {
+        let mut mask = Mask::new(8, 6);
+        mask.set(0, false);
+        Grid::with_mask(8, 6, mask).wilsons_from(None, 0);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::error::UnpackError;
+use crate::{Cell, Grid, Mask};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Every .mz file written by to_mz starts with MAGIC and a version byte, followed by
+// the fields of the Grid in bincode. Files from before there was a header are bincode
+// of the Grid itself, whose fields have changed as features were added.
+const MAGIC: &[u8; 4] = b"MAZE";
+const VERSION: u8 = 1;
+
+// The fields of a Grid in version 1: cells, width, height, mask, weights,
+// wrap_horizontal and wrap_vertical.
+type Layout = (
+    Vec<Cell>,
+    usize,
+    usize,
+    Option<Mask>,
+    Option<Vec<u32>>,
+    bool,
+    bool,
+);
+
+impl Grid {
+    /// to_mz encodes the maze in the .mz format, to be loaded again by from_mz.
+    /// The mask, weights and wrapping are kept, but annotations aren't.
+    pub fn to_mz(&self) -> Vec<u8> {
+        let fields = (
+            &self.cells,
+            self.width,
+            self.height,
+            &self.mask,
+            &self.weights,
+            self.wrap_horizontal,
+            self.wrap_vertical,
+        );
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(bincode::serialize(&fields).unwrap());
+        bytes
+    }
+
+    /// from_mz decodes a maze written by to_mz, or a .mz file saved by an older version
+    /// from before the format had a header.
+    ///
+    /// Returns an error if the version is newer than this one understands, if the bytes
+    /// aren't a maze in any version of the format, or if the maze isn't valid.
+    pub fn from_mz(bytes: &[u8]) -> Result<Grid, UnpackError> {
+        let layout = match bytes.strip_prefix(MAGIC) {
+            Some(rest) => match rest.split_first() {
+                Some((&VERSION, fields)) => decode::<Layout>(fields),
+                Some((&version, _)) => return Err(UnpackError::UnsupportedVersion { version }),
+                None => None,
+            },
+            None => legacy_layout(bytes),
+        };
+        let (cells, width, height, mask, weights, wrap_horizontal, wrap_vertical) =
+            layout.ok_or(UnpackError::Malformed)?;
+
+        let grid = Grid {
+            cells,
+            width,
+            height,
+            mask,
+            weights,
+            wrap_horizontal,
+            wrap_vertical,
+            annotations: HashMap::new(),
+        };
+        grid.validate().map_err(UnpackError::InvalidGrid)?;
+        Ok(grid)
+    }
+}
+
+// Decodes bytes as bincode of a T, as long as that uses up every one of them.
+fn decode<T: Serialize + DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    let value = bincode::deserialize(bytes).ok()?;
+    if bincode::serialized_size(&value).ok()? == bytes.len() as u64 {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+// A .mz file from before the header, tried as each of the layouts a Grid has had,
+// newest first. Only one of them uses up exactly every byte of a file.
+fn legacy_layout(bytes: &[u8]) -> Option<Layout> {
+    type WithWrapHorizontal = (
+        Vec<Cell>,
+        usize,
+        usize,
+        Option<Mask>,
+        Option<Vec<u32>>,
+        bool,
+    );
+    type WithWeights = (Vec<Cell>, usize, usize, Option<Mask>, Option<Vec<u32>>);
+    type WithMask = (Vec<Cell>, usize, usize, Option<Mask>);
+    type Baseline = (Vec<Cell>, usize, usize);
+
+    // Every field of version 1, which is how the last of them were written
+    if let Some(layout) = decode::<Layout>(bytes) {
+        return Some(layout);
+    }
+    if let Some((cells, width, height, mask, weights, wrap_horizontal)) =
+        decode::<WithWrapHorizontal>(bytes)
+    {
+        return Some((cells, width, height, mask, weights, wrap_horizontal, false));
+    }
+    if let Some((cells, width, height, mask, weights)) = decode::<WithWeights>(bytes) {
+        return Some((cells, width, height, mask, weights, false, false));
+    }
+    if let Some((cells, width, height, mask)) = decode::<WithMask>(bytes) {
+        return Some((cells, width, height, mask, None, false, false));
+    }
+    decode::<Baseline>(bytes)
+        .map(|(cells, width, height)| (cells, width, height, None, None, false, false))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::GridError;
+
+    // The maze saved in each of the legacy fixtures, a 4x3 maze made with seed 7
+    fn fixture_maze() -> Grid {
+        Grid::from_ascii(include_str!("../tests/fixtures/maze.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_mz_round_trip() {
+        let mut mask = Mask::new(6, 5);
+        mask.set(8, false);
+        let mut grid = Grid::with_mask(6, 5, mask);
+        grid.set_wrap_horizontal(true);
+        grid.set_wrap_vertical(true);
+        grid.set_weight(3, 9);
+        grid.recursive_backtracker(Some(4));
+        let bytes = grid.to_mz();
+        assert_eq!(b"MAZE\x01", &bytes[..5]);
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+
+        let mut grid = Grid::new(10, 8);
+        grid.weave(Some(5), 1.0);
+        let bytes = grid.to_mz();
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+
+        let grid = Grid::new(0, 0);
+        let bytes = grid.to_mz();
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+    }
+
+    #[test]
+    fn test_mz_legacy() {
+        // Written before the header, with the fields the Grid had at the time:
+        // first only cells, width and height, then a mask as well, then weights,
+        // then horizontal wrapping, and last vertical wrapping
+        let fixtures: [&[u8]; 5] = [
+            include_bytes!("../tests/fixtures/baseline.mz"),
+            include_bytes!("../tests/fixtures/mask.mz"),
+            include_bytes!("../tests/fixtures/weights.mz"),
+            include_bytes!("../tests/fixtures/wrap_horizontal.mz"),
+            include_bytes!("../tests/fixtures/wrap_vertical.mz"),
+        ];
+        for bytes in fixtures.iter() {
+            assert_eq!(Ok(fixture_maze()), Grid::from_mz(bytes));
+        }
+
+        // The fields those layouts had are read too
+        let mut grid = Grid::new(2, 1);
+        grid.link(0, 1).unwrap();
+        grid.set_weight(1, 5);
+        let weights = Some(vec![1, 5]);
+        let fields = (&grid.cells, grid.width, grid.height, None::<Mask>, weights);
+        let bytes = bincode::serialize(&fields).unwrap();
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+
+        let mut grid = Grid::new(2, 1);
+        grid.set_wrap_horizontal(true);
+        grid.link(1, 0).unwrap();
+        let fields = (
+            &grid.cells,
+            grid.width,
+            grid.height,
+            None::<Mask>,
+            None::<Vec<u32>>,
+            true,
+        );
+        let bytes = bincode::serialize(&fields).unwrap();
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+
+        let mut grid = Grid::new(1, 2);
+        grid.set_wrap_vertical(true);
+        grid.link(1, 0).unwrap();
+        let bytes = bincode::serialize(&grid).unwrap();
+        assert_eq!(Ok(grid), Grid::from_mz(&bytes));
+    }
+
+    #[test]
+    fn test_from_mz_errors() {
+        let bytes = Grid::new(3, 2).to_mz();
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        assert_eq!(
+            Err(UnpackError::UnsupportedVersion { version: 2 }),
+            Grid::from_mz(&newer)
+        );
+        assert_eq!(Err(UnpackError::Malformed), Grid::from_mz(&bytes[..5]));
+        assert_eq!(
+            Err(UnpackError::Malformed),
+            Grid::from_mz(&bytes[..bytes.len() - 1])
+        );
+        assert_eq!(Err(UnpackError::Malformed), Grid::from_mz(b"MAZE"));
+        assert_eq!(Err(UnpackError::Malformed), Grid::from_mz(&[1, 2, 3]));
+
+        // A legacy file with a byte too many
+        let mut bytes = include_bytes!("../tests/fixtures/baseline.mz").to_vec();
+        bytes.push(7);
+        assert_eq!(Err(UnpackError::Malformed), Grid::from_mz(&bytes));
+
+        // Dimensions too big to multiply, with no cells at all
+        let too_big = UnpackError::InvalidGrid(GridError::WrongCellCount {
+            expected: usize::MAX,
+            found: 0,
+        });
+        let cells: Vec<Cell> = Vec::new();
+        let bytes = bincode::serialize(&(&cells, usize::MAX, usize::MAX)).unwrap();
+        assert_eq!(Err(too_big.clone()), Grid::from_mz(&bytes));
+
+        let fields: Layout = (cells, usize::MAX, 2, None, None, false, false);
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(bincode::serialize(&fields).unwrap());
+        assert_eq!(Err(too_big), Grid::from_mz(&bytes));
+    }
+}
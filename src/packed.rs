@@ -0,0 +1,247 @@
+use crate::error::UnpackError;
+use crate::{Cell, Direction, Grid};
+
+use std::convert::TryFrom;
+
+// Bytes for the width and height at the start of the packed format
+const HEADER_SIZE: usize = 8;
+
+impl Grid {
+    /// to_packed stores the maze in a compact binary format, about half the size of bincode.
+    /// The width and height come first, as little-endian u32s, followed by each cell's
+    /// NORTH, SOUTH, EAST and WEST flags packed into half a byte, two cells to a byte.
+    ///
+    /// The mask, weights and wrapping aren't stored. UNDER isn't either, since from_packed
+    /// can tell where passages tunnel beneath a cell.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(8, 8);
+    /// grid.recursive_backtracker(Some(1));
+    /// let packed = grid.to_packed();
+    /// assert_eq!(8 + 32, packed.len());
+    /// assert_eq!(Ok(grid), Grid::from_packed(&packed));
+    /// ```
+    ///
+    /// Panics if the width or height doesn't fit in a u32.
+    pub fn to_packed(&self) -> Vec<u8> {
+        let mut packed = Vec::with_capacity(HEADER_SIZE + self.cells.len().div_ceil(2));
+        self.write_header(&mut packed);
+
+        for pair in self.cells.chunks(2) {
+            let low = (pair[0] - Cell::UNDER).bits();
+            let high = pair.get(1).map_or(0, |&cell| (cell - Cell::UNDER).bits());
+            packed.push(low | high << 4);
+        }
+        packed
+    }
+
+    /// from_packed reads a maze stored by to_packed.
+    /// Returns an error if the bytes are the wrong length for the maze's dimensions,
+    /// or the cells don't make a valid maze.
+    pub fn from_packed(packed: &[u8]) -> Result<Grid, UnpackError> {
+        let (width, height) = read_header(packed, |count| count.div_ceil(2))?;
+
+        let mut grid = Grid::new(width, height);
+        for (i, cell) in grid.cells.iter_mut().enumerate() {
+            let bits = packed[HEADER_SIZE + i / 2] >> (4 * (i % 2));
+            *cell = Cell::from_bits_truncate(bits & 0b1111);
+        }
+
+        // A neighbor only links to a cell without the cell linking back
+        // when the passage tunnels beneath it
+        for i in 0..grid.cells.len() {
+            let tunneled = Direction::all().iter().any(|&direction| {
+                grid.in_bounds(i, direction)
+                    && !grid.cells[i].contains(direction.to_flag())
+                    && grid.cells[grid.neighbor(i, direction)]
+                        .contains(direction.opposite().to_flag())
+            });
+            if tunneled {
+                grid.cells[i] |= Cell::UNDER;
+            }
+        }
+
+        grid.validate().map_err(UnpackError::InvalidGrid)?;
+        Ok(grid)
+    }
+
+    /// to_bytes_le stores the maze in a plain binary layout that's easy to read
+    /// without this crate, e.g. from C, or Python with ctypes or struct:
+    ///
+    /// | Offset      | Size          | Contents                                   |
+    /// |-------------|---------------|--------------------------------------------|
+    /// | 0           | 4             | width, as a little-endian u32              |
+    /// | 4           | 4             | height, as a little-endian u32             |
+    /// | 8 + i       | 1             | the bits of cell i, row by row from the top left |
+    ///
+    /// The bits of each cell are 1 for NORTH, 2 for SOUTH, 4 for EAST, 8 for WEST and 16
+    /// for UNDER, the same as Cell::bits. Unlike bincode, the layout won't change when the
+    /// Grid struct does. The mask, weights and wrapping aren't stored.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(2, 1);
+    /// grid.link(0, 1).unwrap();
+    /// assert_eq!(vec![2, 0, 0, 0, 1, 0, 0, 0, 4, 8], grid.to_bytes_le());
+    /// ```
+    ///
+    /// Panics if the width or height doesn't fit in a u32.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.cells.len());
+        self.write_header(&mut bytes);
+        bytes.extend(self.cells.iter().map(|cell| cell.bits()));
+        bytes
+    }
+
+    /// from_bytes_le reads a maze stored by to_bytes_le.
+    /// Returns an error if the bytes are the wrong length for the maze's dimensions,
+    /// or the cells don't make a valid maze. Unknown bits in a cell are ignored.
+    pub fn from_bytes_le(bytes: &[u8]) -> Result<Grid, UnpackError> {
+        let (width, height) = read_header(bytes, |count| count)?;
+        let mut grid = Grid::new(width, height);
+        for (cell, &bits) in grid.cells.iter_mut().zip(bytes[HEADER_SIZE..].iter()) {
+            *cell = Cell::from_bits_truncate(bits);
+        }
+        grid.validate().map_err(UnpackError::InvalidGrid)?;
+        Ok(grid)
+    }
+
+    // Writes the width and height, as little-endian u32s.
+    fn write_header(&self, bytes: &mut Vec<u8>) {
+        for &size in &[self.width, self.height] {
+            let size = u32::try_from(size).expect("grid is too big to pack");
+            bytes.extend_from_slice(&size.to_le_bytes());
+        }
+    }
+}
+
+// Reads the width and height written by write_header, checking that bytes is
+// as long as it should be with body_size(width * height) bytes after them.
+fn read_header(
+    bytes: &[u8],
+    body_size: impl Fn(usize) -> usize,
+) -> Result<(usize, usize), UnpackError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(UnpackError::MissingHeader);
+    }
+    let read_size = |start: usize| {
+        let mut size = [0; 4];
+        size.copy_from_slice(&bytes[start..start + 4]);
+        u32::from_le_bytes(size) as usize
+    };
+    let (width, height) = (read_size(0), read_size(4));
+
+    let cell_count = width.checked_mul(height);
+    let expected = cell_count.map(|count| HEADER_SIZE + body_size(count));
+    if expected != Some(bytes.len()) {
+        return Err(UnpackError::WrongLength {
+            expected: expected.unwrap_or(usize::MAX),
+            found: bytes.len(),
+        });
+    }
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::GridError;
+
+    #[test]
+    fn test_packed_round_trip() {
+        let mut grid = Grid::new(7, 5);
+        grid.recursive_backtracker(None);
+        grid.carve_entrance_exit();
+        let packed = grid.to_packed();
+        assert_eq!(Ok(grid), Grid::from_packed(&packed));
+
+        let mut grid = Grid::new(12, 9);
+        grid.weave(None, 1.0);
+        let packed = grid.to_packed();
+        assert_eq!(Ok(grid), Grid::from_packed(&packed));
+
+        let grid = Grid::new(0, 0);
+        let packed = grid.to_packed();
+        assert_eq!(Ok(grid), Grid::from_packed(&packed));
+    }
+
+    #[test]
+    fn test_packed_size() {
+        let mut grid = Grid::new(20, 20);
+        grid.recursive_backtracker(None);
+        let packed = grid.to_packed();
+        let encoded = bincode::serialize(&grid).unwrap();
+        assert_eq!(8 + 200, packed.len());
+        assert!(2 * packed.len() < encoded.len());
+    }
+
+    #[test]
+    fn test_bytes_le_round_trip() {
+        let mut grid = Grid::new(7, 5);
+        grid.recursive_backtracker(Some(2));
+        grid.carve_entrance_exit();
+        let bytes = grid.to_bytes_le();
+        assert_eq!(8 + 35, bytes.len());
+        assert_eq!(&[7, 0, 0, 0, 5, 0, 0, 0], &bytes[..8]);
+        for (i, cell) in grid.cells.iter().enumerate() {
+            assert_eq!(cell.bits(), bytes[8 + i]);
+        }
+        assert_eq!(Ok(grid), Grid::from_bytes_le(&bytes));
+
+        // UNDER is kept as it is
+        let mut grid = Grid::new(12, 9);
+        grid.weave(Some(3), 1.0);
+        assert!(grid.cells.iter().any(|cell| cell.contains(Cell::UNDER)));
+        let bytes = grid.to_bytes_le();
+        assert_eq!(Ok(grid), Grid::from_bytes_le(&bytes));
+
+        let grid = Grid::new(0, 3);
+        assert_eq!(Ok(grid), Grid::from_bytes_le(&[0, 0, 0, 0, 3, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_from_bytes_le_errors() {
+        assert_eq!(Err(UnpackError::MissingHeader), Grid::from_bytes_le(&[]));
+        assert_eq!(
+            Err(UnpackError::WrongLength {
+                expected: 14,
+                found: 13
+            }),
+            Grid::from_bytes_le(&[2, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            Err(UnpackError::InvalidGrid(GridError::InvalidLink {
+                cell: 0,
+                direction: Cell::EAST
+            })),
+            Grid::from_bytes_le(&[2, 0, 0, 0, 1, 0, 0, 0, 4, 0])
+        );
+    }
+
+    #[test]
+    fn test_from_packed_errors() {
+        assert_eq!(
+            Err(UnpackError::MissingHeader),
+            Grid::from_packed(&[2, 0, 0, 0])
+        );
+        assert_eq!(
+            Err(UnpackError::WrongLength {
+                expected: 11,
+                found: 10
+            }),
+            Grid::from_packed(&[2, 0, 0, 0, 3, 0, 0, 0, 0, 0])
+        );
+        // A 2x1 grid whose first cell links east, but the second doesn't link back
+        assert_eq!(
+            Err(UnpackError::InvalidGrid(GridError::InvalidLink {
+                cell: 0,
+                direction: Cell::EAST
+            })),
+            Grid::from_packed(&[2, 0, 0, 0, 1, 0, 0, 0, 0b0000_0100])
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::maze::{self, Maze};
+use crate::{Cell, Grid};
+
+use rand::Rng;
+
+// Bits stored for each cell: whether it has a passage EAST, and whether it has one SOUTH.
+// Its passages NORTH and WEST are the SOUTH and EAST passages of its neighbors.
+const EAST: u8 = 0b01;
+const SOUTH: u8 = 0b10;
+const CELLS_PER_BYTE: usize = 4;
+
+/*
+PackedGrid is a maze stored in 2 bits per cell, a quarter of the memory of a Grid,
+for mazes so big a Grid wouldn't fit: a 10000x10000 maze takes 25MB instead of 100MB.
+
+Since every passage is stored in both of the cells it joins in a Grid, each cell
+only needs to remember whether it has a passage EAST and whether it has one SOUTH.
+The cells are packed four to a byte, row by row starting from the top left.
+
+There are no masks, weights, wrapping or tunnels. Use to_grid to render small ones,
+or as_bytes to save them. The algorithms in maze work on it too, through the Maze trait,
+though most of them need more memory of their own than the grid itself. ellers and
+binary_tree need next to none.
+*/
+#[derive(PartialEq, Clone, Debug)]
+pub struct PackedGrid {
+    pub width: usize,
+    pub height: usize,
+    bits: Vec<u8>,
+}
+
+impl PackedGrid {
+    pub fn new(width: usize, height: usize) -> PackedGrid {
+        PackedGrid {
+            width,
+            height,
+            bits: vec![0; (width * height).div_ceil(CELLS_PER_BYTE)],
+        }
+    }
+
+    /// from_grid packs the passages of grid. Openings in the outer wall, passages
+    /// across the seams of a wrapping grid, UNDER, the mask and weights are all dropped.
+    pub fn from_grid(grid: &Grid) -> PackedGrid {
+        let mut packed = PackedGrid::new(grid.width, grid.height);
+        for (i, cell) in grid.cells.iter().enumerate() {
+            let (row, col) = (i / grid.width, i % grid.width);
+            if col + 1 < grid.width && cell.contains(Cell::EAST) {
+                packed.set(i, EAST);
+            }
+            if row + 1 < grid.height && cell.contains(Cell::SOUTH) {
+                packed.set(i, SOUTH);
+            }
+        }
+        packed
+    }
+
+    /// to_grid unpacks the maze into a Grid, e.g. to render it.
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::new(self.width, self.height);
+        for (i, cell) in grid.cells.iter_mut().enumerate() {
+            *cell = self.cell(i);
+        }
+        grid
+    }
+
+    /// as_bytes is the packed cells, four to a byte, in the order of the cells:
+    /// bit 0 of the first byte is whether cell 0 has a passage EAST, bit 1 whether it
+    /// has one SOUTH, bits 2 and 3 are the same for cell 1, and so on.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// cell returns the passages of cell i, like the cells of a Grid.
+    pub fn cell(&self, i: usize) -> Cell {
+        let mut cell = Cell::default();
+        if self.get(i, EAST) {
+            cell |= Cell::EAST;
+        }
+        if self.get(i, SOUTH) {
+            cell |= Cell::SOUTH;
+        }
+        if !i.is_multiple_of(self.width) && self.get(i - 1, EAST) {
+            cell |= Cell::WEST;
+        }
+        if i >= self.width && self.get(i - self.width, SOUTH) {
+            cell |= Cell::NORTH;
+        }
+        cell
+    }
+
+    /// binary_tree populates the maze like Grid::binary_tree,
+    /// making exactly the same maze for the same seed.
+    pub fn binary_tree(&mut self, seed: Option<u64>) {
+        let mut rng = Grid::get_rng(seed);
+        self.bits.iter_mut().for_each(|byte| *byte = 0);
+
+        for i in 0..self.width * self.height {
+            let north_valid = i >= self.width;
+            let east_valid = !(i + 1).is_multiple_of(self.width);
+
+            if north_valid && (!east_valid || rng.gen()) {
+                self.set(i - self.width, SOUTH);
+            } else if east_valid {
+                self.set(i, EAST);
+            }
+        }
+    }
+
+    /// ellers populates the maze like Grid::ellers, making exactly the same maze for
+    /// the same seed. It only needs memory for a couple of rows on top of the grid.
+    pub fn ellers(&mut self, seed: Option<u64>) {
+        let width = self.width;
+        let mut row_start = 0;
+        Grid::ellers_streaming(self.width, self.height, seed, |row| {
+            for (col, cell) in row.iter().enumerate() {
+                let i = row_start + col;
+                let bits = &mut self.bits[i / CELLS_PER_BYTE];
+                let shift = 2 * (i % CELLS_PER_BYTE);
+                *bits &= !((EAST | SOUTH) << shift);
+                if cell.contains(Cell::EAST) {
+                    *bits |= EAST << shift;
+                }
+                if cell.contains(Cell::SOUTH) {
+                    *bits |= SOUTH << shift;
+                }
+            }
+            row_start += width;
+        });
+    }
+
+    /// recursive_backtracker populates the maze with maze::recursive_backtracker.
+    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
+        self.bits.iter_mut().for_each(|byte| *byte = 0);
+        maze::recursive_backtracker(self, &mut Grid::get_rng(seed));
+    }
+
+    fn get(&self, i: usize, flag: u8) -> bool {
+        let shift = 2 * (i % CELLS_PER_BYTE);
+        self.bits[i / CELLS_PER_BYTE] & flag << shift != 0
+    }
+
+    fn set(&mut self, i: usize, flag: u8) {
+        let shift = 2 * (i % CELLS_PER_BYTE);
+        self.bits[i / CELLS_PER_BYTE] |= flag << shift;
+    }
+}
+
+impl Maze for PackedGrid {
+    type Dir = Cell;
+
+    fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(Cell, usize)> {
+        let (row, col) = (i / self.width, i % self.width);
+        let mut neighbors = Vec::with_capacity(4);
+        if row > 0 {
+            neighbors.push((Cell::NORTH, i - self.width));
+        }
+        if row + 1 < self.height {
+            neighbors.push((Cell::SOUTH, i + self.width));
+        }
+        if col + 1 < self.width {
+            neighbors.push((Cell::EAST, i + 1));
+        }
+        if col > 0 {
+            neighbors.push((Cell::WEST, i - 1));
+        }
+        neighbors
+    }
+
+    /// Panics if a and b aren't next to each other.
+    fn link(&mut self, a: usize, b: usize) {
+        let (first, second) = (a.min(b), a.max(b));
+        if second == first + 1 && !second.is_multiple_of(self.width) {
+            self.set(first, EAST);
+        } else if second == first + self.width && second < self.cell_count() {
+            self.set(first, SOUTH);
+        } else {
+            panic!("cells {} and {} are not neighbors", a, b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_pcg::Lcg64Xsh32;
+
+    #[test]
+    fn test_same_as_grid() {
+        for &(width, height) in [(1, 1), (1, 6), (7, 1), (13, 9)].iter() {
+            let mut grid = Grid::new(width, height);
+            let mut packed = PackedGrid::new(width, height);
+
+            grid.binary_tree(Some(8));
+            packed.binary_tree(Some(8));
+            assert_eq!(grid, packed.to_grid());
+
+            grid.ellers(Some(8));
+            packed.ellers(Some(8));
+            assert_eq!(grid, packed.to_grid());
+
+            grid.recursive_backtracker(Some(8));
+            packed.recursive_backtracker(Some(8));
+            assert_eq!(grid, packed.to_grid());
+
+            assert_eq!(packed, PackedGrid::from_grid(&grid));
+        }
+    }
+
+    #[test]
+    fn test_maze_algorithms() {
+        let mut rng = Lcg64Xsh32::seed_from_u64(3);
+        let mut packed = PackedGrid::new(11, 6);
+        maze::wilsons(&mut packed, &mut rng);
+
+        let grid = packed.to_grid();
+        assert_eq!(Ok(()), grid.validate());
+        assert!(grid.is_connected());
+        assert_eq!(65, grid.edges().len());
+    }
+
+    #[test]
+    fn test_memory() {
+        // Four cells to a byte, against one cell to a byte for a Grid
+        let packed = PackedGrid::new(100, 99);
+        assert_eq!(2475, packed.as_bytes().len());
+        assert_eq!(
+            9900,
+            Grid::new(100, 99).cells.len() * std::mem::size_of::<Cell>()
+        );
+
+        let mut packed = PackedGrid::new(3, 1);
+        packed.link(1, 2);
+        assert_eq!(&[EAST << 2], packed.as_bytes());
+        assert_eq!(Cell::WEST, packed.cell(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "cells 2 and 3 are not neighbors")]
+    fn test_link_not_neighbors() {
+        // The end of one row and the start of the next
+        PackedGrid::new(3, 3).link(2, 3);
+    }
+}
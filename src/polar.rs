@@ -0,0 +1,327 @@
+use crate::draw::draw_line;
+use crate::maze::{self, Maze};
+use crate::Grid;
+
+use image::{ImageBuffer, RgbImage};
+
+use std::f64::consts::PI;
+
+/*
+PolarCell is a single cell in a PolarGrid.
+A cell only records its links inward and clockwise. Its links outward
+and counter-clockwise are recorded by the cells on the other side,
+so the two ends of a passage can never disagree.
+*/
+bitflags! {
+    #[derive(Default)]
+    pub struct PolarCell: u8 {
+        const INWARD =    0b01;
+        const CLOCKWISE = 0b10;
+    }
+}
+
+/// PolarDirection is which way one cell of a PolarGrid lies from another.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PolarDirection {
+    Inward,
+    Outward,
+    Clockwise,
+    CounterClockwise,
+}
+
+/*
+PolarGrid is a round maze made of concentric rings of cells.
+The center is a single cell, and each ring out is split into as many
+cells as keeps them roughly square, always a multiple of the number of
+cells in the ring inside it. Cells are numbered ring by ring from the
+center, going clockwise from the top within a ring.
+*/
+#[derive(PartialEq, Clone, Debug)]
+pub struct PolarGrid {
+    pub cells: Vec<PolarCell>,
+    rings: Vec<usize>,
+    // Index of the first cell in each ring
+    offsets: Vec<usize>,
+}
+
+impl PolarGrid {
+    /// Creates a grid with the given number of rings, counting the center as one.
+    pub fn new(rings: usize) -> PolarGrid {
+        let mut counts = Vec::with_capacity(rings);
+        for ring in 0..rings {
+            let count = if ring == 0 {
+                1
+            } else {
+                let previous = counts[ring - 1];
+                // A ring's circumference over the number of cells inside, in ring widths
+                let ratio = (2.0 * PI * ring as f64 / previous as f64).round() as usize;
+                previous * ratio.max(1)
+            };
+            counts.push(count);
+        }
+
+        let offsets = counts
+            .iter()
+            .scan(0, |offset, &count| {
+                let start = *offset;
+                *offset += count;
+                Some(start)
+            })
+            .collect();
+
+        PolarGrid {
+            cells: vec![PolarCell::default(); counts.iter().sum()],
+            rings: counts,
+            offsets,
+        }
+    }
+
+    /// Number of rings, counting the center as one.
+    pub fn ring_count(&self) -> usize {
+        self.rings.len()
+    }
+
+    /// Number of cells in ring.
+    pub fn cells_in_ring(&self, ring: usize) -> usize {
+        self.rings[ring]
+    }
+
+    /// position gives the ring of cell i and its place in that ring.
+    pub fn position(&self, i: usize) -> (usize, usize) {
+        let ring = self
+            .offsets
+            .iter()
+            .rposition(|&offset| offset <= i)
+            .unwrap();
+        (ring, i - self.offsets[ring])
+    }
+
+    fn index(&self, ring: usize, place: usize) -> usize {
+        self.offsets[ring] + place
+    }
+
+    /// links gives the indices of the cells that cell i has a passage to.
+    pub fn links(&self, i: usize) -> Vec<usize> {
+        self.neighbors(i)
+            .into_iter()
+            .filter(|&(direction, neighbor)| match direction {
+                PolarDirection::Inward => self.cells[i].contains(PolarCell::INWARD),
+                PolarDirection::Clockwise => self.cells[i].contains(PolarCell::CLOCKWISE),
+                PolarDirection::Outward => self.cells[neighbor].contains(PolarCell::INWARD),
+                PolarDirection::CounterClockwise => {
+                    self.cells[neighbor].contains(PolarCell::CLOCKWISE)
+                }
+            })
+            .map(|(_, neighbor)| neighbor)
+            .collect()
+    }
+
+    /// recursive_backtracker populates the grid with maze::recursive_backtracker.
+    pub fn recursive_backtracker(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::recursive_backtracker(self, &mut Grid::get_rng(seed));
+    }
+
+    /// aldous_broder populates the grid with maze::aldous_broder.
+    pub fn aldous_broder(&mut self, seed: Option<u64>) {
+        self.clear();
+        maze::aldous_broder(self, &mut Grid::get_rng(seed));
+    }
+
+    fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = PolarCell::default();
+        }
+    }
+
+    /// to_image renders the maze as a circle, with each ring cell_size pixels wide.
+    pub fn to_image(
+        &self,
+        cell_size: usize,
+        wall_size: usize,
+        background_pixel: image::Rgb<u8>,
+        wall_pixel: image::Rgb<u8>,
+    ) -> RgbImage {
+        let image_size = 2 * cell_size * self.ring_count() + wall_size;
+        let mut image =
+            ImageBuffer::from_pixel(image_size as u32, image_size as u32, background_pixel);
+
+        let center = image_size as f64 / 2.0;
+        let thickness = wall_size as f64;
+        // Angles go clockwise from the top, since y grows downwards
+        let point = |radius: f64, angle: f64| {
+            (center + radius * angle.sin(), center - radius * angle.cos())
+        };
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let (ring, place) = self.position(i);
+            if ring == 0 {
+                continue;
+            }
+
+            let inner = (ring * cell_size) as f64;
+            let outer = inner + cell_size as f64;
+            let step = 2.0 * PI / self.rings[ring] as f64;
+            let (start, end) = (step * place as f64, step * (place + 1) as f64);
+
+            if !cell.contains(PolarCell::INWARD) {
+                draw_arc(&mut image, &point, inner, start, end, thickness, wall_pixel);
+            }
+            if !cell.contains(PolarCell::CLOCKWISE) {
+                draw_line(
+                    &mut image,
+                    point(inner, end),
+                    point(outer, end),
+                    thickness,
+                    wall_pixel,
+                );
+            }
+        }
+
+        let outside = (self.ring_count() * cell_size) as f64;
+        draw_arc(
+            &mut image,
+            &point,
+            outside,
+            0.0,
+            2.0 * PI,
+            thickness,
+            wall_pixel,
+        );
+        image
+    }
+}
+
+// Draws an arc of the circle with the given radius as a series of short lines.
+fn draw_arc(
+    image: &mut RgbImage,
+    point: &dyn Fn(f64, f64) -> (f64, f64),
+    radius: f64,
+    start: f64,
+    end: f64,
+    thickness: f64,
+    pixel: image::Rgb<u8>,
+) {
+    let segments = ((end - start) * radius / 2.0).ceil().max(1.0) as usize;
+    let step = (end - start) / segments as f64;
+    for segment in 0..segments {
+        let from = point(radius, start + step * segment as f64);
+        let to = point(radius, start + step * (segment + 1) as f64);
+        draw_line(image, from, to, thickness, pixel);
+    }
+}
+
+impl Maze for PolarGrid {
+    type Dir = PolarDirection;
+
+    fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, i: usize) -> Vec<(PolarDirection, usize)> {
+        let (ring, place) = self.position(i);
+        let count = self.rings[ring];
+        let mut neighbors = Vec::new();
+
+        if ring > 0 {
+            let ratio = count / self.rings[ring - 1];
+            neighbors.push((PolarDirection::Inward, self.index(ring - 1, place / ratio)));
+            neighbors.push((
+                PolarDirection::Clockwise,
+                self.index(ring, (place + 1) % count),
+            ));
+            neighbors.push((
+                PolarDirection::CounterClockwise,
+                self.index(ring, (place + count - 1) % count),
+            ));
+        }
+
+        if ring + 1 < self.rings.len() {
+            let ratio = self.rings[ring + 1] / count;
+            for outer in place * ratio..(place + 1) * ratio {
+                neighbors.push((PolarDirection::Outward, self.index(ring + 1, outer)));
+            }
+        }
+
+        neighbors
+    }
+
+    /// Panics if a and b aren't next to each other.
+    fn link(&mut self, a: usize, b: usize) {
+        let direction = self
+            .neighbors(a)
+            .into_iter()
+            .find(|&(_, neighbor)| neighbor == b)
+            .map(|(direction, _)| direction)
+            .unwrap_or_else(|| panic!("cells {} and {} are not neighbors", a, b));
+
+        match direction {
+            PolarDirection::Inward => self.cells[a] |= PolarCell::INWARD,
+            PolarDirection::Outward => self.cells[b] |= PolarCell::INWARD,
+            PolarDirection::Clockwise => self.cells[a] |= PolarCell::CLOCKWISE,
+            PolarDirection::CounterClockwise => self.cells[b] |= PolarCell::CLOCKWISE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::maze::tests::is_spanning_tree;
+
+    #[test]
+    fn test_rings() {
+        let grid = PolarGrid::new(4);
+        assert_eq!(vec![1, 6, 12, 24], grid.rings);
+        assert_eq!(43, grid.cell_count());
+        assert_eq!((2, 3), grid.position(10));
+
+        // The center touches the whole first ring
+        assert_eq!(6, grid.neighbors(0).len());
+        assert_eq!(
+            vec![
+                (PolarDirection::Inward, 2),
+                (PolarDirection::Clockwise, 11),
+                (PolarDirection::CounterClockwise, 9),
+                (PolarDirection::Outward, 25),
+                (PolarDirection::Outward, 26),
+            ],
+            grid.neighbors(10)
+        );
+        // The last cell in a ring wraps around to the first
+        assert!(grid.neighbors(18).contains(&(PolarDirection::Clockwise, 7)));
+    }
+
+    #[test]
+    fn test_recursive_backtracker() {
+        let mut grid = PolarGrid::new(8);
+        grid.recursive_backtracker(None);
+        assert!(is_spanning_tree(&grid, |i, _, neighbor| grid
+            .links(i)
+            .contains(&neighbor)));
+    }
+
+    #[test]
+    fn test_aldous_broder() {
+        let mut grid = PolarGrid::new(8);
+        grid.aldous_broder(Some(3));
+        assert!(is_spanning_tree(&grid, |i, _, neighbor| grid
+            .links(i)
+            .contains(&neighbor)));
+    }
+
+    #[test]
+    fn test_to_image() {
+        let mut grid = PolarGrid::new(3);
+        grid.recursive_backtracker(Some(1));
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let image = grid.to_image(10, 2, background, wall);
+
+        assert_eq!((62, 62), image.dimensions());
+        // The outer wall crosses the top middle, the center cell is open
+        assert_eq!(wall, *image.get_pixel(31, 1));
+        assert_eq!(background, *image.get_pixel(31, 31));
+    }
+}
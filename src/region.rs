@@ -0,0 +1,504 @@
+use crate::{Algorithm, Cell, Direction, DisjointSet, Grid, Mask};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/*
+Rect is a rectangle of cells width across and height down,
+with its top left cell at (row, col).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    // Whether (row, col) is inside the rectangle.
+    fn contains(&self, row: usize, col: usize) -> bool {
+        (self.row..self.row + self.height).contains(&row)
+            && (self.col..self.col + self.width).contains(&col)
+    }
+
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.row < other.row + other.height
+            && other.row < self.row + self.height
+            && self.col < other.col + other.width
+            && other.col < self.col + self.width
+    }
+}
+
+impl Grid {
+    /// regenerate_region re-runs algorithm on just the width x height rectangle of cells
+    /// whose top left corner is top_left, leaving the rest of the maze as it was,
+    /// e.g. to reroll one part of a maze while editing it.
+    ///
+    /// Every passage inside the rectangle and across its edge is removed, and the
+    /// rectangle is generated as a maze of its own. Then the rest of the maze, which may
+    /// have been split into pieces that only met inside the rectangle, is joined back up
+    /// to it through one randomly chosen passage per piece. So if the maze was perfect,
+    /// it still is. Openings in the outer wall are kept.
+    ///
+    /// Panics if the rectangle doesn't fit in the grid, or if a passage tunnels into,
+    /// out of or beneath it, as weave makes.
+    pub fn regenerate_region(
+        &mut self,
+        seed: Option<u64>,
+        top_left: usize,
+        width: usize,
+        height: usize,
+        algorithm: Algorithm,
+    ) {
+        let rng = &mut Grid::get_rng(seed);
+        let (row, col) = self.coords(top_left);
+        let rect = Rect {
+            row,
+            col,
+            width,
+            height,
+        };
+        self.check_fits(&rect);
+        let in_region = |i: usize| rect.contains(i / self.width, i % self.width);
+        let region: Vec<usize> = (0..self.cells.len()).filter(|&i| in_region(i)).collect();
+
+        // The passages across the edge of the region, from inside it to outside
+        let mut edge = Vec::new();
+        for &i in region.iter() {
+            for (direction, neighbor) in self.neighbors_towards(i) {
+                assert!(
+                    !self.cells[i].contains(Cell::UNDER)
+                        && !self.cells[neighbor].contains(Cell::UNDER),
+                    "regenerate_region can't keep passages that tunnel into the region"
+                );
+                if !in_region(neighbor) {
+                    edge.push((i, direction, neighbor));
+                }
+            }
+        }
+
+        self.generate_part(&rect, &region, algorithm, rng);
+        for &(_, direction, neighbor) in edge.iter() {
+            self.cells[neighbor].remove(direction.opposite().to_flag());
+        }
+
+        self.join_across(rng, &mut edge);
+    }
+
+    /// subgrid copies the width x height rectangle of cells whose top left corner is
+    /// top_left into a Grid of its own, e.g. to render or study part of a big maze.
+    /// The mask, weights and annotations of those cells come along too, but the copy
+    /// doesn't wrap around.
+    ///
+    /// Passages out of the rectangle, including openings in the outer wall, are closed
+    /// off, so the cells around its edge have walls on the outside. Where the edge cuts
+    /// through a tunnel, or through the passage over one, the other passage is left as
+    /// an ordinary one through the cell.
+    ///
+    /// Panics if the rectangle doesn't fit in the grid.
+    pub fn subgrid(&self, top_left: usize, width: usize, height: usize) -> Grid {
+        let (row, col) = self.coords(top_left);
+        let rect = Rect {
+            row,
+            col,
+            width,
+            height,
+        };
+        self.check_fits(&rect);
+        let region: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| rect.contains(i / self.width, i % self.width))
+            .collect();
+
+        let mut part = self.empty_part(&rect, &region);
+        let mut outward = Vec::new();
+        for (j, &i) in region.iter().enumerate() {
+            part.cells[j] = self.cells[i];
+            for &direction in Direction::all().iter() {
+                if !part.in_bounds(j, direction) {
+                    outward.push((j, direction));
+                }
+            }
+        }
+        part.close_faces(outward);
+
+        if let Some(weights) = &self.weights {
+            part.weights = Some(region.iter().map(|&i| weights[i]).collect());
+        }
+        for (j, &i) in region.iter().enumerate() {
+            if let Some(&annotation) = self.annotations().get(&i) {
+                part.annotate(j, annotation);
+            }
+        }
+        part
+    }
+
+    /// generate_layered populates the maze with a different algorithm in each of regions,
+    /// such as rooms from RecursiveDivision in one part and the winding passages of
+    /// RecursiveBacktracker in another, each generated with its own seed. Cells that
+    /// aren't in any region are joined up like Kruskals would.
+    ///
+    /// Then the regions, and any cells between them, are stitched together into one
+    /// perfect maze: passages across their edges are opened in a random order, each
+    /// one only if it joins two parts that weren't connected yet. The order comes from
+    /// all of the regions' seeds, so with every seed given, the maze is the same every time.
+    ///
+    /// Panics if a region doesn't fit in the grid, or if two regions overlap.
+    pub fn generate_layered(&mut self, regions: &[(Rect, Algorithm, Option<u64>)]) {
+        for (n, (rect, _, _)) in regions.iter().enumerate() {
+            self.check_fits(rect);
+            if let Some(m) = regions[..n]
+                .iter()
+                .position(|(other, _, _)| rect.overlaps(other))
+            {
+                panic!("regions {} and {} overlap", m, n);
+            }
+        }
+
+        self.reset();
+        for &(rect, algorithm, seed) in regions.iter() {
+            let region: Vec<usize> = (0..self.cells.len())
+                .filter(|&i| rect.contains(i / self.width, i % self.width))
+                .collect();
+            self.generate_part(&rect, &region, algorithm, &mut Grid::get_rng(seed));
+        }
+
+        // Every pair of neighbors not already in the same part
+        let labels = self.component_labels();
+        let mut edges = Vec::new();
+        for i in 0..self.cells.len() {
+            for (direction, neighbor) in self.neighbors_towards(i) {
+                if (direction == Direction::South || direction == Direction::East)
+                    && labels[i] != labels[neighbor]
+                {
+                    edges.push((i, direction, neighbor));
+                }
+            }
+        }
+        let seeds: Option<Vec<u64>> = regions.iter().map(|&(_, _, seed)| seed).collect();
+        let stitch_seed = seeds.map(|seeds| {
+            seeds
+                .iter()
+                .fold(0x9e37_79b9_7f4a_7c15, |mixed: u64, &seed| {
+                    (mixed ^ seed).wrapping_mul(0x0100_0000_01b3)
+                })
+        });
+        self.join_across(&mut Grid::get_rng(stitch_seed), &mut edges);
+    }
+
+    // Panics unless rect is inside the grid.
+    fn check_fits(&self, rect: &Rect) {
+        assert!(
+            rect.col + rect.width <= self.width && rect.row + rect.height <= self.height,
+            "a {}x{} region at ({}, {}) doesn't fit in the {}x{} grid",
+            rect.width,
+            rect.height,
+            rect.row,
+            rect.col,
+            self.width,
+            self.height
+        );
+    }
+
+    // Generates a maze with algorithm in the cells of region, which are the cells of rect
+    // in order, replacing their passages. Openings in the outer wall are kept, but
+    // passages out of the region are left to the caller.
+    fn generate_part<R: Rng>(
+        &mut self,
+        rect: &Rect,
+        region: &[usize],
+        algorithm: Algorithm,
+        rng: &mut R,
+    ) {
+        let mut part = self.empty_part(rect, region);
+        part.generate_with_rng(algorithm, rng);
+
+        for (j, &i) in region.iter().enumerate() {
+            let openings = Direction::all()
+                .iter()
+                .filter(|&&direction| !self.in_bounds(i, direction))
+                .fold(Cell::default(), |openings, direction| {
+                    openings | direction.to_flag()
+                });
+            self.cells[i] = part.cells[j] | (self.cells[i] & openings);
+        }
+    }
+
+    // Walls up the side of each cell in faces that faces direction, closing any passage
+    // through it. Where that cuts through a passage on top of a tunnel, the tunnel comes
+    // up to take its place, and where it cuts through a tunnel, the cell is left on top.
+    // Either way, the cut passage is closed off on the far side of the cell too.
+    fn close_faces(&mut self, mut faces: Vec<(usize, Direction)>) {
+        while let Some((i, direction)) = faces.pop() {
+            let mut sides = vec![(i, direction)];
+            if self.valid_direction(i, direction) {
+                sides.push((self.neighbor(i, direction), direction.opposite()));
+            }
+            for (i, direction) in sides {
+                let cell = self.cells[i];
+                if !cell.contains(Cell::UNDER) {
+                    self.cells[i].remove(direction.to_flag());
+                    continue;
+                }
+                let across = match direction {
+                    Direction::North | Direction::South => Cell::NORTH | Cell::SOUTH,
+                    Direction::East | Direction::West => Cell::EAST | Cell::WEST,
+                };
+                self.cells[i] = if cell.contains(direction.to_flag()) {
+                    // The passage on top is cut
+                    (Cell::NORTH | Cell::SOUTH | Cell::EAST | Cell::WEST) - across
+                } else {
+                    cell - Cell::UNDER
+                };
+                faces.push((i, direction.opposite()));
+            }
+        }
+    }
+
+    // A Grid the size of rect with no passages, masked like the cells of region.
+    fn empty_part(&self, rect: &Rect, region: &[usize]) -> Grid {
+        match &self.mask {
+            Some(mask) => {
+                let mut part_mask = Mask::new(rect.width, rect.height);
+                for (j, &i) in region.iter().enumerate() {
+                    part_mask.set(j, mask.is_on(i));
+                }
+                Grid::with_mask(rect.width, rect.height, part_mask)
+            }
+            None => Grid::new(rect.width, rect.height),
+        }
+    }
+
+    // Links across edges, in a random order, between
+    // pieces of the maze that aren't connected yet.
+    pub(crate) fn join_across<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        edge: &mut [(usize, Direction, usize)],
+    ) {
+        let labels = self.component_labels();
+        let mut pieces = DisjointSet::new(self.cells.len());
+        edge.shuffle(rng);
+        for &(i, direction, neighbor) in edge.iter() {
+            if let (Some(inside), Some(outside)) = (labels[i], labels[neighbor]) {
+                if pieces.union(inside, outside) {
+                    self.link_cells(i, direction);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Annotation;
+
+    fn passages(grid: &Grid) -> usize {
+        grid.edges().len()
+    }
+
+    #[test]
+    fn test_regenerate_region() {
+        let mut grid = Grid::new(12, 10);
+        grid.recursive_backtracker(Some(1));
+        let before = grid.cells.clone();
+
+        for &algorithm in Algorithm::ALL.iter() {
+            grid.regenerate_region(Some(2), grid.index(3, 4), 5, 4, algorithm);
+            assert_eq!(Ok(()), grid.validate());
+            assert!(grid.is_connected(), "{:?}", algorithm);
+            assert_eq!(119, passages(&grid), "{:?}", algorithm);
+        }
+
+        // Outside the region, and away from its edge, nothing changes
+        for (i, (&cell, &was)) in grid.cells.iter().zip(before.iter()).enumerate() {
+            let (row, col) = grid.coords(i);
+            if !(2..=7).contains(&row) || !(3..=9).contains(&col) {
+                assert_eq!(was, cell);
+            }
+        }
+    }
+
+    #[test]
+    fn test_regenerate_region_edges() {
+        // The whole maze, and a region against the edges of a wrapping, masked grid
+        let mut grid = Grid::new(6, 6);
+        grid.regenerate_region(Some(3), 0, 6, 6, Algorithm::Wilsons);
+        assert!(grid.is_connected());
+        assert_eq!(35, passages(&grid));
+
+        let mut mask = Mask::new(8, 6);
+        mask.set(47, false);
+        let mut grid = Grid::with_mask(8, 6, mask);
+        grid.set_wrap_horizontal(true);
+        grid.prims(Some(4));
+        grid.carve_entrance_exit();
+        for seed in 0..20 {
+            grid.regenerate_region(Some(seed), grid.index(3, 5), 3, 3, Algorithm::HuntAndKill);
+            assert_eq!(Ok(()), grid.validate());
+            assert!(grid.is_connected());
+            assert_eq!(46, passages(&grid));
+        }
+        // The exit is still open
+        assert!(grid.cells[46].contains(Cell::SOUTH));
+    }
+
+    #[test]
+    fn test_subgrid() {
+        let mut grid = Grid::new(12, 10);
+        grid.wilsons(Some(1));
+        grid.set_weight(grid.index(3, 4), 7);
+        grid.annotate(grid.index(5, 6), Annotation::Goal);
+        let part = grid.subgrid(grid.index(2, 3), 6, 5);
+        assert_eq!((6, 5), (part.width, part.height));
+        assert_eq!(Ok(()), part.validate());
+
+        for j in 0..part.cells.len() {
+            let (row, col) = part.coords(j);
+            let cell = part.cells[j];
+            let original = grid.cells[grid.index(row + 2, col + 3)];
+            // Nothing leads out of the edges, and every other passage is still there
+            for direction in Direction::all().iter().map(Direction::to_flag) {
+                match part.try_neighbor(j, direction) {
+                    Some(_) => assert_eq!(original.contains(direction), cell.contains(direction)),
+                    None => assert!(!cell.contains(direction)),
+                }
+            }
+        }
+        assert_eq!(
+            Some(&Annotation::Goal),
+            part.annotations().get(&part.index(3, 3))
+        );
+        assert_eq!(7, part.weight(part.index(1, 1)));
+
+        // The whole maze is the same maze
+        let whole = grid.subgrid(0, 12, 10);
+        assert_eq!(grid.cells, whole.cells);
+        assert!(whole.is_perfect());
+    }
+
+    #[test]
+    fn test_subgrid_tunnels() {
+        // Every rectangle of a few sizes cuts through plenty of tunnels
+        let mut grid = Grid::new(10, 10);
+        for seed in 0..5 {
+            grid.weave(Some(seed), 1.0);
+            assert!(grid.cells.iter().any(|cell| cell.contains(Cell::UNDER)));
+            check_subgrids(&grid);
+        }
+    }
+
+    // Every subgrid of a few sizes is a valid maze.
+    fn check_subgrids(grid: &Grid) {
+        for &(width, height) in [(3, 3), (1, 4), (5, 1), (4, 2)].iter() {
+            for row in 0..=10 - height {
+                for col in 0..=10 - width {
+                    let part = grid.subgrid(grid.index(row, col), width, height);
+                    assert_eq!(
+                        Ok(()),
+                        part.validate(),
+                        "{}x{} at ({}, {})",
+                        width,
+                        height,
+                        row,
+                        col
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a 4x3 region at (7, 2) doesn't fit in the 10x9 grid")]
+    fn test_subgrid_too_big() {
+        Grid::new(10, 9).subgrid(72, 4, 3);
+    }
+
+    #[test]
+    fn test_generate_layered() {
+        let rooms = Rect {
+            row: 0,
+            col: 0,
+            width: 8,
+            height: 6,
+        };
+        let passages = Rect {
+            row: 0,
+            col: 10,
+            width: 10,
+            height: 12,
+        };
+        let rows = Rect {
+            row: 7,
+            col: 1,
+            width: 6,
+            height: 5,
+        };
+        let regions = [
+            (rooms, Algorithm::RecursiveDivision, Some(1)),
+            (passages, Algorithm::RecursiveBacktracker, Some(2)),
+            (rows, Algorithm::Ellers, Some(3)),
+        ];
+        let mut grid = Grid::new(20, 12);
+        grid.generate_layered(&regions);
+        assert_eq!(Ok(()), grid.validate());
+        assert!(grid.is_perfect());
+
+        // Inside each region, the maze is just what its algorithm makes
+        for &(rect, algorithm, seed) in regions.iter() {
+            let mut part = Grid::new(rect.width, rect.height);
+            part.generate(algorithm, seed);
+            for (j, &expected) in part.cells.iter().enumerate() {
+                let (row, col) = (rect.row + j / rect.width, rect.col + j % rect.width);
+                let inside = Direction::all()
+                    .iter()
+                    .map(Direction::to_flag)
+                    .filter(|&direction| part.try_neighbor(j, direction).is_some())
+                    .fold(Cell::default(), |inside, direction| inside | direction);
+                assert_eq!(expected, grid.cells[grid.index(row, col)] & inside);
+            }
+        }
+
+        // The same seeds make the same maze
+        let mut again = Grid::new(20, 12);
+        again.generate_layered(&regions);
+        assert_eq!(grid, again);
+
+        // No regions at all, or a masked grid
+        grid.generate_layered(&[]);
+        assert!(grid.is_perfect());
+        let mut mask = Mask::new(20, 12);
+        for &i in [0, 25, 66, 130, 171, 200, 239].iter() {
+            mask.set(i, false);
+        }
+        let mut grid = Grid::with_mask(20, 12, mask);
+        grid.generate_layered(&regions);
+        assert!(grid.is_perfect());
+    }
+
+    #[test]
+    #[should_panic(expected = "regions 0 and 2 overlap")]
+    fn test_generate_layered_overlap() {
+        let rect = |row, col| Rect {
+            row,
+            col,
+            width: 3,
+            height: 3,
+        };
+        let regions = [
+            (rect(0, 0), Algorithm::Prims, None),
+            (rect(0, 3), Algorithm::Prims, None),
+            (rect(2, 2), Algorithm::Prims, None),
+        ];
+        Grid::new(6, 6).generate_layered(&regions);
+    }
+
+    #[test]
+    #[should_panic(expected = "a 4x4 region at (2, 2) doesn't fit in the 5x5 grid")]
+    fn test_regenerate_region_too_big() {
+        let mut grid = Grid::new(5, 5);
+        grid.regenerate_region(None, 12, 4, 4, Algorithm::Prims);
+    }
+}
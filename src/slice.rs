@@ -0,0 +1,187 @@
+use crate::{Cell, Direction};
+
+use rand::Rng;
+
+/*
+SliceGrid generates mazes in a buffer of cells that belongs to the caller,
+laid out row by row like the cells of a Grid. It never allocates, so it works
+without the standard library, e.g. on embedded targets:
+
+let mut cells = [Cell::default(); 8 * 8];
+SliceGrid::new(&mut cells, 8, 8).sidewinder(&mut rng);
+
+Only the algorithms that need no memory beyond the cells themselves are here.
+With default features turned off, this is all there is of the crate besides Cell and Direction.
+*/
+pub struct SliceGrid<'a> {
+    cells: &'a mut [Cell],
+    width: usize,
+}
+
+impl<'a> SliceGrid<'a> {
+    /// new wraps cells as a width x height maze.
+    /// Panics if there aren't exactly width * height cells.
+    pub fn new(cells: &'a mut [Cell], width: usize, height: usize) -> SliceGrid<'a> {
+        assert_eq!(
+            width * height,
+            cells.len(),
+            "a {}x{} maze needs {} cells",
+            width,
+            height,
+            width * height
+        );
+        SliceGrid { cells, width }
+    }
+
+    /// binary_tree populates the maze like Grid::binary_tree, linking each cell
+    /// NORTH or EAST at random.
+    pub fn binary_tree<R: Rng>(&mut self, rng: &mut R) {
+        self.binary_tree_within(rng, Direction::North, Direction::East, |_| true);
+    }
+
+    /// sidewinder populates the maze like Grid::sidewinder.
+    pub fn sidewinder<R: Rng>(&mut self, rng: &mut R) {
+        self.sidewinder_within(rng, 0.5, |_| true);
+    }
+
+    // binary_tree, linking towards vertical or horizontal, and only between cells that are in_maze.
+    pub(crate) fn binary_tree_within<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        vertical: Direction,
+        horizontal: Direction,
+        in_maze: impl Fn(usize) -> bool,
+    ) {
+        self.clear();
+
+        for i in 0..self.cells.len() {
+            let vertical_valid = self.valid_direction(i, vertical, &in_maze);
+            let horizontal_valid = self.valid_direction(i, horizontal, &in_maze);
+
+            if vertical_valid && (!horizontal_valid || rng.gen()) {
+                self.link_cells(i, vertical);
+            } else if horizontal_valid {
+                self.link_cells(i, horizontal);
+            }
+        }
+    }
+
+    // sidewinder, carrying on EAST with probability horizontal_prob,
+    // and only linking between cells that are in_maze.
+    pub(crate) fn sidewinder_within<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        horizontal_prob: f64,
+        in_maze: impl Fn(usize) -> bool,
+    ) {
+        self.clear();
+
+        // We start on the Western cell on the second row - this is the first cell that can
+        // be a valid "NORTH"
+        let mut run_start = self.width;
+
+        for i in 0..self.cells.len() {
+            let north_valid = self.valid_direction(i, Direction::North, &in_maze);
+            let east_valid = self.valid_direction(i, Direction::East, &in_maze);
+
+            if north_valid && (!east_valid || rng.gen::<f64>() >= horizontal_prob) {
+                let mut chosen_cell = rng.gen_range(run_start, i + 1);
+                // Only possible with a mask: fall back to the current cell
+                if !self.valid_direction(chosen_cell, Direction::North, &in_maze) {
+                    chosen_cell = i;
+                }
+                self.link_cells(chosen_cell, Direction::North);
+                // Run resets
+                run_start = i + 1;
+            } else if east_valid {
+                self.link_cells(i, Direction::East);
+            } else {
+                run_start = i + 1;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = Cell::default();
+        }
+    }
+
+    // Whether cell i can be linked to a neighbor in direction, within the edges of the grid.
+    fn valid_direction(
+        &self,
+        i: usize,
+        direction: Direction,
+        in_maze: impl Fn(usize) -> bool,
+    ) -> bool {
+        let in_bounds = match direction {
+            Direction::North => i >= self.width,
+            Direction::South => i + self.width < self.cells.len(),
+            Direction::East => !(i + 1).is_multiple_of(self.width),
+            Direction::West => !i.is_multiple_of(self.width),
+        };
+        in_bounds && in_maze(i) && in_maze(self.neighbor(i, direction))
+    }
+
+    fn neighbor(&self, i: usize, direction: Direction) -> usize {
+        match direction {
+            Direction::North => i - self.width,
+            Direction::South => i + self.width,
+            Direction::East => i + 1,
+            Direction::West => i - 1,
+        }
+    }
+
+    fn link_cells(&mut self, i: usize, direction: Direction) {
+        let neighbor = self.neighbor(i, direction);
+        self.cells[i] |= direction.to_flag();
+        self.cells[neighbor] |= direction.opposite().to_flag();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_pcg::Lcg64Xsh32;
+
+    // Nothing here allocates, so these tests run with --no-default-features too.
+    // A perfect maze has two ends for each of its width * height - 1 passages
+    fn link_count(cells: &[Cell]) -> u32 {
+        cells.iter().map(|cell| cell.bits().count_ones()).sum()
+    }
+
+    #[test]
+    fn test_binary_tree() {
+        let mut rng = Lcg64Xsh32::seed_from_u64(1);
+        let mut cells = [Cell::default(); 7 * 5];
+        for _ in 0..100 {
+            SliceGrid::new(&mut cells, 7, 5).binary_tree(&mut rng);
+            assert_eq!(2 * 34, link_count(&cells));
+            // The top row is one long passage east, and the east column one long passage north
+            assert!(cells[..6].iter().all(|cell| cell.contains(Cell::EAST)));
+            assert!((1..5).all(|row| cells[row * 7 + 6].contains(Cell::NORTH)));
+        }
+    }
+
+    #[test]
+    fn test_sidewinder() {
+        let mut rng = Lcg64Xsh32::seed_from_u64(2);
+        let mut cells = [Cell::default(); 6 * 8];
+        for _ in 0..100 {
+            SliceGrid::new(&mut cells, 6, 8).sidewinder(&mut rng);
+            assert_eq!(2 * 47, link_count(&cells));
+            assert!(cells[..5].iter().all(|cell| cell.contains(Cell::EAST)));
+            assert!(cells.iter().all(|cell| !cell.is_empty()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a 4x4 maze needs 16 cells")]
+    fn test_wrong_size() {
+        let mut cells = [Cell::default(); 15];
+        SliceGrid::new(&mut cells, 4, 4);
+    }
+}
@@ -0,0 +1,719 @@
+use crate::Grid;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+impl Grid {
+    /// solve finds the shortest path from start to goal with a breadth-first search,
+    /// only moving between cells that have an open passage between them.
+    ///
+    /// The path includes both start and goal. Returns None if goal can't be
+    /// reached from start, or if either index is outside the grid.
+    pub fn solve(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        self.breadth_first_path(start, goal, |_| true)
+    }
+
+    /// solution_mask marks the cells on the shortest path from start to goal, as found
+    /// by solve, with one entry per cell. It's handy for renderers that go through
+    /// every cell anyway. If there's no path, no cell is marked.
+    pub fn solution_mask(&self, start: usize, goal: usize) -> Vec<bool> {
+        let mut on_path = vec![false; self.cells.len()];
+        for i in self.solve(start, goal).unwrap_or_default() {
+            on_path[i] = true;
+        }
+        on_path
+    }
+
+    // Finds the shortest path from start to goal like solve, but only through cells that are open.
+    fn breadth_first_path<F>(&self, start: usize, goal: usize, open: F) -> Option<Vec<usize>>
+    where
+        F: Fn(usize) -> bool,
+    {
+        if start >= self.cells.len() || goal >= self.cells.len() {
+            return None;
+        }
+
+        // The cell we came from when first reaching each cell
+        let mut previous = vec![None; self.cells.len()];
+        let mut visited = vec![false; self.cells.len()];
+        let mut queue = VecDeque::new();
+        visited[start] = true;
+        queue.push_back(start);
+
+        while let Some(current_cell) = queue.pop_front() {
+            if current_cell == goal {
+                let mut path = vec![goal];
+                let mut cell = goal;
+                while let Some(prev) = previous[cell] {
+                    path.push(prev);
+                    cell = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for next_cell in self.links(current_cell) {
+                if !visited[next_cell] && open(next_cell) {
+                    visited[next_cell] = true;
+                    previous[next_cell] = Some(current_cell);
+                    queue.push_back(next_cell);
+                }
+            }
+        }
+        None
+    }
+
+    /// solve_dead_end_fill finds a path from start to goal by filling in dead ends.
+    /// Any cell other than start and goal with only one passage leading out of it can't
+    /// be on the way, so it's filled, which can turn the cell before it into a dead end too.
+    /// Once there are no dead ends left, the path is traced through the cells that weren't filled.
+    ///
+    /// In a perfect maze, only the path itself is left, so the result is the same as solve.
+    /// In other mazes the loops are left too, and the shortest way through them is taken.
+    /// Like solve, the path includes both start and goal, and is None if goal
+    /// can't be reached from start or either index is outside the grid.
+    pub fn solve_dead_end_fill(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        if start >= self.cells.len() || goal >= self.cells.len() {
+            return None;
+        }
+
+        let links: Vec<Vec<usize>> = (0..self.cells.len())
+            .map(|i| self.links(i).collect())
+            .collect();
+        // How many unfilled cells each cell still has a passage to
+        let mut exits: Vec<usize> = links.iter().map(Vec::len).collect();
+        let mut filled = vec![false; self.cells.len()];
+
+        let mut dead_ends: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| exits[i] <= 1 && i != start && i != goal)
+            .collect();
+        while let Some(cell) = dead_ends.pop() {
+            filled[cell] = true;
+            for &next_cell in &links[cell] {
+                if filled[next_cell] {
+                    continue;
+                }
+                exits[next_cell] -= 1;
+                if exits[next_cell] == 1 && next_cell != start && next_cell != goal {
+                    dead_ends.push(next_cell);
+                }
+            }
+        }
+
+        self.breadth_first_path(start, goal, |cell| !filled[cell])
+    }
+
+    /// solve_astar finds the cheapest path from start to goal, where stepping into a cell
+    /// costs its weight. heuristic(cell, goal) estimates the cost left from cell to goal,
+    /// and must never overestimate it for the path to be the cheapest.
+    /// manhattan_distance is a good choice as long as no weight is less than 1.
+    ///
+    /// Like solve, the path includes both start and goal, and is None if goal
+    /// can't be reached from start or either index is outside the grid.
+    pub fn solve_astar<H>(&self, start: usize, goal: usize, heuristic: H) -> Option<Vec<usize>>
+    where
+        H: Fn(usize, usize) -> u32,
+    {
+        if start >= self.cells.len() || goal >= self.cells.len() {
+            return None;
+        }
+
+        // The cheapest known cost to reach each cell, and the cell we came from to get it
+        let mut costs: Vec<Option<u64>> = vec![None; self.cells.len()];
+        let mut previous = vec![None; self.cells.len()];
+        // Cells to explore, cheapest estimated total cost first
+        let mut open = BinaryHeap::new();
+        costs[start] = Some(0);
+        open.push(Reverse((u64::from(heuristic(start, goal)), start)));
+
+        while let Some(Reverse((estimate, current_cell))) = open.pop() {
+            let cost = costs[current_cell].unwrap();
+            if current_cell == goal {
+                let mut path = vec![goal];
+                let mut cell = goal;
+                while let Some(prev) = previous[cell] {
+                    path.push(prev);
+                    cell = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            // A cheaper way here has already been explored
+            if estimate > cost + u64::from(heuristic(current_cell, goal)) {
+                continue;
+            }
+
+            for next_cell in self.links(current_cell) {
+                let next_cost = cost + u64::from(self.weight(next_cell));
+                if costs[next_cell].is_none_or(|known| next_cost < known) {
+                    costs[next_cell] = Some(next_cost);
+                    previous[next_cell] = Some(current_cell);
+                    let estimate = next_cost + u64::from(heuristic(next_cell, goal));
+                    open.push(Reverse((estimate, next_cell)));
+                }
+            }
+        }
+        None
+    }
+
+    /// manhattan_distance counts the steps between cells a and b when moving
+    /// only horizontally and vertically, ignoring walls.
+    /// On a grid that wraps around, it goes the shorter way.
+    pub fn manhattan_distance(&self, a: usize, b: usize) -> u32 {
+        let (a_row, a_col) = self.coords(a);
+        let (b_row, b_col) = self.coords(b);
+        let mut down = a_row.max(b_row) - a_row.min(b_row);
+        if self.wraps_vertically() {
+            down = down.min(self.height - down);
+        }
+        let mut across = a_col.max(b_col) - a_col.min(b_col);
+        if self.wraps_horizontally() {
+            across = across.min(self.width - across);
+        }
+        (down + across) as u32
+    }
+
+    /// traffic counts, for every cell, how many of the shortest paths between the
+    /// start and goal of each of pairs pass through it, including their ends.
+    /// The busiest cells are the chokepoints of the maze, e.g. to color its hot corridors.
+    /// In a perfect maze there's only one path between any two cells, so the counts are
+    /// exact. Otherwise, each pair counts the path solve finds. Pairs with no path
+    /// between them aren't counted.
+    pub fn traffic(&self, pairs: &[(usize, usize)]) -> Vec<usize> {
+        let mut traffic = vec![0; self.cells.len()];
+        for &(start, goal) in pairs.iter() {
+            for i in self.solve(start, goal).unwrap_or_default() {
+                traffic[i] += 1;
+            }
+        }
+        traffic
+    }
+
+    /// distances finds how many steps away every cell is from root, following
+    /// open passages with a breadth-first search.
+    ///
+    /// Cells that can't be reached from root are None. If root is outside the grid,
+    /// no cell can be reached.
+    pub fn distances(&self, root: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.cells.len()];
+        if root >= self.cells.len() {
+            return distances;
+        }
+
+        let mut queue = VecDeque::new();
+        distances[root] = Some(0);
+        queue.push_back((root, 0));
+
+        while let Some((current_cell, distance)) = queue.pop_front() {
+            for next_cell in self.links(current_cell) {
+                if distances[next_cell].is_none() {
+                    distances[next_cell] = Some(distance + 1);
+                    queue.push_back((next_cell, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+
+    /// reachable_from is whether each cell can be reached from any of starts,
+    /// following open passages, e.g. from any of the entrances of a maze that has several.
+    /// With a mask or disconnected regions, it's which cells a player can actually get to.
+    ///
+    /// Starts outside the grid are ignored.
+    pub fn reachable_from(&self, starts: &[usize]) -> Vec<bool> {
+        let mut reachable = vec![false; self.cells.len()];
+        let mut queue = VecDeque::new();
+        for &start in starts.iter().filter(|&&start| start < self.cells.len()) {
+            if !reachable[start] {
+                reachable[start] = true;
+                queue.push_back(start);
+            }
+        }
+
+        while let Some(current_cell) = queue.pop_front() {
+            for next_cell in self.links(current_cell) {
+                if !reachable[next_cell] {
+                    reachable[next_cell] = true;
+                    queue.push_back(next_cell);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// is_connected is whether every cell that isn't masked off can be reached from every other,
+    /// following open passages. A maze with no cells is connected.
+    pub fn is_connected(&self) -> bool {
+        let first = match (0..self.cells.len()).find(|&i| !self.is_masked(i)) {
+            Some(first) => first,
+            None => return true,
+        };
+        let distances = self.distances(first);
+        (0..self.cells.len()).all(|i| self.is_masked(i) || distances[i].is_some())
+    }
+
+    /// is_perfect is whether the maze is a perfect maze: every cell that isn't masked off
+    /// can be reached from every other by exactly one path, with no loops. That's what
+    /// the generators make, so this checks a maze that's been loaded or edited is still one.
+    ///
+    /// Passages across the seams of a wrapping grid, and tunnels beneath cells, count like
+    /// any other. Openings in the outer wall lead nowhere, so they don't. A maze with no
+    /// cells is perfect.
+    pub fn is_perfect(&self) -> bool {
+        let cells = (0..self.cells.len()).filter(|&i| !self.is_masked(i));
+        // A tree of n cells has n - 1 passages, each counted here from both ends
+        let (count, links) = cells.fold((0, 0), |(count, links), i| {
+            (count + 1, links + self.links(i).count())
+        });
+        (count == 0 || links == 2 * (count - 1)) && self.is_connected()
+    }
+
+    /// components counts the separate regions of the maze: groups of cells that
+    /// are connected to each other by open passages, but not to any other cell.
+    /// Masked off cells aren't part of any region.
+    pub fn components(&self) -> usize {
+        self.component_labels()
+            .iter()
+            .flatten()
+            .max()
+            .map_or(0, |&last| last + 1)
+    }
+
+    // Numbers the regions found by components from 0, giving the region each cell is in.
+    // Masked off cells are None.
+    pub(crate) fn component_labels(&self) -> Vec<Option<usize>> {
+        let mut labels = vec![None; self.cells.len()];
+        let mut components = 0;
+
+        for first in 0..self.cells.len() {
+            if labels[first].is_some() || self.is_masked(first) {
+                continue;
+            }
+            labels[first] = Some(components);
+            let mut stack = vec![first];
+            while let Some(current_cell) = stack.pop() {
+                for next_cell in self.links(current_cell) {
+                    if labels[next_cell].is_none() {
+                        labels[next_cell] = Some(components);
+                        stack.push(next_cell);
+                    }
+                }
+            }
+            components += 1;
+        }
+        labels
+    }
+
+    // The reachable cell farthest from root, and its distance.
+    fn farthest_from(&self, root: usize) -> (usize, usize) {
+        self.distances(root)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(cell, distance)| distance.map(|distance| (cell, distance)))
+            .fold((root, 0), |farthest, candidate| {
+                if candidate.1 > farthest.1 {
+                    candidate
+                } else {
+                    farthest
+                }
+            })
+    }
+
+    /// longest_path finds the longest of all shortest paths in the maze.
+    /// First, find the cell A farthest from an arbitrary cell.
+    /// Then, find the cell B farthest from A. The path from A to B is the longest path.
+    ///
+    /// For a perfect maze this is the diameter of the tree. For a maze that isn't
+    /// fully connected, only the region containing the first cell that isn't masked
+    /// off is considered. A grid with every cell masked off has no path at all.
+    pub fn longest_path(&self) -> Vec<usize> {
+        if (0..self.cells.len()).all(|i| self.is_masked(i)) {
+            return Vec::new();
+        }
+
+        let (start, goal, _) = self.farthest_pair();
+        self.solve(start, goal).unwrap()
+    }
+
+    /// farthest_pair finds the two ends of longest_path and how many steps apart they
+    /// are, without building the path itself, e.g. to put the start and goal as far
+    /// apart as they can be. It takes two breadth-first searches, the same as
+    /// longest_path, and the first cell returned is where longest_path starts.
+    ///
+    /// Like longest_path, only the region containing the first cell that isn't masked off
+    /// is considered, so both cells are always part of the maze. A grid with no such cell
+    /// gives (0, 0, 0).
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(8, 8);
+    /// grid.prims(Some(3));
+    /// let (start, goal, distance) = grid.farthest_pair();
+    /// assert_eq!(Some(distance), grid.distances(start)[goal]);
+    /// ```
+    pub fn farthest_pair(&self) -> (usize, usize, usize) {
+        let first = match (0..self.cells.len()).find(|&i| !self.is_masked(i)) {
+            Some(first) => first,
+            None => return (0, 0, 0),
+        };
+
+        let (start, _) = self.farthest_from(first);
+        let (goal, distance) = self.farthest_from(start);
+        (start, goal, distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Mask;
+
+    #[test]
+    fn test_solve() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+
+        let path = grid.solve(0, 24).unwrap();
+        assert_eq!(9, path.len());
+        assert_eq!(Some(&0), path.first());
+        assert_eq!(Some(&24), path.last());
+
+        // Every step must go through an open passage
+        for step in path.windows(2) {
+            assert!(grid.links(step[0]).any(|cell| cell == step[1]));
+        }
+
+        assert_eq!(Some(vec![7]), grid.solve(7, 7));
+    }
+
+    #[test]
+    fn test_solution_mask() {
+        let mut grid = Grid::new(9, 7);
+        grid.wilsons(Some(5));
+
+        let path = grid.solve(3, 58).unwrap();
+        let on_path = grid.solution_mask(3, 58);
+        assert_eq!(63, on_path.len());
+        assert_eq!(path.len(), on_path.iter().filter(|&&on| on).count());
+        assert!(path.iter().all(|&i| on_path[i]));
+
+        let grid = Grid::new(5, 5);
+        assert_eq!(vec![false; 25], grid.solution_mask(0, 24));
+    }
+
+    #[test]
+    fn test_solve_unreachable() {
+        let grid = Grid::new(5, 5);
+        assert_eq!(None, grid.solve(0, 24));
+        assert_eq!(None, grid.solve(0, 25));
+        assert_eq!(None, grid.solve(25, 0));
+    }
+
+    #[test]
+    fn test_solve_dead_end_fill() {
+        let mut grid = Grid::new(12, 10);
+        for _ in 0..100 {
+            grid.recursive_backtracker(None);
+            // There's only one path through a perfect maze
+            assert_eq!(grid.solve(0, 119), grid.solve_dead_end_fill(0, 119));
+            assert_eq!(grid.solve(37, 82), grid.solve_dead_end_fill(37, 82));
+        }
+
+        for _ in 0..100 {
+            grid.recursive_backtracker(None);
+            grid.braid(None, 1.0);
+            let path = grid.solve_dead_end_fill(0, 119).unwrap();
+            assert_eq!(grid.solve(0, 119).unwrap().len(), path.len());
+        }
+
+        assert_eq!(Some(vec![7]), grid.solve_dead_end_fill(7, 7));
+        assert_eq!(None, grid.solve_dead_end_fill(0, 120));
+        assert_eq!(None, Grid::new(5, 5).solve_dead_end_fill(0, 24));
+    }
+
+    #[test]
+    fn test_solve_astar() {
+        let mut grid = Grid::new(3, 3);
+        grid.link_all();
+        let manhattan = |a, b| grid.manhattan_distance(a, b);
+
+        // With equal weights, A* finds a shortest path just like solve
+        let path = grid.solve_astar(1, 7, manhattan).unwrap();
+        assert_eq!(vec![1, 4, 7], path);
+
+        // Make the center expensive, so going around is cheaper
+        grid.set_weight(4, 10);
+        let manhattan = |a, b| grid.manhattan_distance(a, b);
+        let path = grid.solve_astar(1, 7, manhattan).unwrap();
+        assert_eq!(5, path.len());
+        assert!(!path.contains(&4));
+        assert_eq!(Some(&7), path.last());
+
+        assert_eq!(None, Grid::new(3, 3).solve_astar(0, 8, |_, _| 0));
+        assert_eq!(None, grid.solve_astar(0, 9, |_, _| 0));
+    }
+
+    #[test]
+    fn test_traffic() {
+        // Five columns joined by a corridor across the middle row
+        let mut grid = Grid::new(5, 5);
+        for i in 0..20 {
+            grid.link(i, i + 5).unwrap();
+        }
+        for i in 10..14 {
+            grid.link(i, i + 1).unwrap();
+        }
+
+        let border: Vec<usize> = (0..25)
+            .filter(|&i| {
+                let (row, col) = grid.coords(i);
+                row == 0 || row == 4 || col == 0 || col == 4
+            })
+            .collect();
+        let pairs: Vec<(usize, usize)> = border
+            .iter()
+            .flat_map(|&a| border.iter().filter(move |&&b| a < b).map(move |&b| (a, b)))
+            .collect();
+        let traffic = grid.traffic(&pairs);
+
+        // Every path crossing between the west and east halves goes through the center
+        let busiest = (0..25).max_by_key(|&i| traffic[i]).unwrap();
+        assert_eq!(12, busiest);
+        assert!((0..25).all(|i| i == 12 || traffic[i] < traffic[12]));
+        // Each cell on the border is at the end of a path to each of the 15 others
+        assert_eq!(15, traffic[0]);
+
+        // Unreachable pairs add nothing
+        assert_eq!(vec![0; 9], Grid::new(3, 3).traffic(&[(0, 8)]));
+        assert_eq!(vec![1, 0, 0, 0], Grid::new(2, 2).traffic(&[(0, 0)]));
+    }
+
+    #[test]
+    fn test_distances() {
+        // A single column is always a straight corridor
+        let mut grid = Grid::new(1, 10);
+        grid.binary_tree(None);
+
+        let distances = grid.distances(0);
+        for (i, distance) in distances.iter().enumerate() {
+            assert_eq!(Some(i), *distance);
+        }
+
+        let distances = grid.distances(4);
+        assert_eq!(Some(4), distances[0]);
+        assert_eq!(Some(5), distances[9]);
+    }
+
+    #[test]
+    fn test_distances_unreachable() {
+        let grid = Grid::new(3, 3);
+        let distances = grid.distances(4);
+        assert_eq!(Some(0), distances[4]);
+        assert_eq!(8, distances.iter().filter(|d| d.is_none()).count());
+        assert!(grid.distances(9).iter().all(|d| d.is_none()));
+    }
+
+    #[test]
+    fn test_reachable_from() {
+        // Three separate mazes side by side, with an entrance into the outer two
+        let mut third = Grid::new(3, 4);
+        third.recursive_backtracker(Some(5));
+        let mut grid = Grid::new(9, 4);
+        grid.cells = third
+            .cells
+            .chunks(3)
+            .flat_map(|row| row.iter().chain(row.iter()).chain(row.iter()))
+            .copied()
+            .collect();
+        assert_eq!(3, grid.components());
+
+        let reachable = grid.reachable_from(&[grid.index(3, 0), grid.index(0, 8)]);
+        for (i, &reachable) in reachable.iter().enumerate() {
+            let (_, col) = grid.coords(i);
+            assert_eq!(!(3..6).contains(&col), reachable, "cell {}", i);
+        }
+
+        // Both entrances in the same region, or none at all
+        let reachable = grid.reachable_from(&[1, 2, 100]);
+        assert_eq!(12, reachable.iter().filter(|&&reachable| reachable).count());
+        assert!(grid.reachable_from(&[]).iter().all(|&reachable| !reachable));
+    }
+
+    #[test]
+    fn test_is_perfect() {
+        let mut grid = Grid::new(8, 6);
+        assert!(!grid.is_perfect());
+        grid.kruskals(Some(2));
+        assert!(grid.is_perfect());
+        // Openings don't make loops
+        grid.carve_entrance_exit();
+        assert!(grid.is_perfect());
+
+        // A loop, or a wall that cuts the maze in two
+        grid.carve_room(0, 2, 2);
+        assert!(!grid.is_perfect());
+        grid.kruskals(Some(2));
+        let (a, b) = grid.edges()[0];
+        grid.unlink(a, b).unwrap();
+        assert!(!grid.is_perfect());
+
+        // Masked off cells are left out
+        let mut mask = Mask::new(8, 6);
+        for &i in [0, 1, 8, 20, 47].iter() {
+            mask.set(i, false);
+        }
+        let mut grid = Grid::with_mask(8, 6, mask);
+        assert!(!grid.is_perfect());
+        grid.wilsons(Some(4));
+        assert!(grid.is_perfect());
+
+        // Passages across the seams count, so joining up both ends of a row is a loop
+        let mut grid = Grid::new(5, 4);
+        grid.set_wrap_horizontal(true);
+        grid.set_wrap_vertical(true);
+        grid.recursive_backtracker(Some(5));
+        assert!(grid.is_perfect());
+        let mut row = Grid::new(3, 1);
+        row.set_wrap_horizontal(true);
+        row.link(0, 1).unwrap();
+        row.link(1, 2).unwrap();
+        assert!(row.is_perfect());
+        row.link(2, 0).unwrap();
+        assert!(!row.is_perfect());
+
+        assert!(Grid::new(0, 0).is_perfect());
+    }
+
+    #[test]
+    fn test_components() {
+        let mut grid = Grid::new(6, 4);
+        assert_eq!(24, grid.components());
+        assert!(!grid.is_connected());
+
+        grid.recursive_backtracker(Some(3));
+        assert_eq!(1, grid.components());
+        assert!(grid.is_connected());
+
+        // Side by side, two separate mazes make two isolated halves
+        let mut half = Grid::new(3, 4);
+        half.recursive_backtracker(Some(4));
+        grid.cells = half
+            .cells
+            .chunks(3)
+            .flat_map(|row| row.iter().chain(row.iter()))
+            .copied()
+            .collect();
+        assert_eq!(Ok(()), grid.validate());
+        assert_eq!(2, grid.components());
+        assert!(!grid.is_connected());
+
+        // Masked off cells are left out
+        let mut mask = Mask::new(3, 1);
+        mask.set(1, false);
+        let grid = Grid::with_mask(3, 1, mask);
+        assert_eq!(2, grid.components());
+        assert!(Grid::new(0, 0).is_connected());
+    }
+
+    #[test]
+    fn test_longest_path() {
+        // A single row is always a straight corridor
+        let mut grid = Grid::new(10, 1);
+        grid.binary_tree(None);
+
+        let mut path = grid.longest_path();
+        assert_eq!(10, path.len());
+        path.sort();
+        assert_eq!((0..10).collect::<Vec<usize>>(), path);
+
+        let mut grid = Grid::new(20, 20);
+        grid.recursive_backtracker(None);
+        let path = grid.longest_path();
+        let distances = grid.distances(path[0]);
+        assert_eq!(
+            path.len() - 1,
+            distances.iter().flatten().copied().max().unwrap()
+        );
+
+        assert!(Grid::new(0, 0).longest_path().is_empty());
+    }
+
+    #[test]
+    fn test_longest_path_masked() {
+        // With the first cell masked off, the search starts from the next one
+        let mut mask = Mask::new(6, 6);
+        mask.set(0, false);
+        let mut grid = Grid::with_mask(6, 6, mask);
+        grid.recursive_backtracker(Some(1));
+        assert!(grid.is_perfect());
+
+        let path = grid.longest_path();
+        assert!(path.len() > 1);
+        assert!(path.iter().all(|&i| !grid.is_masked(i)));
+        let distances = grid.distances(path[0]);
+        assert_eq!(
+            path.len() - 1,
+            distances.iter().flatten().copied().max().unwrap()
+        );
+        assert_eq!(path.len() - 1, grid.stats().longest_path);
+
+        let mut mask = Mask::new(2, 2);
+        for i in 0..4 {
+            mask.set(i, false);
+        }
+        assert!(Grid::with_mask(2, 2, mask).longest_path().is_empty());
+    }
+
+    #[test]
+    fn test_farthest_pair() {
+        // The two ends of a straight corridor
+        let mut grid = Grid::new(10, 1);
+        grid.binary_tree(Some(1));
+        assert_eq!((9, 0, 9), grid.farthest_pair());
+
+        // The same ends as longest_path, for perfect mazes and ones with loops
+        let mut grid = Grid::new(15, 12);
+        for seed in 0..10 {
+            grid.wilsons(Some(seed));
+            if seed % 2 == 1 {
+                grid.braid(Some(seed), 0.5);
+            }
+            let (start, goal, distance) = grid.farthest_pair();
+            let path = grid.longest_path();
+            assert_eq!(Some(&start), path.first());
+            assert_eq!(Some(&goal), path.last());
+            assert_eq!(path.len() - 1, distance);
+        }
+
+        assert_eq!((0, 0, 0), Grid::new(0, 0).farthest_pair());
+        assert_eq!((0, 0, 0), Grid::new(1, 1).farthest_pair());
+
+        // Neither end is ever a masked off cell
+        let mut mask = Mask::new(6, 6);
+        mask.set(0, false);
+        let mut grid = Grid::with_mask(6, 6, mask);
+        for seed in 0..10 {
+            grid.recursive_backtracker(Some(seed));
+            let (start, goal, distance) = grid.farthest_pair();
+            assert!(!grid.is_masked(start) && !grid.is_masked(goal));
+            assert!(distance > 0);
+            assert_eq!(Some(distance), grid.distances(start)[goal]);
+        }
+
+        // A straight corridor along the bottom row, below a masked off top row
+        let mut mask = Mask::new(5, 2);
+        for i in 0..5 {
+            mask.set(i, false);
+        }
+        let mut grid = Grid::with_mask(5, 2, mask.clone());
+        grid.binary_tree(Some(1));
+        assert_eq!((9, 5, 4), grid.farthest_pair());
+
+        for i in 5..10 {
+            mask.set(i, false);
+        }
+        assert_eq!((0, 0, 0), Grid::with_mask(5, 2, mask).farthest_pair());
+    }
+}
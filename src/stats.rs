@@ -0,0 +1,579 @@
+use crate::{Cell, Direction, DisjointSet, Grid};
+
+use std::fmt;
+
+/*
+MazeStats counts features of a maze that give it its texture,
+so that mazes from different algorithms can be compared.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MazeStats {
+    /// Cells with exactly one passage.
+    pub dead_ends: usize,
+    /// Cells with three or four passages.
+    pub junctions: usize,
+    /// Cells with two passages on opposite sides, going straight through.
+    pub straight_passages: usize,
+    /// Number of steps along the longest path, from longest_path.
+    pub longest_path: usize,
+}
+
+/*
+CellKind is the shape of the passages through a cell, from classify_cells.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellKind {
+    /// No passages at all, such as a masked off cell.
+    Empty,
+    /// One passage.
+    DeadEnd,
+    /// Two passages on opposite sides, going straight through.
+    Passage,
+    /// Two passages at right angles.
+    Corner,
+    /// Three passages.
+    Tee,
+    /// Passages on all four sides.
+    Cross,
+}
+
+impl Grid {
+    /// classify_cells gives the shape of each cell by the passages leading out of it.
+    /// Openings in the outer wall count as passages, and a cell that a passage
+    /// tunnels beneath only counts the one on top, so it's a Passage.
+    ///
+    /// ```
+    /// use minotaur::{CellKind, Grid};
+    ///
+    /// let mut grid = Grid::new(3, 1);
+    /// grid.link(0, 1).unwrap();
+    /// grid.link(1, 2).unwrap();
+    /// let kinds = grid.classify_cells();
+    /// assert_eq!(vec![CellKind::DeadEnd, CellKind::Passage, CellKind::DeadEnd], kinds);
+    /// ```
+    pub fn classify_cells(&self) -> Vec<CellKind> {
+        self.cells
+            .iter()
+            .map(|&cell| {
+                let cell = cell - Cell::UNDER;
+                match cell.bits().count_ones() {
+                    0 => CellKind::Empty,
+                    1 => CellKind::DeadEnd,
+                    2 if cell == Cell::NORTH | Cell::SOUTH || cell == Cell::EAST | Cell::WEST => {
+                        CellKind::Passage
+                    }
+                    2 => CellKind::Corner,
+                    3 => CellKind::Tee,
+                    _ => CellKind::Cross,
+                }
+            })
+            .collect()
+    }
+
+    /// stats counts the dead ends, junctions and straight passages in the maze,
+    /// and measures its longest path. Openings in the outer wall count as passages.
+    pub fn stats(&self) -> MazeStats {
+        let mut stats = MazeStats {
+            dead_ends: self.dead_ends().len(),
+            junctions: 0,
+            straight_passages: 0,
+            longest_path: self.longest_path().len().saturating_sub(1),
+        };
+
+        for kind in self.classify_cells() {
+            match kind {
+                CellKind::Passage => stats.straight_passages += 1,
+                CellKind::Tee | CellKind::Cross => stats.junctions += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// open_regions finds the rooms of the maze: open areas rather than corridors,
+    /// such as those made by carve_room or braid, with at least min_size cells each.
+    ///
+    /// A cell is part of an open area if it's a corner of a 2x2 square of cells with
+    /// no walls between them, and squares that share a cell are part of the same area.
+    /// A perfect maze has none, since going around a square would be a loop.
+    /// Each area's cells are in order, and the areas are in order of their first cell.
+    pub fn open_regions(&self, min_size: usize) -> Vec<Vec<usize>> {
+        let mut areas = DisjointSet::new(self.cells.len());
+        let mut in_area = vec![false; self.cells.len()];
+        for i in 0..self.cells.len() {
+            if let Some(square) = self.open_square(i) {
+                for &corner in square.iter() {
+                    areas.union(i, corner);
+                    in_area[corner] = true;
+                }
+            }
+        }
+
+        let mut regions: Vec<Vec<usize>> = Vec::new();
+        let mut region_of: Vec<Option<usize>> = vec![None; self.cells.len()];
+        for i in (0..self.cells.len()).filter(|&i| in_area[i]) {
+            let root = areas.find(i);
+            match region_of[root] {
+                Some(region) => regions[region].push(i),
+                None => {
+                    region_of[root] = Some(regions.len());
+                    regions.push(vec![i]);
+                }
+            }
+        }
+        regions.retain(|region| region.len() >= min_size);
+        regions
+    }
+
+    // The 2x2 square of cells with i at its top left, if there are no walls between them.
+    // Passages that tunnel under a cell don't open it up.
+    fn open_square(&self, i: usize) -> Option<[usize; 4]> {
+        if !self.valid_direction(i, Direction::East) || !self.valid_direction(i, Direction::South) {
+            return None;
+        }
+        let east = self.neighbor(i, Direction::East);
+        let south = self.neighbor(i, Direction::South);
+        if !self.valid_direction(east, Direction::South) {
+            return None;
+        }
+        let south_east = self.neighbor(east, Direction::South);
+
+        let square = [i, east, south, south_east];
+        let open = square
+            .iter()
+            .all(|&corner| !self.cells[corner].contains(Cell::UNDER))
+            && self.cells[i].contains(Cell::EAST | Cell::SOUTH)
+            && self.cells[south_east].contains(Cell::NORTH | Cell::WEST);
+        if open {
+            Some(square)
+        } else {
+            None
+        }
+    }
+
+    /// wall_count counts the walls still standing between neighboring cells.
+    /// The outer wall and the walls around masked off cells aren't counted.
+    pub fn wall_count(&self) -> usize {
+        let (walls, open) = self.internal_walls();
+        walls - open
+    }
+
+    /// open_ratio is the fraction of the walls between neighboring cells that have been
+    /// removed, from 0.0 for a grid with no passages to 1.0 for one big room.
+    ///
+    /// A perfect maze of n cells always has n - 1 passages, so this only depends on its
+    /// size, a little over 0.5 for a large square maze. Braiding or adding loops makes it
+    /// higher, which makes it a quick way to compare how open mazes are.
+    /// A grid with no walls between its cells has an open_ratio of 0.0.
+    pub fn open_ratio(&self) -> f64 {
+        match self.internal_walls() {
+            (0, _) => 0.0,
+            (walls, open) => open as f64 / walls as f64,
+        }
+    }
+
+    // How many walls there could be between neighboring cells, and how many of those
+    // have been removed. Each wall is counted from the cell to its NORTH or WEST.
+    fn internal_walls(&self) -> (usize, usize) {
+        let (mut walls, mut open) = (0, 0);
+        for i in 0..self.cells.len() {
+            for (direction, _) in self.neighbors_towards(i) {
+                if direction == Direction::South || direction == Direction::East {
+                    walls += 1;
+                    if self.cells[i].contains(direction.to_flag()) {
+                        open += 1;
+                    }
+                }
+            }
+        }
+        (walls, open)
+    }
+
+    /// run_lengths measures the straight corridors of the maze, giving histograms of
+    /// how long its horizontal and vertical runs are: lines of cells joined by passages
+    /// EAST, and lines joined by passages SOUTH. Element n of each is the number of runs
+    /// n cells long, so a cell with no passage either side is a run of 1 on its own,
+    /// and element 0 is always 0. Both are as long as the longest run + 1.
+    ///
+    /// Long horizontal runs and short vertical ones are what make a sidewinder maze look
+    /// the way it does, while a recursive_backtracker maze has short runs both ways.
+    /// Openings in the outer wall, passages across the seams of a wrapping grid and
+    /// masked off cells aren't counted.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(4, 2);
+    /// grid.link(0, 1).unwrap();
+    /// grid.link(1, 2).unwrap();
+    /// grid.link(2, 6).unwrap();
+    /// let (horizontal, vertical) = grid.run_lengths();
+    /// assert_eq!(vec![0, 5, 0, 1], horizontal);
+    /// assert_eq!(vec![0, 6, 1], vertical);
+    /// ```
+    pub fn run_lengths(&self) -> (Vec<usize>, Vec<usize>) {
+        (self.runs(Direction::East), self.runs(Direction::South))
+    }
+
+    // Histogram of the lengths of lines of cells joined by passages in direction.
+    fn runs(&self, direction: Direction) -> Vec<usize> {
+        let linked = |i: usize, direction: Direction| {
+            self.cells[i].contains(direction.to_flag())
+                && self.in_bounds(i, direction)
+                && !self.crosses_seam(i, direction)
+        };
+        let mut histogram = vec![0];
+        for i in 0..self.cells.len() {
+            // Each run is counted from its first cell
+            if self.is_masked(i) || linked(i, direction.opposite()) {
+                continue;
+            }
+            let (mut end, mut length) = (i, 1);
+            while linked(end, direction) {
+                end = self.neighbor(end, direction);
+                length += 1;
+            }
+            if histogram.len() <= length {
+                histogram.resize(length + 1, 0);
+            }
+            histogram[length] += 1;
+        }
+        histogram
+    }
+
+    /// difficulty scores how hard it is to find the way from start to goal,
+    /// from 0.0 for a maze with no wrong turns to take up to 1.0. It's made of four
+    /// fractions, each between 0 and 1:
+    ///
+    /// - choices: junctions on the solution / cells on the solution,
+    ///   how often there's a decision to make along the way
+    /// - branching: junctions off the solution / cells off the solution,
+    ///   how much the wrong turns lead on to further wrong turns
+    /// - dead ends: dead ends other than start and goal / all cells in the maze
+    /// - winding: 1 - manhattan_distance(start, goal) / steps on the solution,
+    ///   how far the solution strays from a straight line
+    ///
+    /// difficulty = (choices + branching + dead ends) / 3 * (1 + winding) / 2
+    ///
+    /// so a long, winding solution makes the wrong turns count for more, but without
+    /// any wrong turns, even the longest corridor scores 0.
+    ///
+    /// A junction is a cell with passages to three or four others. Openings in the
+    /// outer wall don't count. Returns f64::INFINITY if goal can't be reached from start,
+    /// so unsolvable mazes sort after all the others.
+    pub fn difficulty(&self, start: usize, goal: usize) -> f64 {
+        let path = match self.solve(start, goal) {
+            Some(path) => path,
+            None => return f64::INFINITY,
+        };
+        let mut on_path = vec![false; self.cells.len()];
+        for &i in path.iter() {
+            on_path[i] = true;
+        }
+
+        let steps = path.len() - 1;
+        let winding = if steps == 0 {
+            0.0
+        } else {
+            1.0 - f64::from(self.manhattan_distance(start, goal)) / steps as f64
+        };
+
+        let (mut path_junctions, mut side_junctions, mut side_cells, mut dead_ends) = (0, 0, 0, 0);
+        let mut cells = 0;
+        for i in (0..self.cells.len()).filter(|&i| !self.is_masked(i)) {
+            cells += 1;
+            let links = self.links(i).count();
+            if on_path[i] {
+                // Leaving the goal isn't a decision
+                if links > 2 && i != goal {
+                    path_junctions += 1;
+                }
+            } else {
+                side_cells += 1;
+                if links > 2 {
+                    side_junctions += 1;
+                }
+            }
+            if links == 1 && i != start && i != goal {
+                dead_ends += 1;
+            }
+        }
+
+        let fraction = |count: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            }
+        };
+        let choices = fraction(path_junctions, path.len());
+        let branching = fraction(side_junctions, side_cells);
+        let dead_ends = fraction(dead_ends, cells);
+        (choices + branching + dead_ends) / 3.0 * (1.0 + winding) / 2.0
+    }
+
+    /// solution_turns counts how many times the shortest path from start to goal,
+    /// as found by solve, changes direction. A straight corridor has no turns at all,
+    /// so unlike its length, this measures how winding the solution is.
+    /// Going straight through a tunnel isn't a turn. Returns 0 if there's no path.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// // An L: east along the top row, then south down the last column
+    /// let mut grid = Grid::new(3, 3);
+    /// grid.link(0, 1).unwrap();
+    /// grid.link(1, 2).unwrap();
+    /// grid.link(2, 5).unwrap();
+    /// grid.link(5, 8).unwrap();
+    /// assert_eq!(1, grid.solution_turns(0, 8));
+    /// assert_eq!(0, grid.solution_turns(0, 2));
+    /// ```
+    pub fn solution_turns(&self, start: usize, goal: usize) -> usize {
+        let path = match self.solve(start, goal) {
+            Some(path) => path,
+            None => return 0,
+        };
+        let directions: Vec<Direction> = path
+            .windows(2)
+            .map(|step| self.step_direction(step[0], step[1]))
+            .collect();
+        directions
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count()
+    }
+
+    // Which way the passage from cell from to cell to leaves from.
+    // Panics if there's no passage between them.
+    fn step_direction(&self, from: usize, to: usize) -> Direction {
+        Direction::all()
+            .iter()
+            .copied()
+            .find(|&direction| {
+                self.cells[from].contains(direction.to_flag())
+                    && self.valid_direction(from, direction)
+                    && self.passage_end(from, direction) == to
+            })
+            .unwrap_or_else(|| panic!("cells {} and {} aren't linked", from, to))
+    }
+}
+
+impl fmt::Display for MazeStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Dead ends: {}", self.dead_ends)?;
+        writeln!(f, "Junctions: {}", self.junctions)?;
+        writeln!(f, "Straight passages: {}", self.straight_passages)?;
+        writeln!(f, "Longest path: {}", self.longest_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+
+        let expected = MazeStats {
+            dead_ends: 7,
+            junctions: 5,
+            straight_passages: 7,
+            longest_path: 14,
+        };
+        assert_eq!(expected, grid.stats());
+    }
+
+    #[test]
+    fn test_open_regions() {
+        let mut grid = Grid::new(12, 9);
+        grid.recursive_backtracker(Some(8));
+        assert!(grid.open_regions(0).is_empty());
+
+        grid.carve_room(grid.index(2, 3), 4, 3);
+        grid.carve_room(grid.index(6, 8), 2, 2);
+        let room: Vec<usize> = (2..5)
+            .flat_map(|row| (3..7).map(move |col| row * 12 + col))
+            .collect();
+        let small_room = vec![80, 81, 92, 93];
+        assert_eq!(vec![room.clone(), small_room], grid.open_regions(4));
+        assert_eq!(vec![room], grid.open_regions(5));
+
+        // A long corridor isn't a room
+        let mut grid = Grid::new(6, 1);
+        grid.carve_room(0, 6, 1);
+        assert!(grid.open_regions(0).is_empty());
+    }
+
+    #[test]
+    fn test_open_ratio() {
+        // 8 rows of 9 walls between neighbors in a row, and 7 rows of 10 between rows
+        let mut grid = Grid::new(10, 8);
+        assert_eq!(142, grid.wall_count());
+        assert_eq!(0.0, grid.open_ratio());
+
+        // A perfect maze removes one wall for each cell but the first
+        grid.wilsons(Some(6));
+        assert_eq!(142 - 79, grid.wall_count());
+        assert_eq!(79.0 / 142.0, grid.open_ratio());
+
+        grid.braid(Some(6), 1.0);
+        assert!(grid.wall_count() < 142 - 79);
+        assert!(grid.open_ratio() > 79.0 / 142.0);
+
+        grid.carve_room(0, 10, 8);
+        assert_eq!(0, grid.wall_count());
+        assert_eq!(1.0, grid.open_ratio());
+
+        assert_eq!(0.0, Grid::new(1, 1).open_ratio());
+    }
+
+    #[test]
+    fn test_run_lengths() {
+        // Cells in all the runs, and how long the runs are on average
+        let cells = |histogram: &[usize]| -> usize {
+            histogram
+                .iter()
+                .enumerate()
+                .map(|(n, count)| n * count)
+                .sum()
+        };
+        let average =
+            |histogram: &[usize]| cells(histogram) as f64 / histogram.iter().sum::<usize>() as f64;
+
+        let mut grid = Grid::new(20, 20);
+        grid.sidewinder(Some(7));
+        let (sidewinder, _) = grid.run_lengths();
+        // The top row is always one long run
+        assert_eq!(1, sidewinder[20]);
+        grid.recursive_backtracker(Some(7));
+        let (backtracker, vertical) = grid.run_lengths();
+        assert!(average(&sidewinder) > average(&backtracker));
+
+        // Every cell is in exactly one run each way
+        assert_eq!(400, cells(&backtracker));
+        assert_eq!(400, cells(&vertical));
+
+        // Nothing but single cells, even with openings and passages across the seam
+        let mut grid = Grid::new(3, 3);
+        grid.set_wrap_horizontal(true);
+        grid.link(2, 0).unwrap();
+        grid.carve_entrance_exit();
+        assert_eq!((vec![0, 9], vec![0, 9]), grid.run_lengths());
+        assert_eq!((vec![0], vec![0]), Grid::new(0, 0).run_lengths());
+    }
+
+    #[test]
+    fn test_classify_cells() {
+        let mut grid = Grid::new(12, 9);
+        grid.kruskals(Some(31));
+        grid.carve_room(grid.index(2, 2), 3, 3);
+
+        let kinds = grid.classify_cells();
+        let count = |kind| kinds.iter().filter(|&&k| k == kind).count();
+        let kinds_of = [
+            CellKind::Empty,
+            CellKind::DeadEnd,
+            CellKind::Passage,
+            CellKind::Corner,
+            CellKind::Tee,
+            CellKind::Cross,
+        ];
+        assert_eq!(108, kinds_of.iter().map(|&kind| count(kind)).sum::<usize>());
+        assert_eq!(0, count(CellKind::Empty));
+        // The middle of the room is open on all sides
+        assert_eq!(CellKind::Cross, kinds[grid.index(3, 3)]);
+        assert!(count(CellKind::Corner) > 0);
+
+        let stats = grid.stats();
+        assert_eq!(stats.dead_ends, count(CellKind::DeadEnd));
+        assert_eq!(stats.straight_passages, count(CellKind::Passage));
+        assert_eq!(
+            stats.junctions,
+            count(CellKind::Tee) + count(CellKind::Cross)
+        );
+
+        grid.reset();
+        assert!(grid
+            .classify_cells()
+            .iter()
+            .all(|&kind| kind == CellKind::Empty));
+    }
+
+    #[test]
+    fn test_difficulty() {
+        // A straight corridor has nothing to get wrong
+        let mut grid = Grid::new(10, 1);
+        grid.binary_tree(None);
+        assert_eq!(0.0, grid.difficulty(0, 9));
+
+        // Winding back and forth doesn't make it any harder without any choices
+        let mut grid = Grid::new(4, 4);
+        for row in 0..4 {
+            for col in 0..3 {
+                let i = grid.index(row, col);
+                grid.link(i, i + 1).unwrap();
+            }
+            let end = if row.is_multiple_of(2) { 3 } else { 0 };
+            if row < 3 {
+                let i = grid.index(row, end);
+                grid.link(i, i + 4).unwrap();
+            }
+        }
+        assert_eq!(0.0, grid.difficulty(0, 12));
+
+        let mut grid = Grid::new(20, 20);
+        for _ in 0..20 {
+            grid.recursive_backtracker(None);
+            let difficulty = grid.difficulty(0, 399);
+            assert!(difficulty > 0.04 && difficulty < 1.0, "{}", difficulty);
+        }
+
+        // Not being able to get there at all is the hardest
+        grid.reset();
+        assert_eq!(f64::INFINITY, grid.difficulty(0, 399));
+    }
+
+    #[test]
+    fn test_solution_turns() {
+        // A straight corridor along the middle row
+        let mut grid = Grid::new(5, 3);
+        for i in 5..9 {
+            grid.link(i, i + 1).unwrap();
+        }
+        assert_eq!(0, grid.solution_turns(5, 9));
+        assert_eq!(0, grid.solution_turns(9, 5));
+        assert_eq!(0, grid.solution_turns(7, 7));
+
+        // An L, down from the top row and then east
+        grid.link(0, 5).unwrap();
+        assert_eq!(1, grid.solution_turns(0, 9));
+        // No path
+        assert_eq!(0, grid.solution_turns(0, 14));
+
+        // A zigzag down the diagonal turns at every step
+        let mut grid = Grid::new(4, 4);
+        for &(a, b) in [(0, 1), (1, 5), (5, 6), (6, 10), (10, 11), (11, 15)].iter() {
+            grid.link(a, b).unwrap();
+        }
+        assert_eq!(5, grid.solution_turns(0, 15));
+    }
+
+    #[test]
+    fn test_stats_corridor() {
+        // A single row is always a straight corridor
+        let mut grid = Grid::new(6, 1);
+        grid.binary_tree(None);
+
+        let expected = MazeStats {
+            dead_ends: 2,
+            junctions: 0,
+            straight_passages: 4,
+            longest_path: 5,
+        };
+        assert_eq!(expected, grid.stats());
+    }
+}
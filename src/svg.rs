@@ -0,0 +1,105 @@
+use crate::{Cell, Grid};
+
+use std::fmt::Write;
+
+impl Grid {
+    /// to_svg renders the maze as a standalone SVG document, drawing each wall as a <line>.
+    ///
+    /// Every wall is drawn exactly once: each cell draws its SOUTH and EAST walls,
+    /// and only cells on the outside of the maze draw their NORTH and WEST walls.
+    /// The document is cell_size * width + wall_width wide, so that the border walls
+    /// aren't cut off.
+    pub fn to_svg(&self, cell_size: usize, wall_width: f64, wall_color: &str) -> String {
+        let image_width = (cell_size * self.width) as f64 + wall_width;
+        let image_height = (cell_size * self.height) as f64 + wall_width;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            image_width, image_height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<g stroke="{}" stroke-width="{}" stroke-linecap="square" transform="translate({2} {2})">"#,
+            wall_color,
+            wall_width,
+            wall_width / 2.0
+        )
+        .unwrap();
+
+        let mut line = |x1: usize, y1: usize, x2: usize, y2: usize| {
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                x1 * cell_size,
+                y1 * cell_size,
+                x2 * cell_size,
+                y2 * cell_size
+            )
+            .unwrap();
+        };
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            if self.is_masked(i) {
+                continue;
+            }
+            let (y, x) = self.coords(i);
+
+            // NORTH and WEST walls belong to the neighbor, unless there isn't one
+            if !cell.contains(Cell::NORTH) && (y == 0 || self.is_masked(i - self.width)) {
+                line(x, y, x + 1, y);
+            }
+            if !cell.contains(Cell::WEST) && (x == 0 || self.is_masked(i - 1)) {
+                line(x, y, x, y + 1);
+            }
+            if !cell.contains(Cell::SOUTH) {
+                line(x, y + 1, x + 1, y + 1);
+            }
+            if !cell.contains(Cell::EAST) {
+                line(x + 1, y, x + 1, y + 1);
+            }
+        }
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let svg = Grid::new(1, 1).to_svg(10, 2.0, "black");
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="12" height="12""#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(4, svg.matches("<line").count());
+        assert!(svg.contains(r#"<line x1="0" y1="10" x2="10" y2="10"/>"#));
+
+        // Every wall of a perfect maze is drawn once: the border, plus the internal walls
+        // that weren't removed to link the cells together.
+        let (width, height) = (12, 7);
+        let mut grid = Grid::new(width, height);
+        grid.recursive_backtracker(None);
+        let border = 2 * width + 2 * height;
+        let internal = width * (height - 1) + height * (width - 1);
+        let links = width * height - 1;
+        let svg = grid.to_svg(10, 1.0, "#000000");
+        assert_eq!(border + internal - links, svg.matches("<line").count());
+    }
+
+    #[test]
+    fn test_to_svg_masked() {
+        let mut mask = crate::Mask::new(2, 1);
+        mask.set(0, false);
+        let svg = Grid::with_mask(2, 1, mask).to_svg(10, 1.0, "black");
+        assert_eq!(4, svg.matches("<line").count());
+        assert!(svg.contains(r#"<line x1="10" y1="0" x2="10" y2="10"/>"#));
+    }
+}
@@ -0,0 +1,712 @@
+use crate::draw;
+use crate::{Cell, Grid, ParseError};
+
+// Box drawing characters for a junction, indexed by which of its arms have a wall:
+// 1 is up, 2 is right, 4 is down and 8 is left.
+const JUNCTIONS: [char; 16] = [
+    ' ', '╵', '╶', '└', '╷', '│', '┌', '├', '╴', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+];
+
+// Quadrant block characters, indexed by which quarters are filled:
+// 1 is top left, 2 is top right, 4 is bottom left and 8 is bottom right.
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/*
+AsciiCharset is the characters Grid::render_ascii draws a maze with. Each horizontal
+wall is horizontal_wall repeated cell_width times, and each cell is cell_width spaces.
+
+The default is the style of Display:
+
++---+---+
+|       |
++---+---+
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiCharset {
+    pub horizontal_wall: char,
+    pub vertical_wall: char,
+    pub corner: char,
+    pub cell_width: usize,
+}
+
+impl Default for AsciiCharset {
+    fn default() -> AsciiCharset {
+        AsciiCharset {
+            horizontal_wall: '-',
+            vertical_wall: '|',
+            corner: '+',
+            cell_width: 3,
+        }
+    }
+}
+
+impl Grid {
+    /// render_ascii renders the maze as ASCII art like Display, but drawn with charset,
+    /// e.g. with narrower cells for a denser maze, or other characters for the walls.
+    ///
+    /// ```
+    /// use minotaur::{AsciiCharset, Grid};
+    ///
+    /// let grid = Grid::new(2, 1);
+    /// let charset = AsciiCharset {
+    ///     horizontal_wall: '=',
+    ///     vertical_wall: '#',
+    ///     corner: '#',
+    ///     cell_width: 1,
+    /// };
+    /// assert_eq!("#=#=#\n# # #\n#=#=#\n", grid.render_ascii(&charset));
+    /// assert_eq!(grid.to_string(), grid.render_ascii(&AsciiCharset::default()));
+    /// ```
+    pub fn render_ascii(&self, charset: &AsciiCharset) -> String {
+        let blank = " ".repeat(charset.cell_width);
+        self.to_ascii(charset, |_| &blank)
+    }
+
+    /// render_ascii_with_labels renders the maze as ASCII art like Display, with the
+    /// inside of each cell filled in with its label, e.g. its distance from somewhere or
+    /// its index, for teaching or debugging. Each label is centered in its cell. Labels
+    /// longer than the 3 characters a cell holds lose characters from both ends,
+    /// keeping their middle. Masked off cells are left blank.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(2, 1);
+    /// grid.link(0, 1).unwrap();
+    /// let labels = vec!["A".to_string(), "BCDEF".to_string()];
+    /// assert_eq!("+---+---+\n| A  CDE|\n+---+---+\n", grid.render_ascii_with_labels(&labels));
+    /// ```
+    ///
+    /// Panics unless there's exactly one label for each cell.
+    pub fn render_ascii_with_labels(&self, labels: &[String]) -> String {
+        assert_eq!(
+            self.cells.len(),
+            labels.len(),
+            "expected {} labels, one for each cell, but found {}",
+            self.cells.len(),
+            labels.len()
+        );
+        let charset = AsciiCharset::default();
+        let bodies: Vec<String> = labels
+            .iter()
+            .map(|label| fit_label(label, charset.cell_width))
+            .collect();
+        self.to_ascii(&charset, |i| &bodies[i])
+    }
+
+    // Whether there is a wall along the north side of (row, col).
+    // row may be height, for the south border.
+    fn north_wall(&self, row: usize, col: usize) -> bool {
+        let above = self.in_maze(row.wrapping_sub(1), col);
+        let here = self.in_maze(row, col);
+        // Checking both sides also finds openings in the border
+        let open = (here && self.cells[row * self.width + col].contains(Cell::NORTH))
+            || (above && self.cells[(row - 1) * self.width + col].contains(Cell::SOUTH));
+        (above || here) && !open
+    }
+
+    // Whether there is a wall along the west side of (row, col).
+    // col may be width, for the east border.
+    fn west_wall(&self, row: usize, col: usize) -> bool {
+        let left = self.in_maze(row, col.wrapping_sub(1));
+        let here = self.in_maze(row, col);
+        let open = (here && self.cells[row * self.width + col].contains(Cell::WEST))
+            || (left && self.cells[row * self.width + col - 1].contains(Cell::EAST));
+        (left || here) && !open
+    }
+
+    /// to_unicode renders the maze like Display, but with box drawing characters
+    /// instead of "+", "-" and "|".
+    ///
+    /// The character at each corner is picked by looking at which of the four walls
+    /// that meet there are present.
+    pub fn to_unicode(&self) -> String {
+        let mut output = String::new();
+
+        for row in 0..=self.height {
+            for col in 0..=self.width {
+                let mut arms = 0;
+                if row > 0 && self.west_wall(row - 1, col) {
+                    arms |= 1;
+                }
+                if col < self.width && self.north_wall(row, col) {
+                    arms |= 2;
+                }
+                if row < self.height && self.west_wall(row, col) {
+                    arms |= 4;
+                }
+                if col > 0 && self.north_wall(row, col - 1) {
+                    arms |= 8;
+                }
+                output.push(JUNCTIONS[arms]);
+
+                if col < self.width {
+                    output.push_str(if self.north_wall(row, col) {
+                        "───"
+                    } else {
+                        "   "
+                    });
+                }
+            }
+            output.push('\n');
+
+            if row == self.height {
+                break;
+            }
+
+            for col in 0..=self.width {
+                output.push(if self.west_wall(row, col) { '│' } else { ' ' });
+                if col < self.width {
+                    output.push_str("   ");
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// to_string_scaled renders the maze as text at most max_cols characters wide, picking
+    /// the most detailed rendering that fits. That's Display if there's room for it.
+    /// Otherwise every cell, wall and corner gets one character, a block for walls and
+    /// a space for passages. Failing that, it's to_compact.
+    ///
+    /// If even that doesn't fit, it's returned anyway.
+    pub fn to_string_scaled(&self, max_cols: usize) -> String {
+        if 4 * self.width < max_cols {
+            return self.to_string();
+        }
+        if 2 * self.width >= max_cols {
+            return self.to_compact();
+        }
+
+        let mut output = String::new();
+        for line in self.wall_grid() {
+            output.extend(line.iter().map(|&wall| if wall { '█' } else { ' ' }));
+            output.push('\n');
+        }
+        output
+    }
+
+    /// to_ansi renders the maze for a terminal with ANSI escape codes, in colored blocks
+    /// two characters wide for every cell, wall and corner. Walls are black and passages white.
+    ///
+    /// With a root, each cell is instead colored by its distance from root, like
+    /// to_image_colored, going from blue for the closest cells to red for the farthest.
+    /// Passages between cells take the color of the closer one. Cells that can't be
+    /// reached from root are left white. Masked off cells are left blank.
+    ///
+    /// The colors are 24 bit, which most terminals support.
+    pub fn to_ansi(&self, root: Option<usize>) -> String {
+        const WALL: [u8; 3] = [0, 0, 0];
+        const PASSAGE: [u8; 3] = [255, 255, 255];
+
+        let distances = root.map(|root| self.distances(root));
+        let max_distance = distances
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0);
+        // The cells on either side of a point of the wall grid, along one axis
+        let touching = |point: usize, len: usize| {
+            let cells = if point % 2 == 1 {
+                point / 2..point / 2 + 1
+            } else {
+                (point / 2).saturating_sub(1)..point / 2 + 1
+            };
+            cells.filter(move |&cell| cell < len)
+        };
+
+        let mut output = String::new();
+        for (row, line) in self.wall_grid().iter().enumerate() {
+            for (col, &wall) in line.iter().enumerate() {
+                let cells = touching(row, self.height)
+                    .flat_map(|r| touching(col, self.width).map(move |c| (r, c)))
+                    .filter(|&(r, c)| self.in_maze(r, c))
+                    .map(|(r, c)| r * self.width + c)
+                    .collect::<Vec<usize>>();
+
+                let color = if wall {
+                    WALL
+                } else if cells.is_empty() {
+                    output.push_str("\x1b[0m  ");
+                    continue;
+                } else {
+                    let closest = distances
+                        .as_ref()
+                        .and_then(|distances| cells.iter().filter_map(|&i| distances[i]).min());
+                    match closest {
+                        Some(distance) => draw::heat(distance, max_distance),
+                        None => PASSAGE,
+                    }
+                };
+                let [red, green, blue] = color;
+                output.push_str(&format!("\x1b[48;2;{};{};{}m  ", red, green, blue));
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        output
+    }
+
+    /// to_compact renders the maze as densely as text allows, for looking over large mazes.
+    /// Each character is a 2 by 2 square of quadrant blocks, and every cell, wall and corner
+    /// is one of the quadrants, so the maze is only one character wider than it is cells.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let maze = Grid::new(50, 50).to_compact();
+    /// assert_eq!(51, maze.lines().count());
+    /// assert!(maze.lines().all(|line| line.chars().count() == 51));
+    /// ```
+    pub fn to_compact(&self) -> String {
+        let walls = self.wall_grid();
+        let filled =
+            |row: usize, col: usize| walls.get(row).and_then(|line| line.get(col)) == Some(&true);
+
+        let mut output = String::new();
+        for row in (0..walls.len()).step_by(2) {
+            for col in (0..walls[row].len()).step_by(2) {
+                let quarters = filled(row, col) as usize
+                    | (filled(row, col + 1) as usize) << 1
+                    | (filled(row + 1, col) as usize) << 2
+                    | (filled(row + 1, col + 1) as usize) << 3;
+                output.push(QUADRANTS[quarters]);
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// to_wall_grid expands the maze into tiles, the way games and roguelikes usually
+    /// store them: a (2 * height + 1) x (2 * width + 1) grid, row by row, where true is a
+    /// wall and false is open. Each cell is a tile at an odd row and column, with a tile
+    /// for the wall between it and each neighbor, and a tile for each corner in between.
+    ///
+    /// Corners are always walls, and so are masked off cells and anything between them.
+    /// Openings in the outer wall are left open.
+    ///
+    /// ```
+    /// use minotaur::Grid;
+    ///
+    /// let mut grid = Grid::new(2, 1);
+    /// grid.link(0, 1).unwrap();
+    /// let tiles = grid.to_wall_grid();
+    /// assert_eq!(vec![true; 5], tiles[0]);
+    /// assert_eq!(vec![true, false, false, false, true], tiles[1]);
+    /// assert_eq!(vec![true; 5], tiles[2]);
+    /// ```
+    pub fn to_wall_grid(&self) -> Vec<Vec<bool>> {
+        let mut walls = self.wall_grid();
+        for (row, line) in walls.iter_mut().enumerate() {
+            for (col, wall) in line.iter_mut().enumerate() {
+                // The cells either side of a wall, or the cell itself
+                let (above, left) = (row.wrapping_sub(1) / 2, col.wrapping_sub(1) / 2);
+                let in_maze = match (row % 2, col % 2) {
+                    (0, 0) => false,
+                    (1, 1) => self.in_maze(row / 2, col / 2),
+                    (0, _) => self.in_maze(above, col / 2) || self.in_maze(row / 2, col / 2),
+                    _ => self.in_maze(row / 2, left) || self.in_maze(row / 2, col / 2),
+                };
+                *wall |= !in_maze;
+            }
+        }
+        walls
+    }
+
+    // Whether there is a wall at each point of a grid with a point for every cell,
+    // and a point for every wall and corner around them. Cells are at odd rows and columns.
+    fn wall_grid(&self) -> Vec<Vec<bool>> {
+        let mut walls = vec![vec![false; 2 * self.width + 1]; 2 * self.height + 1];
+        for row in 0..=self.height {
+            for col in 0..=self.width {
+                if col < self.width {
+                    walls[2 * row][2 * col + 1] = self.north_wall(row, col);
+                }
+                if row < self.height {
+                    walls[2 * row + 1][2 * col] = self.west_wall(row, col);
+                }
+            }
+        }
+
+        // A corner is a wall if any wall meets it
+        for row in (0..walls.len()).step_by(2) {
+            for col in (0..walls[row].len()).step_by(2) {
+                walls[row][col] = (row > 0 && walls[row - 1][col])
+                    || (row + 1 < walls.len() && walls[row + 1][col])
+                    || (col > 0 && walls[row][col - 1])
+                    || (col + 1 < walls[row].len() && walls[row][col + 1]);
+            }
+        }
+        walls
+    }
+}
+
+impl Grid {
+    /// from_ascii reads a maze in the format written by Display, such as:
+    ///
+    /// ```text
+    /// +---+---+
+    /// |       |
+    /// +   +---+
+    /// |       |
+    /// +---+---+
+    /// ```
+    ///
+    /// The size of the maze comes from the size of the text. Walls are shared between
+    /// neighbors, so a gap links the cells on both sides of it. A gap in the outer border
+    /// is an opening, like add_opening makes. Whatever is inside the cells, such as the
+    /// path drawn by to_string_with_path, is ignored, and masks aren't recovered.
+    pub fn from_ascii(s: &str) -> Result<Grid, ParseError> {
+        let lines = s.lines().map(|line| line.chars().collect::<Vec<char>>());
+        let lines = lines.collect::<Vec<Vec<char>>>();
+
+        let line_length = lines.first().map_or(0, |line| line.len());
+        if lines.len() % 2 == 0 || line_length % 4 != 1 {
+            return Err(ParseError::WrongShape {
+                line: lines.len().max(1),
+            });
+        }
+        if let Some(line) = lines.iter().position(|line| line.len() != line_length) {
+            return Err(ParseError::WrongShape { line: line + 1 });
+        }
+
+        let width = line_length / 4;
+        let height = lines.len() / 2;
+        let mut grid = Grid::new(width, height);
+
+        // Whether the text at (line, column) is a wall, a gap, or neither
+        let is_wall = |line: usize, column: usize, wall: &str| {
+            let text = &lines[line][column..column + wall.len()];
+            if text.iter().copied().eq(wall.chars()) {
+                Ok(true)
+            } else if text.iter().all(|&c| c == ' ') {
+                Ok(false)
+            } else {
+                // Point at a character that's neither, or else where a partial wall stops
+                let wall = wall.chars().collect::<Vec<char>>();
+                let offset = (0..text.len())
+                    .find(|&i| text[i] != ' ' && text[i] != wall[i])
+                    .or_else(|| (0..text.len()).find(|&i| text[i] != wall[i]))
+                    .unwrap();
+                Err(ParseError::UnexpectedCharacter {
+                    line: line + 1,
+                    column: column + offset + 1,
+                    found: text[offset],
+                })
+            }
+        };
+
+        for (line, text) in lines.iter().enumerate() {
+            for column in (0..line_length).step_by(4) {
+                // Corners separate the walls, and can't be anything else
+                if line % 2 == 0 && text[column] != '+' && text[column] != ' ' {
+                    return Err(ParseError::UnexpectedCharacter {
+                        line: line + 1,
+                        column: column + 1,
+                        found: text[column],
+                    });
+                }
+            }
+        }
+
+        for row in 0..=height {
+            for col in 0..=width {
+                // The wall along the north side of (row, col), which may be the south border
+                if col < width && !is_wall(2 * row, 4 * col + 1, "---")? {
+                    if row < height {
+                        grid.cells[row * width + col] |= Cell::NORTH;
+                    }
+                    if row > 0 {
+                        grid.cells[(row - 1) * width + col] |= Cell::SOUTH;
+                    }
+                }
+                // The wall along the west side of (row, col), which may be the east border
+                if row < height && !is_wall(2 * row + 1, 4 * col, "|")? {
+                    if col < width {
+                        grid.cells[row * width + col] |= Cell::WEST;
+                    }
+                    if col > 0 {
+                        grid.cells[row * width + col - 1] |= Cell::EAST;
+                    }
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+// Centers label in width characters, cutting off its ends if it's too long.
+// Odd leftover characters go on the right.
+fn fit_label(label: &str, width: usize) -> String {
+    let chars: Vec<char> = label.chars().collect();
+    if chars.len() > width {
+        let start = (chars.len() - width) / 2;
+        chars[start..start + width].iter().collect()
+    } else {
+        let left = (width - chars.len()) / 2;
+        let right = width - chars.len() - left;
+        format!("{}{}{}", " ".repeat(left), label, " ".repeat(right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{Direction, Mask};
+
+    #[test]
+    fn test_to_unicode() {
+        assert_eq!("┌───┐\n│   │\n└───┘\n", Grid::new(1, 1).to_unicode());
+
+        // A single row is always a straight corridor
+        let mut grid = Grid::new(2, 1);
+        grid.binary_tree(None);
+        assert_eq!("┌───────┐\n│       │\n└───────┘\n", grid.to_unicode());
+
+        let mut grid = Grid::new(2, 2);
+        grid.link_cells(0, Direction::East);
+        grid.link_cells(0, Direction::South);
+        grid.link_cells(1, Direction::South);
+        let expected = "┌───────┐\n│       │\n│   ╷   │\n│   │   │\n└───┴───┘\n";
+        assert_eq!(expected, grid.to_unicode());
+    }
+
+    #[test]
+    fn test_to_string_scaled() {
+        let mut grid = Grid::new(2, 2);
+        grid.link_cells(0, Direction::East);
+        grid.link_cells(0, Direction::South);
+        grid.link_cells(1, Direction::South);
+
+        assert_eq!(grid.to_string(), grid.to_string_scaled(9));
+        let expected = "█████\n█   █\n█ █ █\n█ █ █\n█████\n";
+        assert_eq!(expected, grid.to_string_scaled(8));
+        assert_eq!("▛▀▌\n▌▌▌\n▀▀▘\n", grid.to_string_scaled(4));
+        // Too small to fit at all
+        assert_eq!("▛▀▌\n▌▌▌\n▀▀▘\n", grid.to_string_scaled(1));
+
+        let mut grid = Grid::new(100, 10);
+        grid.recursive_backtracker(None);
+        let scaled = grid.to_string_scaled(120);
+        assert!(scaled.lines().all(|line| line.chars().count() == 101));
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        const WALL: &str = "\x1b[48;2;0;0;0m  ";
+        const PASSAGE: &str = "\x1b[48;2;255;255;255m  ";
+        let mut grid = Grid::new(2, 1);
+        grid.link(0, 1).unwrap();
+        let line = |points: &[&str]| points.concat() + "\x1b[0m\n";
+        let border = line(&[WALL; 5]);
+        let expected = border.clone() + &line(&[WALL, PASSAGE, PASSAGE, PASSAGE, WALL]) + &border;
+        assert_eq!(expected, grid.to_ansi(None));
+
+        // Blue at the root, red at the far end, and the passage between them is as close as the root
+        const BLUE: &str = "\x1b[48;2;0;0;255m  ";
+        const RED: &str = "\x1b[48;2;255;0;0m  ";
+        let expected = border.clone() + &line(&[WALL, BLUE, BLUE, RED, WALL]) + &border;
+        assert_eq!(expected, grid.to_ansi(Some(0)));
+
+        // Masked off cells are blank, and cells cut off from the root are white
+        let mut mask = Mask::new(3, 1);
+        mask.set(2, false);
+        let mut grid = Grid::with_mask(3, 1, mask);
+        const BLANK: &str = "\x1b[0m  ";
+        let expected = line(&[WALL, WALL, WALL, WALL, WALL, BLANK, BLANK])
+            + &line(&[WALL, BLUE, WALL, PASSAGE, WALL, BLANK, BLANK])
+            + &line(&[WALL, WALL, WALL, WALL, WALL, BLANK, BLANK]);
+        assert_eq!(expected, grid.to_ansi(Some(0)));
+        grid.link(0, 1).unwrap();
+        assert!(grid.to_ansi(Some(0)).contains(RED));
+    }
+
+    #[test]
+    fn test_to_wall_grid() {
+        let mut mask = Mask::new(7, 5);
+        mask.set(10, false);
+        mask.set(11, false);
+        let mut grid = Grid::with_mask(7, 5, mask);
+        grid.recursive_backtracker(Some(4));
+        grid.carve_room(grid.index(2, 3), 3, 3);
+
+        let tiles = grid.to_wall_grid();
+        assert_eq!(11, tiles.len());
+        assert!(tiles.iter().all(|line| line.len() == 15));
+        // The border is all walls, and so is every corner, even inside the room
+        assert!(tiles[0].iter().chain(tiles[10].iter()).all(|&wall| wall));
+        assert!(tiles.iter().all(|line| line[0] && line[14]));
+        for row in (0..11).step_by(2) {
+            assert!((0..15).step_by(2).all(|col| tiles[row][col]));
+        }
+        // Cells are open unless they're masked off, and so is the wall between the masked cells
+        assert!(tiles[3][7] && tiles[3][9] && tiles[3][8]);
+        assert!(!tiles[1][1] && !tiles[3][5]);
+
+        // Walls follow the passages
+        for i in (0..35).filter(|&i| !grid.is_masked(i)) {
+            let (row, col) = (2 * (i / 7) + 1, 2 * (i % 7) + 1);
+            assert_eq!(!grid.cells[i].contains(Cell::EAST), tiles[row][col + 1]);
+            assert_eq!(!grid.cells[i].contains(Cell::SOUTH), tiles[row + 1][col]);
+        }
+
+        // Openings are gaps in the border
+        grid.carve_entrance_exit();
+        let tiles = grid.to_wall_grid();
+        let border = tiles[0].iter().chain(tiles[10].iter());
+        let border = border.chain(tiles.iter().flat_map(|line| vec![&line[0], &line[14]]));
+        assert_eq!(2, border.filter(|&&wall| !wall).count());
+    }
+
+    #[test]
+    fn test_render_ascii_with_labels() {
+        let mut grid = Grid::new(4, 3);
+        grid.sidewinder(Some(3));
+        let labels: Vec<String> = grid
+            .distances(0)
+            .iter()
+            .map(|distance| distance.unwrap().to_string())
+            .collect();
+
+        let text = grid.render_ascii_with_labels(&labels);
+        // Only the insides of the cells change
+        let blank: Vec<String> = vec![String::new(); 12];
+        assert_eq!(grid.to_string(), grid.render_ascii_with_labels(&blank));
+        let plain = grid.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        for (row, (line, expected)) in lines.iter().zip(plain.lines()).enumerate() {
+            if row % 2 == 0 {
+                assert_eq!(expected, *line);
+            }
+        }
+        assert!(lines[1].starts_with("| 0 "));
+        for (i, label) in labels.iter().enumerate() {
+            let (row, col) = grid.coords(i);
+            let body = &lines[2 * row + 1][4 * col + 1..4 * col + 4];
+            assert_eq!(label, body.trim());
+        }
+
+        assert_eq!("12 ", fit_label("12", 3));
+        assert_eq!("234", fit_label("12345", 3));
+        assert_eq!("123", fit_label("1234", 3));
+        assert_eq!("   ", fit_label("", 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 4 labels, one for each cell, but found 3")]
+    fn test_render_ascii_with_labels_wrong_count() {
+        let labels = vec!["1".to_string(); 3];
+        Grid::new(2, 2).render_ascii_with_labels(&labels);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut grid = Grid::new(9, 4);
+        grid.sidewinder(Some(6));
+
+        let charset = AsciiCharset {
+            cell_width: 1,
+            ..AsciiCharset::default()
+        };
+        let text = grid.render_ascii(&charset);
+        assert_eq!(9, text.lines().count());
+        assert!(text.lines().all(|line| line.len() == 19));
+        assert_eq!("+-+-+-+-+-+-+-+-+-+", text.lines().next().unwrap());
+
+        // Wider cells, and walls drawn in other characters
+        let charset = AsciiCharset {
+            horizontal_wall: '~',
+            vertical_wall: '!',
+            corner: 'o',
+            cell_width: 5,
+        };
+        let text = grid.render_ascii(&charset);
+        assert_eq!(9, text.lines().count());
+        assert!(text.lines().all(|line| line.len() == 55));
+
+        // The same maze, whatever it's drawn with
+        let charset = AsciiCharset {
+            cell_width: 3,
+            ..charset
+        };
+        let text = grid.render_ascii(&charset);
+        let restyled = text.replace('~', "-").replace('!', "|").replace('o', "+");
+        assert_eq!(grid.to_string(), restyled);
+    }
+
+    #[test]
+    fn test_to_compact() {
+        let mut grid = Grid::new(5, 5);
+        grid.binary_tree(Some(12_345_678));
+        let expected = "▛▀▀▀▀▌\n▛▀▀▘▌▌\n▛▘▌▛▘▌\n▛▀▀▘▌▌\n▌▛▀▘▌▌\n▀▀▀▀▀▘\n";
+        assert_eq!(expected, grid.to_compact());
+    }
+
+    #[test]
+    fn test_to_unicode_masked() {
+        let mut mask = crate::Mask::new(2, 2);
+        mask.set(3, false);
+        let grid = Grid::with_mask(2, 2, mask);
+        let expected = "┌───┬───┐\n│   │   │\n├───┼───┘\n│   │    \n└───┘    \n";
+        assert_eq!(expected, grid.to_unicode());
+    }
+
+    #[test]
+    fn test_from_ascii() {
+        let mut grid = Grid::new(7, 5);
+        grid.recursive_backtracker(None);
+        assert_eq!(Ok(&grid), Grid::from_ascii(&grid.to_string()).as_ref());
+
+        grid.carve_entrance_exit();
+        assert_eq!(Ok(&grid), Grid::from_ascii(&grid.to_string()).as_ref());
+
+        let path = grid.solve(0, 34).unwrap();
+        let text = grid.to_string_with_path(&path);
+        assert_eq!(Ok(&grid), Grid::from_ascii(&text).as_ref());
+
+        assert_eq!(Ok(Grid::new(0, 0)), Grid::from_ascii("+\n"));
+    }
+
+    #[test]
+    fn test_from_ascii_errors() {
+        assert_eq!(
+            Err(ParseError::WrongShape { line: 1 }),
+            Grid::from_ascii("")
+        );
+        assert_eq!(
+            Err(ParseError::WrongShape { line: 2 }),
+            Grid::from_ascii("+---+\n|  |\n+---+\n")
+        );
+        assert_eq!(
+            Err(ParseError::UnexpectedCharacter {
+                line: 3,
+                column: 3,
+                found: '='
+            }),
+            Grid::from_ascii("+---+\n|   |\n+-=-+\n")
+        );
+        assert_eq!(
+            Err(ParseError::UnexpectedCharacter {
+                line: 1,
+                column: 5,
+                found: '*'
+            }),
+            Grid::from_ascii("+---*\n|   |\n+---+\n")
+        );
+        assert_eq!(
+            Err(ParseError::UnexpectedCharacter {
+                line: 1,
+                column: 4,
+                found: ' '
+            }),
+            Grid::from_ascii("+-- +\n|   |\n+---+\n")
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Cell, Direction, Grid, Mask};
+
+impl Grid {
+    /// rotated makes a copy of the maze turned a quarter turn clockwise.
+    /// The width and height swap over, and a passage NORTH becomes a passage EAST,
+    /// EAST becomes SOUTH, and so on. The mask, weights and wrapping are turned with it.
+    pub fn rotated(&self) -> Grid {
+        let mut grid = self.transformed(
+            self.height,
+            self.width,
+            |row, col| (col, self.height - 1 - row),
+            |direction| match direction {
+                Direction::North => Direction::East,
+                Direction::East => Direction::South,
+                Direction::South => Direction::West,
+                Direction::West => Direction::North,
+            },
+        );
+        grid.wrap_horizontal = self.wrap_vertical;
+        grid.wrap_vertical = self.wrap_horizontal;
+        grid
+    }
+
+    /// flipped_horizontal makes a mirror image copy of the maze, with east and west swapped over.
+    pub fn flipped_horizontal(&self) -> Grid {
+        self.transformed(
+            self.width,
+            self.height,
+            |row, col| (row, self.width - 1 - col),
+            |direction| match direction {
+                Direction::East | Direction::West => direction.opposite(),
+                Direction::North | Direction::South => direction,
+            },
+        )
+    }
+
+    /// flipped_vertical makes an upside down copy of the maze, with north and south swapped over.
+    pub fn flipped_vertical(&self) -> Grid {
+        self.transformed(
+            self.width,
+            self.height,
+            |row, col| (self.height - 1 - row, col),
+            |direction| match direction {
+                Direction::North | Direction::South => direction.opposite(),
+                Direction::East | Direction::West => direction,
+            },
+        )
+    }
+
+    // Copies the maze into a new width x height grid, moving the cell at (row, col)
+    // to position(row, col) and turning each of its passages with turn.
+    fn transformed(
+        &self,
+        width: usize,
+        height: usize,
+        position: impl Fn(usize, usize) -> (usize, usize),
+        turn: impl Fn(Direction) -> Direction,
+    ) -> Grid {
+        let mut grid = Grid::new(width, height);
+        let mut on = vec![true; width * height];
+        let mut weights = vec![1; width * height];
+
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let (row, col) = position(i / self.width, i % self.width);
+            let moved = row * width + col;
+
+            grid.cells[moved] = cell & Cell::UNDER;
+            for &direction in Direction::all().iter() {
+                if cell.contains(direction.to_flag()) {
+                    grid.cells[moved] |= turn(direction).to_flag();
+                }
+            }
+            on[moved] = !self.is_masked(i);
+            weights[moved] = self.weight(i);
+        }
+
+        if self.mask.is_some() {
+            grid.mask = Some(Mask::from_vec(width, height, on));
+        }
+        if self.weights.is_some() {
+            grid.weights = Some(weights);
+        }
+        grid.wrap_horizontal = self.wrap_horizontal;
+        grid.wrap_vertical = self.wrap_vertical;
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rotated() {
+        // A passage from the top left corner to the cell below it
+        let mut grid = Grid::new(3, 2);
+        grid.link(0, 3).unwrap();
+        grid.add_opening(5, Cell::EAST);
+
+        let rotated = grid.rotated();
+        assert_eq!((2, 3), (rotated.width, rotated.height));
+        // The top left corner is now the top right, and the passage runs west from it
+        assert_eq!(Cell::WEST, rotated.cells[1]);
+        assert_eq!(Cell::EAST, rotated.cells[0]);
+        // The opening on the east side of the bottom right corner faces south
+        assert_eq!(Cell::SOUTH, rotated.cells[4]);
+        assert_eq!(Ok(()), rotated.validate());
+
+        // Four quarter turns make a full turn
+        let turned = grid.rotated().rotated().rotated().rotated();
+        assert_eq!(grid, turned);
+    }
+
+    #[test]
+    fn test_rotated_perfect() {
+        let mut grid = Grid::new(9, 6);
+        grid.set_wrap_horizontal(true);
+        grid.set_weight(4, 7);
+        grid.weave(Some(8), 0.5);
+
+        let rotated = grid.rotated();
+        assert_eq!(Ok(()), rotated.validate());
+        assert!(rotated.is_connected());
+        let passages = rotated.to_adjacency().iter().map(Vec::len).sum::<usize>();
+        assert_eq!(2 * 53, passages);
+        assert!(rotated.wraps_vertically() && !rotated.wraps_horizontally());
+        assert_eq!(7, rotated.weight(rotated.index(4, 5)));
+
+        // The longest path is just as long, however the maze is turned
+        let longest = grid.longest_path().len();
+        assert_eq!(longest, rotated.longest_path().len());
+        assert_eq!(longest, grid.flipped_horizontal().longest_path().len());
+        assert_eq!(longest, grid.flipped_vertical().longest_path().len());
+    }
+
+    #[test]
+    fn test_flipped() {
+        let mut mask = Mask::new(5, 4);
+        mask.set(0, false);
+        let mut grid = Grid::with_mask(5, 4, mask);
+        grid.recursive_backtracker(Some(6));
+
+        let flipped = grid.flipped_horizontal();
+        assert_eq!(Ok(()), flipped.validate());
+        assert!(flipped.is_masked(4));
+        assert_eq!(grid, flipped.flipped_horizontal());
+
+        let flipped = grid.flipped_vertical();
+        assert_eq!(Ok(()), flipped.validate());
+        assert!(flipped.is_masked(15));
+        assert_eq!(grid, flipped.flipped_vertical());
+
+        // Flipping both ways is half a turn
+        assert_eq!(
+            grid.rotated().rotated(),
+            grid.flipped_horizontal().flipped_vertical()
+        );
+    }
+}
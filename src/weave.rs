@@ -0,0 +1,221 @@
+use crate::{Cell, Direction, Grid};
+
+use image::RgbImage;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+impl Grid {
+    /// weave populates the maze like recursive_backtracker, but lets passages cross over
+    /// and under each other.
+    ///
+    /// When the walk is next to a straight passage running across its way, with an
+    /// unvisited cell on the far side, it can tunnel beneath the passage to that cell.
+    /// If there are any such tunnels, it takes one with probability weave_density,
+    /// otherwise it carries on to an unvisited neighbor as usual.
+    ///
+    /// The cell a tunnel goes beneath is marked UNDER. Following links, the maze is still perfect.
+    pub fn weave(&mut self, seed: Option<u64>, weave_density: f64) {
+        self.weave_with_rng(&mut Grid::get_rng(seed), weave_density);
+    }
+
+    /// weave_with_rng works like weave, but draws its random numbers from rng.
+    pub fn weave_with_rng<R: Rng>(&mut self, rng: &mut R, weave_density: f64) {
+        self.reset();
+
+        let mut visited: Vec<bool> = (0..self.cells.len()).map(|i| self.is_masked(i)).collect();
+        let unmasked: Vec<usize> = (0..self.cells.len()).filter(|&i| !visited[i]).collect();
+        let start = match unmasked.choose(rng) {
+            Some(&start) => start,
+            None => return,
+        };
+        visited[start] = true;
+        let mut stack = vec![start];
+
+        while let Some(&current) = stack.last() {
+            let mut steps = Vec::new();
+            let mut tunnels = Vec::new();
+            for &direction in Direction::all().iter() {
+                if !self.valid_direction(current, direction) {
+                    continue;
+                }
+                let neighbor = self.neighbor(current, direction);
+                if !visited[neighbor] {
+                    steps.push(direction);
+                } else if self.can_tunnel(neighbor, direction)
+                    && !visited[self.neighbor(neighbor, direction)]
+                {
+                    tunnels.push(direction);
+                }
+            }
+
+            let next = if !tunnels.is_empty() && rng.gen::<f64>() < weave_density {
+                let direction = *tunnels.choose(rng).unwrap();
+                let under = self.neighbor(current, direction);
+                let next = self.neighbor(under, direction);
+                self.cells[current] |= direction.to_flag();
+                self.cells[under] |= Cell::UNDER;
+                self.cells[next] |= direction.opposite().to_flag();
+                next
+            } else if let Some(&direction) = steps.choose(rng) {
+                self.link_cells(current, direction);
+                self.neighbor(current, direction)
+            } else {
+                stack.pop();
+                continue;
+            };
+            visited[next] = true;
+            stack.push(next);
+        }
+    }
+
+    // A passage heading in direction can tunnel beneath cell if the cell is a straight
+    // passage at right angles to it, with another cell on the far side.
+    fn can_tunnel(&self, cell: usize, direction: Direction) -> bool {
+        let across = match direction {
+            Direction::North | Direction::South => Cell::EAST | Cell::WEST,
+            Direction::East | Direction::West => Cell::NORTH | Cell::SOUTH,
+        };
+        self.cells[cell] == across && self.valid_direction(cell, direction)
+    }
+
+    // Draws a cell that has a passage beneath it, at x, y in image.
+    // The passage on top is a narrower bridge, with rails on either side,
+    // and the passage beneath shows through on both sides of the bridge.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_bridge(
+        &self,
+        image: &mut RgbImage,
+        cell: Cell,
+        (x, y): (usize, usize),
+        cell_width: usize,
+        cell_height: usize,
+        wall_size: usize,
+        wall_pixel: image::Rgb<u8>,
+    ) {
+        let mut fill = |x: usize, y: usize, width: usize, height: usize| {
+            for x in x..x + width {
+                for y in y..y + height {
+                    image.put_pixel(x as u32, y as u32, wall_pixel);
+                }
+            }
+        };
+
+        if cell.contains(Cell::NORTH) {
+            let (left, right) = (x + cell_width / 4, x + cell_width * 3 / 4);
+            for &rail in &[left, right] {
+                fill(rail, y, wall_size, cell_height + wall_size);
+            }
+            // Join the rails to the walls of the passage to the north and south
+            for &edge in &[y, y + cell_height] {
+                fill(x, edge, left - x, wall_size);
+                fill(right, edge, x + cell_width - right + wall_size, wall_size);
+            }
+        } else {
+            let (top, bottom) = (y + cell_height / 4, y + cell_height * 3 / 4);
+            for &rail in &[top, bottom] {
+                fill(x, rail, cell_width + wall_size, wall_size);
+            }
+            // Join the rails to the walls of the passage to the east and west
+            for &edge in &[x, x + cell_width] {
+                fill(edge, y, wall_size, top - y);
+                fill(
+                    edge,
+                    bottom,
+                    wall_size,
+                    y + cell_height - bottom + wall_size,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{GridError, Mask};
+
+    #[test]
+    fn test_weave() {
+        let mut grid = Grid::new(20, 20);
+        grid.weave(Some(4), 0.5);
+        assert!(grid.is_perfect());
+        assert_eq!(Ok(()), grid.validate());
+        assert!(grid.cells.iter().any(|cell| cell.contains(Cell::UNDER)));
+
+        let mut again = Grid::new(20, 20);
+        again.weave(Some(4), 0.5);
+        assert_eq!(grid, again);
+
+        // Without weaving, it's an ordinary maze
+        for _ in 0..100 {
+            grid.weave(None, 0.0);
+            assert!(grid.is_perfect());
+            assert!(grid.cells.iter().all(|cell| !cell.contains(Cell::UNDER)));
+        }
+        for _ in 0..100 {
+            grid.weave(None, 1.0);
+            assert!(grid.is_perfect());
+            assert_eq!(Ok(()), grid.validate());
+        }
+
+        // Tunnels inside a room open into it
+        grid.carve_room(grid.index(5, 5), 10, 10);
+        assert_eq!(Ok(()), grid.validate());
+        assert!(grid.distances(0).iter().all(|distance| distance.is_some()));
+    }
+
+    #[test]
+    fn test_weave_masked() {
+        let mut mask = Mask::new(10, 10);
+        for i in 40..46 {
+            mask.set(i, false);
+        }
+        let mut grid = Grid::with_mask(10, 10, mask);
+        for _ in 0..100 {
+            grid.weave(None, 1.0);
+            assert!(grid.is_perfect());
+            assert_eq!(Ok(()), grid.validate());
+        }
+    }
+
+    #[test]
+    fn test_tunnel() {
+        // A passage from west to east, tunneling under one from north to south
+        let mut grid = Grid::new(3, 3);
+        grid.cells[1] = Cell::SOUTH;
+        grid.cells[4] = Cell::NORTH | Cell::SOUTH | Cell::UNDER;
+        grid.cells[7] = Cell::NORTH;
+        grid.cells[3] = Cell::EAST;
+        grid.cells[5] = Cell::WEST;
+        assert_eq!(Ok(()), grid.validate());
+        assert_eq!(vec![5], grid.links(3).collect::<Vec<usize>>());
+        assert_eq!(vec![1, 7], grid.links(4).collect::<Vec<usize>>());
+        assert_eq!(Some(vec![3, 5]), grid.solve(3, 5));
+
+        grid.cells[4] = Cell::NORTH | Cell::EAST | Cell::UNDER;
+        assert_eq!(Err(GridError::InvalidCrossing { cell: 4 }), grid.validate());
+    }
+
+    #[test]
+    fn test_draw_bridge() {
+        let mut grid = Grid::new(3, 3);
+        grid.cells[1] = Cell::SOUTH;
+        grid.cells[4] = Cell::NORTH | Cell::SOUTH | Cell::UNDER;
+        grid.cells[7] = Cell::NORTH;
+        grid.cells[3] = Cell::EAST;
+        grid.cells[5] = Cell::WEST;
+
+        let background = image::Rgb([255, 255, 255]);
+        let wall = image::Rgb([0, 0, 0]);
+        let image = grid.to_image(8, 1, background, wall);
+
+        // The rails of the bridge
+        assert_eq!(wall, *image.get_pixel(10, 12));
+        assert_eq!(wall, *image.get_pixel(14, 12));
+        // The passage beneath shows on both sides, and the bridge is open down the middle
+        assert_eq!(background, *image.get_pixel(8, 12));
+        assert_eq!(background, *image.get_pixel(16, 12));
+        assert_eq!(background, *image.get_pixel(12, 8));
+    }
+}